@@ -0,0 +1,107 @@
+//! Standard base64 (RFC 4648) with padding, as used for CosmWasm `Binary`
+
+use alloc::vec::Vec;
+
+const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Appends the padded encoding of `input` to `out`
+pub(crate) fn encode_into(input: &[u8], out: &mut Vec<u8>) {
+    out.reserve((input.len() + 2) / 3 * 4);
+    for chunk in input.chunks(3) {
+        let mut bytes = [0u8; 3];
+        bytes[..chunk.len()].copy_from_slice(chunk);
+        let bits = (bytes[0] as u32) << 16 | (bytes[1] as u32) << 8 | bytes[2] as u32;
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(ALPHABET[(bits >> (18 - 6 * i) & 0x3f) as usize]);
+            } else {
+                out.push(b'=');
+            }
+        }
+    }
+}
+
+/// Decodes `input`, or returns `None` if it isn't padded, canonical base64
+pub(crate) fn decode(input: &[u8]) -> Option<Vec<u8>> {
+    if input.len() % 4 != 0 {
+        return None;
+    }
+    let mut out = Vec::with_capacity(input.len() / 4 * 3);
+    let chunks = input.len() / 4;
+    for (i, chunk) in input.chunks(4).enumerate() {
+        let padding = if i + 1 == chunks {
+            chunk.iter().rev().take_while(|&&c| c == b'=').count()
+        } else {
+            0
+        };
+        if padding > 2 {
+            return None;
+        }
+        let mut bits: u32 = 0;
+        for &c in &chunk[..4 - padding] {
+            bits = bits << 6 | decode_char(c)? as u32;
+        }
+        bits <<= 6 * padding;
+        let bytes = [(bits >> 16) as u8, (bits >> 8) as u8, bits as u8];
+        // the bits left over before the padding must be zero
+        if bytes[3 - padding..].iter().any(|&b| b != 0) {
+            return None;
+        }
+        out.extend_from_slice(&bytes[..3 - padding]);
+    }
+    Some(out)
+}
+
+fn decode_char(c: u8) -> Option<u8> {
+    match c {
+        b'A'..=b'Z' => Some(c - b'A'),
+        b'a'..=b'z' => Some(c - b'a' + 26),
+        b'0'..=b'9' => Some(c - b'0' + 52),
+        b'+' => Some(62),
+        b'/' => Some(63),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decode, encode_into};
+
+    fn encode(input: &[u8]) -> String {
+        let mut out = Vec::new();
+        encode_into(input, &mut out);
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn encodes() {
+        assert_eq!(encode(b""), "");
+        assert_eq!(encode(b"f"), "Zg==");
+        assert_eq!(encode(b"fo"), "Zm8=");
+        assert_eq!(encode(b"foo"), "Zm9v");
+        assert_eq!(encode(b"foobar"), "Zm9vYmFy");
+        assert_eq!(encode(&[0xfb, 0xff]), "+/8=");
+        let bytes: Vec<u8> = (0..=255).collect();
+        assert_eq!(decode(encode(&bytes).as_bytes()), Some(bytes));
+    }
+
+    #[test]
+    fn decodes() {
+        assert_eq!(decode(b""), Some(vec![]));
+        assert_eq!(decode(b"Zg=="), Some(b"f".to_vec()));
+        assert_eq!(decode(b"Zm8="), Some(b"fo".to_vec()));
+        assert_eq!(decode(b"Zm9v"), Some(b"foo".to_vec()));
+        assert_eq!(decode(b"Zm9vYmFy"), Some(b"foobar".to_vec()));
+        assert_eq!(decode(b"+/8="), Some(vec![0xfb, 0xff]));
+    }
+
+    #[test]
+    fn rejects_invalid_input() {
+        assert_eq!(decode(b"Zg"), None);
+        assert_eq!(decode(b"Zg=a"), None);
+        assert_eq!(decode(b"Zg==Zg=="), None);
+        assert_eq!(decode(b"Z==="), None);
+        assert_eq!(decode(b"Zh=="), None);
+        assert_eq!(decode(b"Zm9-"), None);
+    }
+}
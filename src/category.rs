@@ -0,0 +1,18 @@
+/// Categorizes the cause of a [`de::Error`](crate::de::Error) or
+/// [`ser::Error`](crate::ser::Error)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Category {
+    /// Reading the input failed.
+    Io,
+
+    /// The input is not syntactically valid JSON.
+    Syntax,
+
+    /// The input is valid JSON but does not match the structure of the target type, or the value
+    /// could not be serialized.
+    Data,
+
+    /// The input ended before a JSON value was complete. Parsing a longer prefix of the same
+    /// stream may succeed.
+    Eof,
+}
@@ -0,0 +1,328 @@
+use core::marker::PhantomData;
+
+use serde::de;
+
+use crate::de::{Deserializer, Error, Options, Result};
+
+/// Returns an iterator that deserializes the elements of the JSON array in `input` one at a time
+///
+/// Unlike deserializing a `Vec<T>`, only one element is held in memory at a time, so arrays of
+/// any length can be processed. A syntax error in the array, or an element that fails to
+/// deserialize, is returned as the last item.
+///
+/// ```
+/// use serde_json_wasm::de::array_iter;
+///
+/// let total: u64 = array_iter::<u64>(b"[1, 2, 3]").map(Result::unwrap).sum();
+/// assert_eq!(total, 6);
+/// ```
+pub fn array_iter<'a, T>(input: &'a [u8]) -> ArrayIter<'a, T>
+where
+    T: de::Deserialize<'a>,
+{
+    ArrayIter::with_options(input, &Options::default())
+}
+
+/// Iterator over the elements of a top-level JSON array, created by [`array_iter`]
+pub struct ArrayIter<'a, T> {
+    de: Deserializer<'a>,
+    /// Number of elements returned so far, or `None` before the opening bracket was read
+    len: Option<usize>,
+    /// Set after the closing bracket or an error
+    done: bool,
+    /// Whether elements that fail to deserialize are skipped
+    recover: bool,
+    output: PhantomData<T>,
+}
+
+impl<'a, T> ArrayIter<'a, T>
+where
+    T: de::Deserialize<'a>,
+{
+    /// Creates an iterator over the elements of the array in `input`, which is parsed using
+    /// `options`
+    pub fn with_options(input: &'a [u8], options: &Options) -> Self {
+        ArrayIter {
+            de: Deserializer::with_options(input, options),
+            len: None,
+            done: false,
+            recover: false,
+            output: PhantomData,
+        }
+    }
+
+    /// Sets whether iteration continues after an element that fails to deserialize
+    ///
+    /// The error of such an element is returned in its place, and the element is skipped. This
+    /// is for ingesting third-party data with occasional malformed records. If the element isn't
+    /// valid JSON, its error is returned as the last item since the rest of the array cannot be
+    /// found.
+    ///
+    /// ```
+    /// use serde_json_wasm::de::{array_iter, Error, JsonType};
+    ///
+    /// let records: Vec<_> = array_iter::<u8>(br#"[1, "two", 3]"#)
+    ///     .recover_from_errors(true)
+    ///     .collect();
+    /// assert_eq!(records, [Ok(1), Err(Error::InvalidType(JsonType::String)), Ok(3)]);
+    /// ```
+    pub fn recover_from_errors(mut self, recover: bool) -> Self {
+        self.recover = recover;
+        self
+    }
+
+    /// Returns the number of bytes consumed so far
+    ///
+    /// After an error this is the position at which parsing failed.
+    pub fn byte_offset(&self) -> usize {
+        self.de.read.index
+    }
+
+    /// Consumes the opening bracket of the array
+    fn start(&mut self) -> Result<()> {
+        if let Some(max_input_len) = self.de.options.max_input_len {
+            if self.de.read.slice.len() > max_input_len {
+                return Err(Error::InputTooLong);
+            }
+        }
+        match self
+            .de
+            .parse_whitespace()
+            .ok_or(Error::EofWhileParsingValue)?
+        {
+            b'[' => {
+                self.de.enter_nested()?;
+                self.de.eat_char();
+                Ok(())
+            }
+            c => Err(Error::unexpected_byte(c)),
+        }
+    }
+
+    /// Parses the next element, or consumes the closing bracket and returns `None`. An element
+    /// that failed to deserialize but was skipped is returned as the inner error.
+    fn parse_element(&mut self, len: usize) -> Result<Option<Result<T>>> {
+        let mut c = self
+            .de
+            .parse_whitespace()
+            .ok_or(Error::EofWhileParsingList)?;
+        if len > 0 && c != b']' {
+            if c != b',' {
+                return Err(Error::ExpectedListCommaOrEnd);
+            }
+            self.de.eat_char();
+            c = self
+                .de
+                .parse_whitespace()
+                .ok_or(Error::EofWhileParsingValue)?;
+            if c == b']' && !self.de.options.allow_trailing_commas {
+                return Err(Error::TrailingComma);
+            }
+        }
+        if c == b']' {
+            self.de.eat_char();
+            self.de.leave_nested();
+            self.de.end()?;
+            return Ok(None);
+        }
+
+        if let Some(max_array_len) = self.de.options.max_array_len {
+            if len >= max_array_len {
+                return Err(Error::ArrayTooLong);
+            }
+        }
+        let start = self.de.read.index;
+        let remaining_depth = self.de.remaining_depth;
+        match de::Deserialize::deserialize(&mut self.de) {
+            Ok(element) => Ok(Some(Ok(element))),
+            Err(error) if self.recover => {
+                self.de.read.index = start;
+                self.de.remaining_depth = remaining_depth;
+                self.de.skip_value_checked(true)?;
+                Ok(Some(Err(error)))
+            }
+            Err(error) => Err(error),
+        }
+    }
+}
+
+impl<'a, T> Iterator for ArrayIter<'a, T>
+where
+    T: de::Deserialize<'a>,
+{
+    type Item = Result<T>;
+
+    fn next(&mut self) -> Option<Result<T>> {
+        if self.done {
+            return None;
+        }
+        let len = match self.len {
+            Some(len) => len,
+            None => {
+                if let Err(error) = self.start() {
+                    self.done = true;
+                    return Some(Err(error));
+                }
+                0
+            }
+        };
+        match self.parse_element(len) {
+            Ok(Some(element)) => {
+                self.len = Some(len + 1);
+                Some(element)
+            }
+            Ok(None) => {
+                self.done = true;
+                None
+            }
+            Err(error) => {
+                self.done = true;
+                Some(Err(error))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{array_iter, ArrayIter};
+    use crate::de::{Error, JsonType, Options};
+    use serde_derive::Deserialize;
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Transfer<'a> {
+        to: &'a str,
+        amount: u64,
+    }
+
+    #[test]
+    fn array_iter_elements() {
+        let input = br#" [{"to": "alice", "amount": 1}, {"to": "bob", "amount": 2}] "#;
+        let mut iter = array_iter::<Transfer<'_>>(input);
+        assert_eq!(
+            iter.next(),
+            Some(Ok(Transfer {
+                to: "alice",
+                amount: 1
+            }))
+        );
+        assert_eq!(
+            iter.next(),
+            Some(Ok(Transfer {
+                to: "bob",
+                amount: 2
+            }))
+        );
+        assert_eq!(iter.next(), None);
+        assert_eq!(iter.byte_offset(), input.len());
+        assert_eq!(iter.next(), None);
+
+        assert_eq!(array_iter::<u8>(b"[]").next(), None);
+        let values: Result<Vec<Vec<u8>>, _> = array_iter(b"[[1], [], [2, 3]]").collect();
+        assert_eq!(values, Ok(vec![vec![1], vec![], vec![2, 3]]));
+    }
+
+    #[test]
+    fn array_iter_errors() {
+        let errors = |input: &[u8]| {
+            array_iter::<u8>(input)
+                .filter_map(Result::err)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(errors(b""), [Error::EofWhileParsingValue]);
+        assert_eq!(errors(b"{}"), [Error::InvalidType(JsonType::Object)]);
+        assert_eq!(errors(b"[1 2]"), [Error::ExpectedListCommaOrEnd]);
+        assert_eq!(errors(b"[1,]"), [Error::TrailingComma]);
+        assert_eq!(errors(b"[,1]"), [Error::ExpectedSomeValue(b',')]);
+        assert_eq!(errors(b"[1"), [Error::EofWhileParsingList]);
+        assert_eq!(errors(b"[1] 2"), [Error::TrailingCharacters]);
+
+        // the elements before the error are returned
+        let mut iter = array_iter::<u8>(b"[1, true, 3]");
+        assert_eq!(iter.next(), Some(Ok(1)));
+        assert!(iter.next().unwrap().is_err());
+        assert_eq!(iter.next(), None);
+    }
+
+    #[test]
+    fn array_iter_recovery() {
+        let input = br#"[
+            {"to": "alice", "amount": 1},
+            {"to": "bob", "amount": "2"},
+            {"to": "carol", "extra": [[1]], "amount": 3, "memo": {"a": [1, "x"]}},
+            {"to": 4},
+            {"to": "dave", "amount": 5}
+        ]"#;
+        let records: Vec<_> = array_iter::<Transfer<'_>>(input)
+            .recover_from_errors(true)
+            .collect();
+        assert_eq!(records.len(), 5);
+        assert_eq!(
+            records[0],
+            Ok(Transfer {
+                to: "alice",
+                amount: 1
+            })
+        );
+        assert!(records[1].is_err());
+        assert_eq!(
+            records[2],
+            Ok(Transfer {
+                to: "carol",
+                amount: 3
+            })
+        );
+        assert!(records[3].is_err());
+        assert_eq!(
+            records[4],
+            Ok(Transfer {
+                to: "dave",
+                amount: 5
+            })
+        );
+
+        // the depth of failed elements is not counted against later ones
+        let options = Options::default().max_depth(3);
+        let records: Vec<_> = ArrayIter::<Vec<u8>>::with_options(b"[[[1]], [[2]], [3]]", &options)
+            .recover_from_errors(true)
+            .collect();
+        assert_eq!(
+            records,
+            [
+                Err(Error::InvalidType(JsonType::Array)),
+                Err(Error::InvalidType(JsonType::Array)),
+                Ok(vec![3])
+            ]
+        );
+
+        // syntax errors end the iteration
+        let records: Vec<_> = array_iter::<u8>(b"[1, true, [2 3], 4]")
+            .recover_from_errors(true)
+            .collect();
+        assert_eq!(
+            records,
+            [
+                Ok(1),
+                Err(Error::InvalidType(JsonType::Bool)),
+                Err(Error::ExpectedListCommaOrEnd)
+            ]
+        );
+        assert_eq!(
+            array_iter::<u8>(b"[1, 2] 3")
+                .recover_from_errors(true)
+                .collect::<Vec<_>>(),
+            [Ok(1), Ok(2), Err(Error::TrailingCharacters)]
+        );
+    }
+
+    #[test]
+    fn array_iter_options() {
+        let options = Options::default()
+            .allow_trailing_commas(true)
+            .max_array_len(2);
+        let values: Result<Vec<u8>, _> = ArrayIter::with_options(b"[1, 2,]", &options).collect();
+        assert_eq!(values, Ok(vec![1, 2]));
+        let values: Result<Vec<u8>, _> = ArrayIter::with_options(b"[1, 2, 3]", &options).collect();
+        assert_eq!(values, Err(Error::ArrayTooLong));
+    }
+}
@@ -0,0 +1,321 @@
+use core::future::Future;
+use core::pin::Pin;
+use core::task::{Context, Poll};
+
+use serde::de::{self, DeserializeOwned};
+
+use crate::de::push::FillBuffer;
+use crate::de::{Error, Feed, Options, PushDeserializer, Result};
+
+/// Size of the chunks read by [`FromAsyncReadFn`]
+const READ_CHUNK_SIZE: usize = 256;
+
+const POLLED_AFTER_COMPLETION: &str = "FromAsyncReadFn polled after completion";
+
+/// Deserializes an instance of type `T` from an `embedded_io_async::Read` source, reading into
+/// `buf`
+///
+/// Reading stops as soon as the value is complete, which doesn't require the source to end. The
+/// value is deserialized from `buf`, so borrowed fields point into it. It is returned together
+/// with the bytes that were read after the value, which belong to whatever follows it in the
+/// source.
+///
+/// A value that doesn't fit into `buf` fails with [`Error::InputTooLong`], and errors of the
+/// reader are reported as [`Error::Read`].
+pub async fn from_async_reader<'a, T, R>(reader: &mut R, buf: &'a mut [u8]) -> Result<(T, &'a [u8])>
+where
+    T: de::Deserialize<'a>,
+    R: embedded_io_async::Read,
+{
+    from_async_reader_with_options(reader, buf, &Options::default()).await
+}
+
+/// Deserializes an instance of type `T` from an `embedded_io_async::Read` source like
+/// [`from_async_reader`], using the given [`Options`]
+pub async fn from_async_reader_with_options<'a, T, R>(
+    reader: &mut R,
+    buf: &'a mut [u8],
+    options: &Options,
+) -> Result<(T, &'a [u8])>
+where
+    T: de::Deserialize<'a>,
+    R: embedded_io_async::Read,
+{
+    let mut fill = FillBuffer::new(buf, options);
+    let end = loop {
+        let len = reader
+            .read(fill.unfilled(buf)?)
+            .await
+            .map_err(|_| Error::Read)?;
+        if let Some(end) = fill.fill(buf, len) {
+            break end;
+        }
+    };
+    fill.deserialize(buf, end, options)
+}
+
+/// Returns a future that deserializes an instance of type `T` from an asynchronous byte source
+///
+/// `poll_read` has the shape of `futures_io::AsyncRead::poll_read`: it fills the start of the
+/// given buffer and returns the number of bytes written, with 0 meaning the end of the input.
+/// An `AsyncRead` is adapted with
+/// `from_async_read_fn(|cx, buf| Pin::new(&mut reader).poll_read(cx, buf))`. Errors of
+/// `poll_read` are reported as [`Error::Read`].
+///
+/// Like [`from_read_fn`](crate::de::from_read_fn), reading stops as soon as the value is complete
+/// and bytes after the value that were read as part of the last chunk are discarded.
+pub fn from_async_read_fn<T, F, E>(poll_read: F) -> FromAsyncReadFn<T, F>
+where
+    T: DeserializeOwned,
+    F: FnMut(&mut Context<'_>, &mut [u8]) -> Poll<core::result::Result<usize, E>>,
+{
+    from_async_read_fn_with_options(poll_read, &Options::default())
+}
+
+/// Returns a future that deserializes an instance of type `T` from an asynchronous byte source
+/// like [`from_async_read_fn`], using the given [`Options`]
+pub fn from_async_read_fn_with_options<T, F, E>(
+    poll_read: F,
+    options: &Options,
+) -> FromAsyncReadFn<T, F>
+where
+    T: DeserializeOwned,
+    F: FnMut(&mut Context<'_>, &mut [u8]) -> Poll<core::result::Result<usize, E>>,
+{
+    FromAsyncReadFn {
+        poll_read,
+        de: Some(PushDeserializer::with_options(options)),
+        chunk: [0u8; READ_CHUNK_SIZE],
+    }
+}
+
+/// Future returned by [`from_async_read_fn`]
+pub struct FromAsyncReadFn<T, F> {
+    poll_read: F,
+    /// `None` once the future completed
+    de: Option<PushDeserializer<T>>,
+    chunk: [u8; READ_CHUNK_SIZE],
+}
+
+// No field is ever pinned
+impl<T, F> Unpin for FromAsyncReadFn<T, F> {}
+
+impl<T, F, E> Future for FromAsyncReadFn<T, F>
+where
+    T: DeserializeOwned,
+    F: FnMut(&mut Context<'_>, &mut [u8]) -> Poll<core::result::Result<usize, E>>,
+{
+    type Output = Result<T>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<T>> {
+        let this = self.get_mut();
+        loop {
+            let len = match (this.poll_read)(cx, &mut this.chunk) {
+                Poll::Pending => return Poll::Pending,
+                Poll::Ready(Ok(len)) => len,
+                Poll::Ready(Err(_)) => {
+                    this.de = None;
+                    return Poll::Ready(Err(Error::Read));
+                }
+            };
+            if len == 0 {
+                let de = this.de.take().expect(POLLED_AFTER_COMPLETION);
+                let result = de
+                    .finish()
+                    .and_then(|v| v.ok_or(Error::EofWhileParsingValue));
+                return Poll::Ready(result);
+            }
+            let de = this.de.as_mut().expect(POLLED_AFTER_COMPLETION);
+            match de.push(&this.chunk[..len]) {
+                Ok(Feed::NeedMoreData) => {}
+                Ok(Feed::Value(value)) => {
+                    this.de = None;
+                    return Poll::Ready(Ok(value));
+                }
+                Err(e) => {
+                    this.de = None;
+                    return Poll::Ready(Err(e));
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        from_async_read_fn, from_async_read_fn_with_options, from_async_reader,
+        from_async_reader_with_options,
+    };
+    use crate::de::{Error, Options};
+    use core::future::Future;
+    use core::pin::Pin;
+    use core::task::{Context, Poll, RawWaker, RawWakerVTable, Waker};
+
+    /// Polls `future` until it completes, with a waker that does nothing
+    fn block_on<F: Future + Unpin>(mut future: F) -> F::Output {
+        fn raw_waker() -> RawWaker {
+            fn clone(_: *const ()) -> RawWaker {
+                raw_waker()
+            }
+            fn noop(_: *const ()) {}
+            static VTABLE: RawWakerVTable = RawWakerVTable::new(clone, noop, noop, noop);
+            RawWaker::new(core::ptr::null(), &VTABLE)
+        }
+        let waker = unsafe { Waker::from_raw(raw_waker()) };
+        let mut cx = Context::from_waker(&waker);
+        loop {
+            if let Poll::Ready(output) = Pin::new(&mut future).poll(&mut cx) {
+                return output;
+            }
+        }
+    }
+
+    /// Source that alternates between not being ready and returning up to 3 bytes
+    fn source(
+        input: &'static [u8],
+    ) -> impl FnMut(&mut Context<'_>, &mut [u8]) -> Poll<Result<usize, ()>> {
+        let mut rest = input;
+        let mut ready = false;
+        move |_cx, buf| {
+            ready = !ready;
+            if !ready {
+                return Poll::Pending;
+            }
+            let len = buf.len().min(3).min(rest.len());
+            buf[..len].copy_from_slice(&rest[..len]);
+            rest = &rest[len..];
+            Poll::Ready(Ok(len))
+        }
+    }
+
+    #[test]
+    fn async_read() {
+        assert_eq!(
+            block_on(from_async_read_fn(source(b"[1, 2, 3] [4]"))),
+            Ok(vec![1u8, 2, 3])
+        );
+        assert_eq!(block_on(from_async_read_fn(source(b"123"))), Ok(123u32));
+        assert_eq!(
+            block_on(from_async_read_fn::<Vec<u8>, _, _>(source(b"[1, 2"))),
+            Err(Error::EofWhileParsingList)
+        );
+        assert_eq!(
+            block_on(from_async_read_fn::<u8, _, _>(|_cx, _buf| Poll::Ready(
+                Err(())
+            ))),
+            Err(Error::Read)
+        );
+
+        let options = Options::default().max_input_len(8);
+        assert_eq!(
+            block_on(from_async_read_fn_with_options::<Vec<u8>, _, _>(
+                source(b"[1, 2, 3, 4]"),
+                &options
+            )),
+            Err(Error::InputTooLong)
+        );
+    }
+
+    /// Reader that is not ready the first time each read is polled and then returns up to 3 bytes
+    struct Socket<'a> {
+        input: &'a [u8],
+    }
+
+    impl embedded_io_async::ErrorType for Socket<'_> {
+        type Error = embedded_io_async::ErrorKind;
+    }
+
+    impl embedded_io_async::Read for Socket<'_> {
+        async fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+            NotReadyOnce(false).await;
+            let len = buf.len().min(3).min(self.input.len());
+            buf[..len].copy_from_slice(&self.input[..len]);
+            self.input = &self.input[len..];
+            Ok(len)
+        }
+    }
+
+    /// Future that returns `Poll::Pending` once before it completes
+    struct NotReadyOnce(bool);
+
+    impl Future for NotReadyOnce {
+        type Output = ();
+
+        fn poll(mut self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<()> {
+            if self.0 {
+                Poll::Ready(())
+            } else {
+                self.0 = true;
+                Poll::Pending
+            }
+        }
+    }
+
+    fn socket(input: &[u8]) -> Socket<'_> {
+        Socket { input }
+    }
+
+    #[test]
+    fn async_reader() {
+        #[derive(Debug, serde_derive::Deserialize, PartialEq)]
+        struct Coin<'a> {
+            denom: &'a str,
+            amount: u64,
+        }
+
+        let mut buf = [0u8; 64];
+        let mut reader = socket(br#"{"denom": "uatom", "amount": 1} {"denom""#);
+        assert_eq!(
+            block_on(Box::pin(from_async_reader(&mut reader, &mut buf))),
+            Ok((
+                Coin {
+                    denom: "uatom",
+                    amount: 1
+                },
+                &b" {"[..]
+            ))
+        );
+        // the rest of the source is left unread
+        assert_eq!(reader.input, br#""denom""#);
+
+        let mut buf = [0u8; 8];
+        assert_eq!(
+            block_on(Box::pin(from_async_reader(&mut socket(b"42"), &mut buf))),
+            Ok((42u8, &b""[..]))
+        );
+        assert_eq!(
+            block_on(Box::pin(from_async_reader::<Vec<u8>, _>(
+                &mut socket(b"[1, 2"),
+                &mut buf
+            ))),
+            Err(Error::EofWhileParsingList)
+        );
+        assert_eq!(
+            block_on(Box::pin(from_async_reader::<Vec<u8>, _>(
+                &mut socket(b"[1, 2, 3, 4]"),
+                &mut buf
+            ))),
+            Err(Error::InputTooLong)
+        );
+        assert_eq!(
+            block_on(Box::pin(from_async_reader_with_options::<Vec<u8>, _>(
+                &mut socket(b"[1, 2]"),
+                &mut buf,
+                &Options::default().max_input_len(4)
+            ))),
+            Err(Error::InputTooLong)
+        );
+
+        let options = Options::default().allow_byte_order_mark(true);
+        let mut buf = [0u8; 16];
+        assert_eq!(
+            block_on(Box::pin(from_async_reader_with_options::<Vec<u8>, _>(
+                &mut socket(b"\xEF\xBB\xBF[1,2] [3]"),
+                &mut buf,
+                &options
+            ))),
+            Ok((vec![1, 2], &b" "[..]))
+        );
+    }
+}
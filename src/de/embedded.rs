@@ -0,0 +1,156 @@
+use serde::de;
+
+use crate::de::read::BufferRead;
+use crate::de::{from_read_prefix, Error, Options, Result};
+
+/// Deserializes an instance of type `T` from an `embedded_io::Read` source, like a UART or flash,
+/// reading into `buf`
+///
+/// The value is parsed while it is read, and reading stops as soon as it is complete, which
+/// doesn't require the source to end. The input is kept in `buf`, so borrowed fields point into
+/// it. It is returned together
+/// with the bytes that were read after the value, which belong to whatever follows it in the
+/// source.
+///
+/// A value that doesn't fit into `buf` fails with [`Error::InputTooLong`], and errors of the
+/// reader are reported as [`Error::Read`]. Neither `std` nor an allocation for the input is
+/// needed.
+///
+/// ```
+/// use serde_json_wasm::de::from_embedded_io;
+///
+/// let mut uart: &[u8] = b"[1, 2] [3]";
+/// let mut buf = [0u8; 16];
+/// let (value, rest) = from_embedded_io::<Vec<u8>, _>(&mut uart, &mut buf).unwrap();
+/// assert_eq!(value, [1, 2]);
+/// assert_eq!(rest, b" [3]");
+/// ```
+pub fn from_embedded_io<'a, T, R>(reader: &mut R, buf: &'a mut [u8]) -> Result<(T, &'a [u8])>
+where
+    T: de::Deserialize<'a>,
+    R: embedded_io::Read,
+{
+    from_embedded_io_with_options(reader, buf, &Options::default())
+}
+
+/// Deserializes an instance of type `T` from an `embedded_io::Read` source like
+/// [`from_embedded_io`], using the given [`Options`]
+pub fn from_embedded_io_with_options<'a, T, R>(
+    reader: &mut R,
+    buf: &'a mut [u8],
+    options: &Options,
+) -> Result<(T, &'a [u8])>
+where
+    T: de::Deserialize<'a>,
+    R: embedded_io::Read,
+{
+    let read = |buf: &mut [u8]| reader.read(buf).map_err(|_| Error::Read);
+    let limit = options.max_input_len.unwrap_or(core::usize::MAX);
+    let (value, read) = from_read_prefix(BufferRead::new(buf, limit, read), options)?;
+    Ok((value, read.rest()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{from_embedded_io, from_embedded_io_with_options};
+    use crate::de::{Error, Options};
+    use serde_derive::Deserialize;
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Coin<'a> {
+        denom: &'a str,
+        amount: u64,
+    }
+
+    /// Source that returns up to 3 bytes per read and then fails if `fail` is set
+    struct Uart<'a> {
+        input: &'a [u8],
+        fail: bool,
+    }
+
+    impl embedded_io::ErrorType for Uart<'_> {
+        type Error = embedded_io::ErrorKind;
+    }
+
+    impl embedded_io::Read for Uart<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+            if self.input.is_empty() && self.fail {
+                return Err(embedded_io::ErrorKind::Other);
+            }
+            let len = buf.len().min(3).min(self.input.len());
+            buf[..len].copy_from_slice(&self.input[..len]);
+            self.input = &self.input[len..];
+            Ok(len)
+        }
+    }
+
+    fn uart(input: &[u8]) -> Uart<'_> {
+        Uart { input, fail: false }
+    }
+
+    #[test]
+    fn embedded_io() {
+        let mut buf = [0u8; 64];
+        let mut reader = uart(br#"{"denom": "uatom", "amount": 1} {"denom""#);
+        assert_eq!(
+            from_embedded_io(&mut reader, &mut buf),
+            Ok((
+                Coin {
+                    denom: "uatom",
+                    amount: 1
+                },
+                &b" {"[..]
+            ))
+        );
+        // the rest of the source is left unread
+        assert_eq!(reader.input, br#""denom""#);
+
+        // a scalar is complete at the end of the source or at the next whitespace
+        let mut buf = [0u8; 8];
+        assert_eq!(
+            from_embedded_io(&mut uart(b"42"), &mut buf),
+            Ok((42u8, &b""[..]))
+        );
+        assert_eq!(
+            from_embedded_io(&mut uart(b" 42 7"), &mut buf),
+            Ok((42u8, &b" 7"[..]))
+        );
+
+        let mut slice: &[u8] = b"[1, 2]";
+        assert_eq!(
+            from_embedded_io(&mut slice, &mut buf),
+            Ok((vec![1u8, 2], &b""[..]))
+        );
+    }
+
+    #[test]
+    fn embedded_io_errors() {
+        let mut buf = [0u8; 8];
+        assert_eq!(
+            from_embedded_io::<Vec<u8>, _>(&mut uart(b"[1, 2, 3, 4]"), &mut buf),
+            Err(Error::InputTooLong)
+        );
+        assert_eq!(
+            from_embedded_io_with_options::<Vec<u8>, _>(
+                &mut uart(b"[1, 2]"),
+                &mut buf,
+                &Options::default().max_input_len(4)
+            ),
+            Err(Error::InputTooLong)
+        );
+        assert_eq!(
+            from_embedded_io::<Vec<u8>, _>(&mut uart(b"[1, 2"), &mut buf),
+            Err(Error::EofWhileParsingList)
+        );
+        assert_eq!(
+            from_embedded_io::<Vec<u8>, _>(
+                &mut Uart {
+                    input: b"[1,",
+                    fail: true
+                },
+                &mut buf
+            ),
+            Err(Error::Read)
+        );
+    }
+}
@@ -1,18 +1,21 @@
-use serde::de;
+use serde::de::{self, value::BorrowedStrDeserializer, IntoDeserializer};
 
-use crate::de::{Deserializer, Error, Result};
+use crate::de::read::Read;
+use crate::de::{Deserializer, Error, JsonType, PathSegment, Result, StringLike};
 
-pub(crate) struct UnitVariantAccess<'a, 'b> {
-    de: &'a mut Deserializer<'b>,
+pub(crate) struct UnitVariantAccess<'a, 'b, R> {
+    de: &'a mut Deserializer<'b, R>,
+    /// Type of the JSON value the variant is read from
+    found: JsonType,
 }
 
-impl<'a, 'b> UnitVariantAccess<'a, 'b> {
-    pub(crate) fn new(de: &'a mut Deserializer<'b>) -> Self {
-        UnitVariantAccess { de }
+impl<'a, 'b, R> UnitVariantAccess<'a, 'b, R> {
+    pub(crate) fn new(de: &'a mut Deserializer<'b, R>, found: JsonType) -> Self {
+        UnitVariantAccess { de, found }
     }
 }
 
-impl<'a, 'de> de::EnumAccess<'de> for UnitVariantAccess<'a, 'de> {
+impl<'a, 'de, R: Read<'de>> de::EnumAccess<'de> for UnitVariantAccess<'a, 'de, R> {
     type Error = Error;
     type Variant = Self;
 
@@ -25,7 +28,7 @@ impl<'a, 'de> de::EnumAccess<'de> for UnitVariantAccess<'a, 'de> {
     }
 }
 
-impl<'de, 'a> de::VariantAccess<'de> for UnitVariantAccess<'a, 'de> {
+impl<'de, 'a, R: Read<'de>> de::VariantAccess<'de> for UnitVariantAccess<'a, 'de, R> {
     type Error = Error;
 
     fn unit_variant(self) -> Result<()> {
@@ -36,60 +39,94 @@ impl<'de, 'a> de::VariantAccess<'de> for UnitVariantAccess<'a, 'de> {
     where
         T: de::DeserializeSeed<'de>,
     {
-        Err(Error::InvalidType)
+        Err(Error::InvalidType(self.found))
     }
 
     fn tuple_variant<V>(self, _len: usize, _visitor: V) -> Result<V::Value>
     where
         V: de::Visitor<'de>,
     {
-        Err(Error::InvalidType)
+        Err(Error::InvalidType(self.found))
     }
 
     fn struct_variant<V>(self, _fields: &'static [&'static str], _visitor: V) -> Result<V::Value>
     where
         V: de::Visitor<'de>,
     {
-        Err(Error::InvalidType)
+        Err(Error::InvalidType(self.found))
     }
 }
 
-pub(crate) struct StructVariantAccess<'a, 'b> {
-    de: &'a mut Deserializer<'b>,
+pub(crate) struct StructVariantAccess<'a, 'b, R> {
+    de: &'a mut Deserializer<'b, R>,
+    /// Byte range of the variant name, without quotes
+    variant: (usize, usize),
 }
 
-impl<'a, 'b> StructVariantAccess<'a, 'b> {
-    pub fn new(de: &'a mut Deserializer<'b>) -> Self {
-        StructVariantAccess { de }
+impl<'a, 'b, R: Read<'b>> StructVariantAccess<'a, 'b, R> {
+    pub fn new(de: &'a mut Deserializer<'b, R>) -> Self {
+        StructVariantAccess {
+            de,
+            variant: (0, 0),
+        }
+    }
+
+    fn record_path(&mut self, e: Error) -> Error {
+        self.de
+            .record_path(PathSegment::Key(self.variant.0, self.variant.1));
+        self.de.record_variant(self.variant.0, self.variant.1);
+        e
     }
 }
 
-impl<'a, 'de> de::EnumAccess<'de> for StructVariantAccess<'a, 'de> {
+impl<'a, 'de, R: Read<'de>> de::EnumAccess<'de> for StructVariantAccess<'a, 'de, R> {
     type Error = Error;
     type Variant = Self;
 
-    fn variant_seed<V>(self, seed: V) -> Result<(V::Value, Self)>
+    fn variant_seed<V>(mut self, seed: V) -> Result<(V::Value, Self)>
     where
         V: de::DeserializeSeed<'de>,
     {
-        let val = seed.deserialize(&mut *self.de)?;
+        let start = self
+            .de
+            .parse_whitespace()
+            .map_or(0, |_| self.de.read.position());
+        let val = match self.de.parse_unquoted_key() {
+            Some(variant) => {
+                let val = match variant {
+                    StringLike::Borrowed(variant) => {
+                        seed.deserialize(BorrowedStrDeserializer::new(variant))?
+                    }
+                    variant => seed.deserialize(variant.as_str().into_deserializer())?,
+                };
+                self.variant = (start, self.de.read.position());
+                val
+            }
+            None => {
+                let val = seed.deserialize(&mut *self.de)?;
+                self.variant = (start + 1, self.de.read.position() - 1);
+                val
+            }
+        };
         self.de.parse_object_colon()?;
         Ok((val, self))
     }
 }
 
-impl<'a, 'de> de::VariantAccess<'de> for StructVariantAccess<'a, 'de> {
+impl<'a, 'de, R: Read<'de>> de::VariantAccess<'de> for StructVariantAccess<'a, 'de, R> {
     type Error = Error;
 
     fn unit_variant(self) -> Result<()> {
-        Err(Error::InvalidType)
+        Err(Error::InvalidType(JsonType::Object))
     }
 
-    fn newtype_variant_seed<T>(self, seed: T) -> Result<T::Value>
+    fn newtype_variant_seed<T>(mut self, seed: T) -> Result<T::Value>
     where
         T: de::DeserializeSeed<'de>,
     {
-        let value = seed.deserialize(&mut *self.de)?;
+        let value = seed
+            .deserialize(&mut *self.de)
+            .map_err(|e| self.record_path(e))?;
         // we remove trailing '}' to be consistent with struct_variant algorithm
         match self
             .de
@@ -100,15 +137,16 @@ impl<'a, 'de> de::VariantAccess<'de> for StructVariantAccess<'a, 'de> {
                 self.de.eat_char();
                 Ok(value)
             }
-            _ => Err(Error::ExpectedSomeValue),
+            c => Err(Error::ExpectedSomeValue(c)),
         }
     }
 
-    fn tuple_variant<V>(self, len: usize, visitor: V) -> Result<V::Value>
+    fn tuple_variant<V>(mut self, len: usize, visitor: V) -> Result<V::Value>
     where
         V: de::Visitor<'de>,
     {
-        let value = de::Deserializer::deserialize_tuple(&mut *self.de, len, visitor)?;
+        let value = de::Deserializer::deserialize_tuple(&mut *self.de, len, visitor)
+            .map_err(|e| self.record_path(e))?;
         match self
             .de
             .parse_whitespace()
@@ -118,15 +156,16 @@ impl<'a, 'de> de::VariantAccess<'de> for StructVariantAccess<'a, 'de> {
                 self.de.eat_char();
                 Ok(value)
             }
-            _ => Err(Error::ExpectedSomeValue),
+            c => Err(Error::ExpectedSomeValue(c)),
         }
     }
 
-    fn struct_variant<V>(self, fields: &'static [&'static str], visitor: V) -> Result<V::Value>
+    fn struct_variant<V>(mut self, fields: &'static [&'static str], visitor: V) -> Result<V::Value>
     where
         V: de::Visitor<'de>,
     {
-        let value = de::Deserializer::deserialize_struct(&mut *self.de, "", fields, visitor)?;
+        let value = de::Deserializer::deserialize_struct(&mut *self.de, "", fields, visitor)
+            .map_err(|e| self.record_path(e))?;
         match self
             .de
             .parse_whitespace()
@@ -136,7 +175,7 @@ impl<'a, 'de> de::VariantAccess<'de> for StructVariantAccess<'a, 'de> {
                 self.de.eat_char();
                 Ok(value)
             }
-            _ => Err(Error::ExpectedSomeValue),
+            c => Err(Error::ExpectedSomeValue(c)),
         }
     }
 }
@@ -1,5 +1,10 @@
+use alloc::string::{String, ToString};
+use core::fmt;
 use serde::de;
-use std::{error, fmt};
+#[cfg(feature = "std")]
+use std::error;
+
+use crate::Category;
 
 /// Deserialization result
 pub type Result<T> = core::result::Result<T, Error>;
@@ -8,9 +13,19 @@ pub type Result<T> = core::result::Result<T, Error>;
 #[derive(Debug, PartialEq)]
 #[non_exhaustive]
 pub enum Error {
+    /// An array has more elements than [`Options::max_array_len`](crate::de::Options::max_array_len).
+    ArrayTooLong,
+
     /// Control character (U+0000 to U+001F) found in string. Those must always be escaped.
     ControlCharacterInString,
 
+    /// An object contains the same key twice, which is rejected with
+    /// [`DuplicateKeys::Reject`](crate::de::DuplicateKeys::Reject).
+    DuplicateKey,
+
+    /// EOF while parsing a block comment.
+    EofWhileParsingComment,
+
     /// EOF while parsing a list.
     EofWhileParsingList,
 
@@ -42,36 +57,178 @@ pub enum Error {
     ExpectedSomeIdent,
 
     /// Expected this character to start a JSON value.
-    ExpectedSomeValue,
+    ExpectedSomeValue(u8),
+
+    /// A number with a fraction or exponent, which is rejected with
+    /// [`Options::deny_floats`](crate::de::Options::deny_floats).
+    FloatNotAllowed,
+
+    /// An `f32` or `f64` was requested, but float support is compiled out because the `float`
+    /// feature is disabled.
+    FloatNotSupported,
+
+    /// The input is longer than [`Options::max_input_len`](crate::de::Options::max_input_len).
+    InputTooLong,
+
+    /// A string is not a valid encoding of bytes.
+    InvalidBytes,
 
     /// Invalid escape sequence
     InvalidEscape,
 
+    /// A string or sequence has a length that the type doesn't accept, e.g. a tuple with too few
+    /// elements, or a string or array that doesn't fit into a type with a fixed capacity like
+    /// `arrayvec::ArrayVec<T, N>` or `heapless::String<N>`. Holds the length that the type
+    /// reported.
+    InvalidLength(usize),
+
+    /// Reading the input failed with an I/O error of this kind.
+    #[cfg(feature = "std")]
+    Io(std::io::ErrorKind),
+
     /// Invalid number.
     InvalidNumber,
 
-    /// Invalid type
-    InvalidType,
+    /// A number is valid JSON but out of the range of the type it is deserialized to, like `256`
+    /// for a `u8` or `-1` for a `u64`.
+    NumberOutOfRange,
+
+    /// Invalid type. Holds the type of the JSON value that was found instead.
+    InvalidType(JsonType),
 
     /// Invalid unicode code point.
     InvalidUnicodeCodePoint,
 
+    /// UTF-16 input is not valid, e.g. because of a lone surrogate. Holds the index of the first
+    /// invalid code unit.
+    #[cfg(feature = "utf16")]
+    InvalidUtf16(usize),
+
+    /// A string contains invalid UTF-8. Holds the byte offset of the first invalid byte in the
+    /// input.
+    InvalidUtf8(usize),
+
     /// Object key is not a string.
     KeyMustBeAString,
 
+    /// A required struct field is missing from the object.
+    MissingField(&'static str),
+
+    /// An object has more members than
+    /// [`Options::max_object_len`](crate::de::Options::max_object_len).
+    ObjectTooLong,
+
+    /// Reading the input from a byte source failed.
+    Read,
+
+    /// Arrays and objects are nested deeper than the configured maximum depth.
+    RecursionLimitExceeded,
+
     /// Found a lone surrogate, which can exist in JSON but cannot be encoded to UTF-8
     LoneSurrogateFound,
 
+    /// A string is longer than [`Options::max_string_len`](crate::de::Options::max_string_len).
+    StringTooLong,
+
     /// JSON has non-whitespace trailing characters after the value.
     TrailingCharacters,
 
     /// JSON has a comma after the last value in an array or map.
     TrailingComma,
 
+    /// An object key that is not a field of the struct, see
+    /// [`Options::deny_unknown_fields`](crate::de::Options::deny_unknown_fields).
+    UnknownField(String),
+
+    /// An enum variant (or tag of an internally tagged enum) that does not exist.
+    #[non_exhaustive]
+    UnknownVariant {
+        /// The variant that was found
+        variant: String,
+        /// Names of the variants of the enum
+        #[cfg(feature = "expected-variants")]
+        expected: &'static [&'static str],
+    },
+
     /// Custom error message from serde
     Custom(String),
 }
 
+impl Error {
+    /// Categorizes the cause of this error
+    pub fn classify(&self) -> Category {
+        match self {
+            #[cfg(feature = "std")]
+            Error::Io(_) => Category::Io,
+            Error::Read => Category::Io,
+            #[cfg(feature = "utf16")]
+            Error::InvalidUtf16(_) => Category::Syntax,
+            Error::EofWhileParsingComment
+            | Error::EofWhileParsingList
+            | Error::EofWhileParsingObject
+            | Error::EofWhileParsingString
+            | Error::EofWhileParsingValue => Category::Eof,
+            // the input may be valid JSON that exceeds the limits of the options
+            Error::ArrayTooLong
+            | Error::InputTooLong
+            | Error::ObjectTooLong
+            | Error::RecursionLimitExceeded
+            | Error::StringTooLong => Category::Data,
+            Error::DuplicateKey
+            | Error::FloatNotAllowed
+            | Error::FloatNotSupported
+            | Error::InvalidBytes
+            | Error::InvalidLength(_)
+            | Error::InvalidType(_)
+            | Error::MissingField(_)
+            | Error::NumberOutOfRange
+            | Error::UnknownField(_)
+            | Error::UnknownVariant { .. }
+            | Error::Custom(_) => Category::Data,
+            Error::ControlCharacterInString
+            | Error::ExpectedColon
+            | Error::ExpectedHighSurrogate
+            | Error::ExpectedListCommaOrEnd
+            | Error::ExpectedLowSurrogate
+            | Error::ExpectedObjectCommaOrEnd
+            | Error::ExpectedSomeIdent
+            | Error::ExpectedSomeValue(_)
+            | Error::InvalidEscape
+            | Error::InvalidNumber
+            | Error::InvalidUnicodeCodePoint
+            | Error::InvalidUtf8(_)
+            | Error::KeyMustBeAString
+            | Error::LoneSurrogateFound
+            | Error::TrailingCharacters
+            | Error::TrailingComma => Category::Syntax,
+        }
+    }
+
+    /// Returns true if the input is not syntactically valid JSON
+    pub fn is_syntax(&self) -> bool {
+        self.classify() == Category::Syntax
+    }
+
+    /// Returns true if the input is valid JSON that does not match the target type
+    pub fn is_data(&self) -> bool {
+        self.classify() == Category::Data
+    }
+
+    /// Returns true if the input ended before a JSON value was complete
+    pub fn is_eof(&self) -> bool {
+        self.classify() == Category::Eof
+    }
+
+    /// Error for a requested type that doesn't match the value starting with `c`
+    pub(crate) fn unexpected_byte(c: u8) -> Self {
+        match JsonType::from_first_byte(c) {
+            Some(found) => Error::InvalidType(found),
+            None => Error::ExpectedSomeValue(c),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
 impl error::Error for Error {
     fn source(&self) -> Option<&(dyn error::Error + 'static)> {
         None
@@ -82,6 +239,10 @@ impl error::Error for Error {
     }
 }
 
+/// Without `std`, serde requires its own stand-in for `std::error::Error`
+#[cfg(not(feature = "std"))]
+impl de::StdError for Error {}
+
 impl de::Error for Error {
     fn custom<T>(msg: T) -> Self
     where
@@ -89,51 +250,381 @@ impl de::Error for Error {
     {
         Error::Custom(msg.to_string())
     }
+
+    fn invalid_type(unexp: de::Unexpected<'_>, exp: &dyn de::Expected) -> Self {
+        de::Error::custom(format_args!(
+            "invalid type: {}, expected {}",
+            DescribeUnexpected(unexp),
+            exp
+        ))
+    }
+
+    fn invalid_value(unexp: de::Unexpected<'_>, exp: &dyn de::Expected) -> Self {
+        de::Error::custom(format_args!(
+            "invalid value: {}, expected {}",
+            DescribeUnexpected(unexp),
+            exp
+        ))
+    }
+
+    fn invalid_length(len: usize, _exp: &dyn de::Expected) -> Self {
+        Error::InvalidLength(len)
+    }
+
+    fn missing_field(field: &'static str) -> Self {
+        Error::MissingField(field)
+    }
+
+    fn unknown_field(field: &str, _expected: &'static [&'static str]) -> Self {
+        Error::UnknownField(field.to_string())
+    }
+
+    fn unknown_variant(variant: &str, expected: &'static [&'static str]) -> Self {
+        #[cfg(not(feature = "expected-variants"))]
+        let _ = expected;
+        Error::UnknownVariant {
+            variant: variant.to_string(),
+            #[cfg(feature = "expected-variants")]
+            expected,
+        }
+    }
+}
+
+/// Describes an unexpected value like the `Display` impl of [`de::Unexpected`], except that the
+/// value of a float is left out
+///
+/// That impl formats floats in the same function as all other values, so calling it for any
+/// error would pull the float formatting code of `core::fmt` into the binary.
+struct DescribeUnexpected<'a>(de::Unexpected<'a>);
+
+impl fmt::Display for DescribeUnexpected<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use serde::de::Unexpected;
+
+        match self.0 {
+            Unexpected::Bool(b) => write!(f, "boolean `{}`", b),
+            Unexpected::Unsigned(i) => write!(f, "integer `{}`", i),
+            Unexpected::Signed(i) => write!(f, "integer `{}`", i),
+            Unexpected::Float(_) => f.write_str("floating point"),
+            Unexpected::Char(c) => write!(f, "character `{}`", c),
+            Unexpected::Str(s) => write!(f, "string {:?}", s),
+            Unexpected::Bytes(_) => f.write_str("byte array"),
+            Unexpected::Unit => f.write_str("unit value"),
+            Unexpected::Option => f.write_str("Option value"),
+            Unexpected::NewtypeStruct => f.write_str("newtype struct"),
+            Unexpected::Seq => f.write_str("sequence"),
+            Unexpected::Map => f.write_str("map"),
+            Unexpected::Enum => f.write_str("enum"),
+            Unexpected::UnitVariant => f.write_str("unit variant"),
+            Unexpected::NewtypeVariant => f.write_str("newtype variant"),
+            Unexpected::TupleVariant => f.write_str("tuple variant"),
+            Unexpected::StructVariant => f.write_str("struct variant"),
+            Unexpected::Other(other) => f.write_str(other),
+        }
+    }
 }
 
 impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(
-            f,
-            "{}",
-            match self {
-                Error::ControlCharacterInString => "Control character found in string.",
-                Error::EofWhileParsingList => "EOF while parsing a list.",
-                Error::EofWhileParsingObject => "EOF while parsing an object.",
-                Error::EofWhileParsingString => "EOF while parsing a string.",
-                Error::EofWhileParsingValue => "EOF while parsing a JSON value.",
-                Error::ExpectedColon => "Expected this character to be a `':'`.",
-                Error::ExpectedHighSurrogate => "Expected a high surrogate (D800–DBFF).",
-                Error::ExpectedListCommaOrEnd => {
-                    "Expected this character to be either a `','` or\
-                     a \
-                     `']'`."
-                }
-                Error::ExpectedLowSurrogate => "Expected a low surrogate (DC00–DFFF).",
-                Error::ExpectedObjectCommaOrEnd => {
-                    "Expected this character to be either a `','` \
-                     or a \
-                     `'}'`."
-                }
-                Error::ExpectedSomeIdent => {
-                    "Expected to parse either a `true`, `false`, or a \
-                     `null`."
-                }
-                Error::ExpectedSomeValue => "Expected this character to start a JSON value.",
-                Error::InvalidEscape => "Invalid escape sequence.",
-                Error::InvalidNumber => "Invalid number.",
-                Error::InvalidType => "Invalid type",
-                Error::InvalidUnicodeCodePoint => "Invalid unicode code point.",
-                Error::KeyMustBeAString => "Object key is not a string.",
-                Error::LoneSurrogateFound => "Found a lone surrogate, which can exist in JSON but cannot be encoded to UTF-8.",
-                Error::TrailingCharacters => {
-                    "JSON has non-whitespace trailing characters after \
-                     the \
-                     value."
+        match self {
+            Error::ArrayTooLong => f.write_str("Array exceeds the maximum number of elements."),
+            Error::ControlCharacterInString => f.write_str("Control character found in string."),
+            Error::DuplicateKey => f.write_str("Object contains a duplicate key."),
+            Error::EofWhileParsingComment => f.write_str("EOF while parsing a comment."),
+            Error::EofWhileParsingList => f.write_str("EOF while parsing a list."),
+            Error::EofWhileParsingObject => f.write_str("EOF while parsing an object."),
+            Error::EofWhileParsingString => f.write_str("EOF while parsing a string."),
+            Error::EofWhileParsingValue => f.write_str("EOF while parsing a JSON value."),
+            Error::ExpectedColon => f.write_str("Expected this character to be a `':'`."),
+            Error::ExpectedHighSurrogate => f.write_str("Expected a high surrogate (D800–DBFF)."),
+            Error::ExpectedListCommaOrEnd => f.write_str(
+                "Expected this character to be either a `','` or\
+                 a \
+                 `']'`.",
+            ),
+            Error::ExpectedLowSurrogate => f.write_str("Expected a low surrogate (DC00–DFFF)."),
+            Error::ExpectedObjectCommaOrEnd => f.write_str(
+                "Expected this character to be either a `','` \
+                 or a \
+                 `'}'`.",
+            ),
+            Error::ExpectedSomeIdent => f.write_str(
+                "Expected to parse either a `true`, `false`, or a \
+                 `null`.",
+            ),
+            Error::ExpectedSomeValue(c) => write!(
+                f,
+                "Expected this character to start a JSON value, found {}.",
+                DisplayByte(*c)
+            ),
+            Error::FloatNotAllowed => {
+                f.write_str("Numbers with a fraction or exponent are not allowed.")
+            }
+            Error::FloatNotSupported => {
+                f.write_str("Floats are not supported without the `float` feature.")
+            }
+            Error::InputTooLong => f.write_str("Input exceeds the maximum length."),
+            Error::InvalidBytes => f.write_str("String is not a valid encoding of bytes."),
+            Error::InvalidEscape => f.write_str("Invalid escape sequence."),
+            #[cfg(feature = "std")]
+            Error::Io(kind) => write!(f, "I/O error: {:?}.", kind),
+            Error::InvalidLength(len) => write!(f, "Invalid length {}.", len),
+            Error::InvalidNumber => f.write_str("Invalid number."),
+            Error::NumberOutOfRange => f.write_str("Number is out of range for the type."),
+            Error::InvalidType(found) => write!(f, "Invalid type, found {}", found),
+            Error::InvalidUnicodeCodePoint => f.write_str("Invalid unicode code point."),
+            #[cfg(feature = "utf16")]
+            Error::InvalidUtf16(index) => write!(f, "Invalid UTF-16 at code unit {}.", index),
+            Error::InvalidUtf8(offset) => write!(f, "Invalid UTF-8 at byte offset {}.", offset),
+            Error::KeyMustBeAString => f.write_str("Object key is not a string."),
+            Error::LoneSurrogateFound => f.write_str(
+                "Found a lone surrogate, which can exist in JSON but cannot be encoded to UTF-8.",
+            ),
+            Error::MissingField(field) => write!(f, "missing field `{}`", field),
+            Error::ObjectTooLong => f.write_str("Object exceeds the maximum number of members."),
+            Error::Read => f.write_str("Reading the input failed."),
+            Error::RecursionLimitExceeded => f.write_str("Recursion limit exceeded."),
+            Error::StringTooLong => f.write_str("String exceeds the maximum length."),
+            Error::TrailingCharacters => f.write_str(
+                "JSON has non-whitespace trailing characters after \
+                 the \
+                 value.",
+            ),
+            Error::TrailingComma => {
+                f.write_str("JSON has a comma after the last value in an array or map.")
+            }
+            Error::UnknownField(field) => write!(f, "unknown field `{}`", field),
+            Error::UnknownVariant {
+                variant,
+                #[cfg(feature = "expected-variants")]
+                expected,
+            } => {
+                write!(f, "unknown variant `{}`", variant)?;
+                #[cfg(feature = "expected-variants")]
+                match expected.len() {
+                    0 => write!(f, ", there are no variants")?,
+                    1 => write!(f, ", expected `{}`", expected[0])?,
+                    2 => write!(f, ", expected `{}` or `{}`", expected[0], expected[1])?,
+                    _ => {
+                        write!(f, ", expected one of ")?;
+                        for (i, name) in expected.iter().enumerate() {
+                            if i > 0 {
+                                write!(f, ", ")?;
+                            }
+                            write!(f, "`{}`", name)?;
+                        }
+                    }
                 }
-                Error::TrailingComma => "JSON has a comma after the last value in an array or map.",
-                Error::Custom(msg) => &msg,
+                Ok(())
             }
-        )
+            Error::Custom(msg) => f.write_str(msg),
+        }
+    }
+}
+
+/// The type of a JSON value, as determined by its first character
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JsonType {
+    /// `null`
+    Null,
+    /// `true` or `false`
+    Bool,
+    /// An integer or floating point number
+    Number,
+    /// A string
+    String,
+    /// An array
+    Array,
+    /// An object
+    Object,
+}
+
+impl JsonType {
+    /// Returns the type of the JSON value starting with `c`, or `None` if no value starts with it
+    pub(crate) fn from_first_byte(c: u8) -> Option<Self> {
+        match c {
+            b'n' => Some(JsonType::Null),
+            b't' | b'f' => Some(JsonType::Bool),
+            b'-' | b'0'..=b'9' => Some(JsonType::Number),
+            b'"' => Some(JsonType::String),
+            b'[' => Some(JsonType::Array),
+            b'{' => Some(JsonType::Object),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for JsonType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            JsonType::Null => "null",
+            JsonType::Bool => "boolean",
+            JsonType::Number => "number",
+            JsonType::String => "string",
+            JsonType::Array => "array",
+            JsonType::Object => "object",
+        })
+    }
+}
+
+/// Formats an input byte as a quoted character if it is printable ASCII, or in hex otherwise
+struct DisplayByte(u8);
+
+impl fmt::Display for DisplayByte {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.0.is_ascii_graphic() || self.0 == b' ' {
+            write!(f, "`{}`", self.0 as char)
+        } else {
+            write!(f, "byte 0x{:02X}", self.0)
+        }
+    }
+}
+
+/// An [`Error`] together with the byte offset in the input at which it occurred
+#[derive(Debug, PartialEq)]
+#[non_exhaustive]
+pub struct ErrorWithOffset {
+    /// The error that occurred
+    pub error: Error,
+    /// Position of the parser in the input when the error occurred, in bytes from the start
+    pub offset: usize,
+    /// Line of `offset`, starting at 1
+    #[cfg(feature = "line-column")]
+    pub line: usize,
+    /// Column of `offset` in bytes, starting at 1
+    #[cfg(feature = "line-column")]
+    pub column: usize,
+}
+
+impl ErrorWithOffset {
+    pub(crate) fn new(error: Error, input: &[u8], offset: usize) -> Self {
+        #[cfg(not(feature = "line-column"))]
+        let _ = input;
+        #[cfg(feature = "line-column")]
+        let (line, column) = {
+            let before = &input[..offset.min(input.len())];
+            let line = 1 + before.iter().filter(|&&b| b == b'\n').count();
+            let line_start = before
+                .iter()
+                .rposition(|&b| b == b'\n')
+                .map_or(0, |newline| newline + 1);
+            (line, offset - line_start + 1)
+        };
+        ErrorWithOffset {
+            error,
+            offset,
+            #[cfg(feature = "line-column")]
+            line,
+            #[cfg(feature = "line-column")]
+            column,
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl error::Error for ErrorWithOffset {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+/// Writes the message of `error` without its final period, so that the location can follow it
+fn write_message(f: &mut fmt::Formatter<'_>, error: &Error) -> fmt::Result {
+    f.write_str(error.to_string().trim_end_matches('.'))
+}
+
+impl fmt::Display for ErrorWithOffset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_message(f, &self.error)?;
+        #[cfg(feature = "line-column")]
+        return write!(f, " at line {} column {}", self.line, self.column);
+        #[cfg(not(feature = "line-column"))]
+        write!(f, " at byte offset {}", self.offset)
+    }
+}
+
+/// An [`Error`] together with the path to the value in which it occurred
+#[derive(Debug, PartialEq)]
+#[non_exhaustive]
+pub struct ErrorWithPath {
+    /// The error that occurred
+    pub error: Error,
+    /// Location of the failing value, e.g. `messages[3].amount.denom`. Empty if the error
+    /// occurred at the top level.
+    pub path: String,
+}
+
+#[cfg(feature = "std")]
+impl error::Error for ErrorWithPath {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+impl fmt::Display for ErrorWithPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.path.is_empty() {
+            write!(f, "{}", self.error)
+        } else {
+            write_message(f, &self.error)?;
+            write!(f, " at {}", self.path)
+        }
+    }
+}
+
+/// An [`Error`] together with the types and enum variants that were being deserialized when it
+/// occurred
+#[cfg(feature = "type-context")]
+#[derive(Debug, PartialEq)]
+#[non_exhaustive]
+pub struct ErrorWithContext {
+    /// The error that occurred
+    pub error: Error,
+    /// The types that were being deserialized, outermost first, e.g.
+    /// `ExecuteMsg::transfer > Coin`. Variants are named as in the input. Empty if the error
+    /// occurred outside of any struct or enum.
+    pub context: String,
+}
+
+#[cfg(all(feature = "std", feature = "type-context"))]
+impl error::Error for ErrorWithContext {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+#[cfg(feature = "type-context")]
+impl fmt::Display for ErrorWithContext {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.context.is_empty() {
+            write!(f, "{}", self.error)
+        } else {
+            write!(f, "while deserializing {}: {}", self.context, self.error)
+        }
+    }
+}
+
+/// An [`Error`] together with the number of the line of newline-delimited JSON in which it
+/// occurred
+#[derive(Debug, PartialEq)]
+#[non_exhaustive]
+pub struct ErrorWithLine {
+    /// The error that occurred
+    pub error: Error,
+    /// Number of the failing line, starting at 1
+    pub line: usize,
+}
+
+#[cfg(feature = "std")]
+impl error::Error for ErrorWithLine {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+impl fmt::Display for ErrorWithLine {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_message(f, &self.error)?;
+        write!(f, " on line {}", self.line)
     }
 }
@@ -0,0 +1,343 @@
+use alloc::borrow::Cow;
+use alloc::vec::Vec;
+
+use crate::de::{Deserializer, Error, Options, Result, StringLike};
+
+/// Returns a parser that reads the JSON text in `input` as a sequence of [`Event`]s
+///
+/// This is the level below serde: it checks the syntax, but doesn't build any values, which
+/// makes it cheap to scan, filter or reshape documents. Strings and keys are borrowed from the
+/// input unless they contain escape sequences.
+///
+/// ```
+/// use serde_json_wasm::de::{events, Event};
+///
+/// let mut parser = events(br#"{"amount": [1, 2]}"#);
+/// assert_eq!(parser.next_event(), Ok(Some(Event::ObjectStart)));
+/// assert_eq!(parser.next_event(), Ok(Some(Event::Key("amount".into()))));
+/// assert_eq!(parser.next_event(), Ok(Some(Event::ArrayStart)));
+/// assert_eq!(parser.next_event(), Ok(Some(Event::Number("1"))));
+/// ```
+pub fn events(input: &[u8]) -> Events<'_> {
+    Events::with_options(input, &Options::default())
+}
+
+/// A syntactic element of a JSON document, returned by [`Events::next_event`]
+#[derive(Debug, Clone, PartialEq)]
+pub enum Event<'a> {
+    /// `{`
+    ObjectStart,
+    /// `}`
+    ObjectEnd,
+    /// `[`
+    ArrayStart,
+    /// `]`
+    ArrayEnd,
+    /// The key of an object member. The event for its value follows.
+    Key(Cow<'a, str>),
+    /// A string value, unescaped
+    String(Cow<'a, str>),
+    /// The text of a number, like `-1.5e3`
+    Number(&'a str),
+    /// `true` or `false`
+    Bool(bool),
+    /// `null`
+    Null,
+}
+
+/// Pull parser over the events of one JSON value, created by [`events`]
+pub struct Events<'a> {
+    de: Deserializer<'a>,
+    /// Arrays and objects that are currently open
+    open: Vec<Container>,
+    /// Set after a key, when the value of the member comes next
+    after_key: bool,
+    started: bool,
+    /// Set at the end of the input or after an error
+    done: bool,
+    /// Byte range of the text of the last event
+    token: (usize, usize),
+}
+
+struct Container {
+    close: u8,
+    /// Number of elements or members started so far
+    len: usize,
+}
+
+impl<'a> Events<'a> {
+    /// Creates a parser over `input` that uses `options`, e.g. to accept trailing commas or
+    /// comments
+    pub fn with_options(input: &'a [u8], options: &Options) -> Self {
+        Events {
+            de: Deserializer::with_options(input, options),
+            open: Vec::new(),
+            after_key: false,
+            started: false,
+            done: false,
+            token: (0, 0),
+        }
+    }
+
+    /// Returns the number of bytes consumed so far
+    pub fn byte_offset(&self) -> usize {
+        self.de.read.index
+    }
+
+    /// Returns the text of the last event in the input, e.g. a string with its quotes and escape
+    /// sequences
+    pub(crate) fn token(&self) -> &'a [u8] {
+        &self.de.read.slice[self.token.0..self.token.1]
+    }
+
+    /// Parses the next event, or returns `None` once the value is complete and only whitespace
+    /// follows it
+    ///
+    /// After an error, `None` is returned.
+    pub fn next_event(&mut self) -> Result<Option<Event<'a>>> {
+        if self.done {
+            return Ok(None);
+        }
+        let result = self.parse_event();
+        if !matches_some(&result) {
+            self.done = true;
+        }
+        result
+    }
+
+    fn parse_event(&mut self) -> Result<Option<Event<'a>>> {
+        if self.after_key {
+            self.after_key = false;
+            return self.parse_value().map(Some);
+        }
+        let (close, len) = match self.open.last() {
+            Some(container) => (container.close, container.len),
+            None if self.started => return self.de.end().map(|()| None),
+            None => {
+                self.started = true;
+                if let Some(max_input_len) = self.de.options.max_input_len {
+                    if self.de.read.slice.len() > max_input_len {
+                        return Err(Error::InputTooLong);
+                    }
+                }
+                return self.parse_value().map(Some);
+            }
+        };
+        let object = close == b'}';
+        let eof = if object {
+            Error::EofWhileParsingObject
+        } else {
+            Error::EofWhileParsingList
+        };
+
+        let mut c = self.de.parse_whitespace().ok_or(eof)?;
+        if c == close {
+            return Ok(Some(self.close()));
+        }
+        if len > 0 {
+            if c != b',' {
+                return Err(if object {
+                    Error::ExpectedObjectCommaOrEnd
+                } else {
+                    Error::ExpectedListCommaOrEnd
+                });
+            }
+            self.de.eat_char();
+            c = self
+                .de
+                .parse_whitespace()
+                .ok_or(Error::EofWhileParsingValue)?;
+            if c == close {
+                if !self.de.options.allow_trailing_commas {
+                    return Err(Error::TrailingComma);
+                }
+                return Ok(Some(self.close()));
+            }
+        }
+        self.de.check_len(close, len + 1)?;
+        if let Some(container) = self.open.last_mut() {
+            container.len += 1;
+        }
+        if !object {
+            return self.parse_value().map(Some);
+        }
+
+        let start = self.de.read.index;
+        let key = if self.de.is_quote(c) {
+            cow(self.de.parse_string()?)
+        } else {
+            match self.de.parse_unquoted_key() {
+                Some(key) => cow(key),
+                None => return Err(Error::KeyMustBeAString),
+            }
+        };
+        self.token = (start, self.de.read.index);
+        self.de.parse_object_colon()?;
+        self.after_key = true;
+        Ok(Some(Event::Key(key)))
+    }
+
+    /// Consumes the closing bracket of the innermost container
+    fn close(&mut self) -> Event<'a> {
+        self.token = (self.de.read.index, self.de.read.index + 1);
+        self.de.eat_char();
+        self.de.leave_nested();
+        match self.open.pop() {
+            Some(Container { close: b'}', .. }) => Event::ObjectEnd,
+            _ => Event::ArrayEnd,
+        }
+    }
+
+    fn parse_value(&mut self) -> Result<Event<'a>> {
+        let c = self
+            .de
+            .parse_whitespace()
+            .ok_or(Error::EofWhileParsingValue)?;
+        let start = self.de.read.index;
+        let event = self.parse_value_starting_with(c)?;
+        self.token = (start, self.de.read.index);
+        Ok(event)
+    }
+
+    fn parse_value_starting_with(&mut self, c: u8) -> Result<Event<'a>> {
+        match c {
+            b'{' | b'[' => {
+                self.de.enter_nested()?;
+                self.de.eat_char();
+                let close = if c == b'{' { b'}' } else { b']' };
+                self.open.push(Container { close, len: 0 });
+                Ok(if c == b'{' {
+                    Event::ObjectStart
+                } else {
+                    Event::ArrayStart
+                })
+            }
+            c if self.de.is_quote(c) => Ok(Event::String(cow(self.de.parse_string()?))),
+            b't' => self.de.parse_ident(b"true").map(|()| Event::Bool(true)),
+            b'f' => self.de.parse_ident(b"false").map(|()| Event::Bool(false)),
+            b'n' => self.de.parse_ident(b"null").map(|()| Event::Null),
+            b'-' | b'0'..=b'9' => self.de.parse_number_slice().map(Event::Number),
+            c => Err(self.de.unexpected_number_byte(c)),
+        }
+    }
+}
+
+/// Whether `result` holds an event
+fn matches_some(result: &Result<Option<Event<'_>>>) -> bool {
+    match result {
+        Ok(Some(_)) => true,
+        _ => false,
+    }
+}
+
+fn cow<'a>(string: StringLike<'a, '_>) -> Cow<'a, str> {
+    match string {
+        StringLike::Borrowed(string) => Cow::Borrowed(string),
+        StringLike::Copied(string) => Cow::Owned(string.into()),
+        StringLike::Owned(string) => Cow::Owned(string),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{events, Event, Events};
+    use crate::de::{Error, Options};
+
+    /// Collects all events of `input`, or the first error
+    fn collect(input: &str) -> Result<Vec<Event<'_>>, Error> {
+        let mut parser = events(input.as_bytes());
+        let mut out = Vec::new();
+        while let Some(event) = parser.next_event()? {
+            out.push(event);
+        }
+        Ok(out)
+    }
+
+    #[test]
+    fn events_of_a_document() {
+        assert_eq!(
+            collect(r#" {"a": [1, -2.5e3, "x\ny", true], "b": {}, "c\\": null, "d": []} "#),
+            Ok(vec![
+                Event::ObjectStart,
+                Event::Key("a".into()),
+                Event::ArrayStart,
+                Event::Number("1"),
+                Event::Number("-2.5e3"),
+                Event::String("x\ny".into()),
+                Event::Bool(true),
+                Event::ArrayEnd,
+                Event::Key("b".into()),
+                Event::ObjectStart,
+                Event::ObjectEnd,
+                Event::Key("c\\".into()),
+                Event::Null,
+                Event::Key("d".into()),
+                Event::ArrayStart,
+                Event::ArrayEnd,
+                Event::ObjectEnd,
+            ])
+        );
+        assert_eq!(collect("false"), Ok(vec![Event::Bool(false)]));
+        assert_eq!(collect(r#""a""#), Ok(vec![Event::String("a".into())]));
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(collect(""), Err(Error::EofWhileParsingValue));
+        assert_eq!(collect("[1 2]"), Err(Error::ExpectedListCommaOrEnd));
+        assert_eq!(collect("[1,]"), Err(Error::TrailingComma));
+        assert_eq!(collect(r#"{"a" 1}"#), Err(Error::ExpectedColon));
+        assert_eq!(
+            collect(r#"{"a": 1 "b": 2}"#),
+            Err(Error::ExpectedObjectCommaOrEnd)
+        );
+        assert_eq!(collect("{1: 2}"), Err(Error::KeyMustBeAString));
+        assert_eq!(collect("[1"), Err(Error::EofWhileParsingList));
+        assert_eq!(collect("[tru]"), Err(Error::ExpectedSomeIdent));
+        assert_eq!(collect("[}"), Err(Error::ExpectedSomeValue(b'}')));
+        assert_eq!(collect("1 2"), Err(Error::TrailingCharacters));
+        assert_eq!(collect("-"), Err(Error::InvalidNumber));
+
+        // after an error, the parser stops
+        let mut parser = events(b"[x, 1]");
+        assert_eq!(parser.next_event(), Ok(Some(Event::ArrayStart)));
+        assert_eq!(parser.next_event(), Err(Error::ExpectedSomeValue(b'x')));
+        assert_eq!(parser.next_event(), Ok(None));
+    }
+
+    #[test]
+    fn options() {
+        let options = Options::default()
+            .allow_trailing_commas(true)
+            .allow_unquoted_keys(true)
+            .max_depth(2)
+            .max_array_len(1);
+        let mut parser = Events::with_options(b"{a: [1,],}", &options);
+        let mut out = Vec::new();
+        while let Some(event) = parser.next_event().unwrap() {
+            out.push(event);
+        }
+        assert_eq!(
+            out,
+            [
+                Event::ObjectStart,
+                Event::Key("a".into()),
+                Event::ArrayStart,
+                Event::Number("1"),
+                Event::ArrayEnd,
+                Event::ObjectEnd
+            ]
+        );
+        assert_eq!(parser.byte_offset(), 10);
+
+        let mut parser = Events::with_options(b"[[[]]]", &options);
+        assert_eq!(parser.next_event(), Ok(Some(Event::ArrayStart)));
+        assert_eq!(parser.next_event(), Ok(Some(Event::ArrayStart)));
+        assert_eq!(parser.next_event(), Err(Error::RecursionLimitExceeded));
+
+        let mut parser = Events::with_options(b"[1, 2]", &options);
+        assert_eq!(parser.next_event(), Ok(Some(Event::ArrayStart)));
+        assert_eq!(parser.next_event(), Ok(Some(Event::Number("1"))));
+        assert_eq!(parser.next_event(), Err(Error::ArrayTooLong));
+    }
+}
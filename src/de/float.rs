@@ -0,0 +1,425 @@
+//! Conversion of the text of JSON numbers to `f32` and `f64`
+//!
+//! The conversion only uses integer arithmetic and assembles the bits of the result directly, so
+//! neither floating point instructions nor the float code of `core::fmt` and `core::num` end up
+//! in the binary. The result is correctly rounded (to nearest, ties to even), like `str::parse`.
+
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+
+/// A binary floating point format
+pub(crate) trait Float: Sized {
+    /// Number of explicitly stored mantissa bits
+    const MANTISSA_BITS: u32;
+    /// Number of exponent bits
+    const EXPONENT_BITS: u32;
+
+    fn from_bits(bits: u64) -> Self;
+}
+
+impl Float for f32 {
+    const MANTISSA_BITS: u32 = 23;
+    const EXPONENT_BITS: u32 = 8;
+
+    fn from_bits(bits: u64) -> Self {
+        f32::from_bits(bits as u32)
+    }
+}
+
+impl Float for f64 {
+    const MANTISSA_BITS: u32 = 52;
+    const EXPONENT_BITS: u32 = 11;
+
+    fn from_bits(bits: u64) -> Self {
+        f64::from_bits(bits)
+    }
+}
+
+/// Number of significant digits that are converted exactly. Later digits only decide whether the
+/// number is above a halfway point, which is enough since the halfway points between two `f64`
+/// have at most 767 significant digits.
+const MAX_DIGITS: usize = 800;
+
+/// Converts the text of a number following the JSON grammar, like `-1.5e3`, to the closest `F`.
+/// Returns `None` if the number is too large to be finite.
+pub(crate) fn parse<F: Float>(text: &[u8]) -> Option<F> {
+    let (negative, text) = match text.split_first() {
+        Some((b'-', rest)) => (true, rest),
+        _ => (false, text),
+    };
+    let (mantissa, mut exponent) = match text.iter().position(|&c| c == b'e' || c == b'E') {
+        Some(e) => (&text[..e], parse_exponent(&text[e + 1..])),
+        None => (text, 0),
+    };
+
+    // the number is `digits * 10^exponent`
+    let mut digits = Big::default();
+    let mut len = 0;
+    let mut truncated = false;
+    let mut fraction = false;
+    for &c in mantissa {
+        if c == b'.' {
+            fraction = true;
+            continue;
+        }
+        let digit = u32::from(c - b'0');
+        if len == 0 && digit == 0 {
+            // leading zero
+        } else if len < MAX_DIGITS {
+            digits.mul_small(10);
+            digits.add_small(digit);
+            len += 1;
+        } else {
+            truncated |= digit != 0;
+            if !fraction {
+                exponent += 1;
+            }
+            continue;
+        }
+        if fraction {
+            exponent -= 1;
+        }
+    }
+    if truncated {
+        // stands for the truncated digits, which are more than zero but less than one unit
+        digits.mul_small(10);
+        digits.add_small(1);
+        len += 1;
+        exponent -= 1;
+    }
+
+    let sign = (negative as u64) << (F::MANTISSA_BITS + F::EXPONENT_BITS);
+    if len == 0 {
+        return Some(F::from_bits(sign));
+    }
+    // the number is below 10^magnitude
+    let magnitude = len as i64 + exponent;
+    if magnitude > 310 {
+        return None;
+    }
+    if magnitude < -330 {
+        return Some(F::from_bits(sign));
+    }
+
+    let (bits, binary_exponent, sticky) = if exponent >= 0 {
+        digits.mul_pow5(exponent as u32);
+        let shift = digits.bit_len().saturating_sub(64);
+        let sticky = digits.shr(shift);
+        (digits.low_u64(), exponent + shift as i64, sticky)
+    } else {
+        // divide by 10^-exponent, scaling so that the quotient has 63 or 64 bits
+        let mut divisor = Big::from_small(1);
+        divisor.mul_pow5(-exponent as u32);
+        let scale = 63 - (digits.bit_len() as i64 - divisor.bit_len() as i64);
+        if scale >= 0 {
+            digits.shl(scale as usize);
+        } else {
+            divisor.shl(-scale as usize);
+        }
+        let quotient = digits.div_u64(divisor);
+        (quotient, exponent - scale, !digits.is_zero())
+    };
+    round::<F>(bits, binary_exponent, sticky).map(|bits| F::from_bits(bits | sign))
+}
+
+/// Parses the digits of an exponent with an optional sign, saturating far outside of the range
+/// of any float
+fn parse_exponent(text: &[u8]) -> i64 {
+    let (negative, digits) = match text.split_first() {
+        Some((b'-', rest)) => (true, rest),
+        Some((b'+', rest)) => (false, rest),
+        _ => (false, text),
+    };
+    let value = digits.iter().fold(0i64, |value, &c| {
+        (value * 10 + i64::from(c - b'0')).min(1_000_000_000)
+    });
+    if negative {
+        -value
+    } else {
+        value
+    }
+}
+
+/// Rounds `bits * 2^exponent` to the bits of the closest `F`, where `sticky` tells that the
+/// number is a little more than that. Returns `None` if it rounds to infinity.
+fn round<F: Float>(bits: u64, exponent: i64, sticky: bool) -> Option<u64> {
+    let mantissa_bits = i64::from(F::MANTISSA_BITS);
+    let bias = (1i64 << (F::EXPONENT_BITS - 1)) - 1;
+    let len = 64 - i64::from(bits.leading_zeros());
+    let biased_exponent = len - 1 + exponent + bias;
+    // significant bits of the result, fewer for subnormal numbers
+    let keep = if biased_exponent >= 1 {
+        mantissa_bits + 1
+    } else {
+        mantissa_bits + biased_exponent
+    };
+    let drop = len - keep;
+    let mantissa = if drop <= 0 {
+        bits << -drop
+    } else if drop >= 128 {
+        0
+    } else {
+        let bits = u128::from(bits);
+        let rest = bits & ((1 << drop) - 1);
+        let half = 1 << (drop - 1);
+        let mantissa = (bits >> drop) as u64;
+        let round_up = rest > half || (rest == half && (sticky || mantissa & 1 == 1));
+        mantissa + round_up as u64
+    };
+    // a mantissa that rounded up to the next power of two carries into the exponent
+    let result = if biased_exponent >= 1 {
+        (((biased_exponent - 1) as u64) << mantissa_bits) + mantissa
+    } else {
+        mantissa
+    };
+    let infinity = ((1u64 << F::EXPONENT_BITS) - 1) << mantissa_bits;
+    if result >= infinity {
+        None
+    } else {
+        Some(result)
+    }
+}
+
+/// Unsigned integer of arbitrary size, with the least significant 32 bit limb first and no
+/// trailing zero limbs
+#[derive(Default)]
+struct Big(Vec<u32>);
+
+impl Big {
+    fn from_small(value: u32) -> Self {
+        let mut big = Big::default();
+        big.add_small(value);
+        big
+    }
+
+    fn is_zero(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    fn bit_len(&self) -> usize {
+        match self.0.last() {
+            Some(last) => self.0.len() * 32 - last.leading_zeros() as usize,
+            None => 0,
+        }
+    }
+
+    fn low_u64(&self) -> u64 {
+        let limb = |i| u64::from(self.0.get(i).cloned().unwrap_or(0));
+        limb(0) | limb(1) << 32
+    }
+
+    fn trim(&mut self) {
+        while self.0.last() == Some(&0) {
+            self.0.pop();
+        }
+    }
+
+    fn add_small(&mut self, value: u32) {
+        let mut carry = value;
+        for limb in &mut self.0 {
+            if carry == 0 {
+                return;
+            }
+            let (sum, overflow) = limb.overflowing_add(carry);
+            *limb = sum;
+            carry = overflow as u32;
+        }
+        if carry != 0 {
+            self.0.push(carry);
+        }
+    }
+
+    fn mul_small(&mut self, factor: u32) {
+        let mut carry = 0;
+        for limb in &mut self.0 {
+            let product = u64::from(*limb) * u64::from(factor) + carry;
+            *limb = product as u32;
+            carry = product >> 32;
+        }
+        if carry != 0 {
+            self.0.push(carry as u32);
+        }
+        self.trim();
+    }
+
+    fn mul_pow5(&mut self, mut exponent: u32) {
+        // 5^13 is the largest power of five that fits into a limb
+        while exponent >= 13 {
+            self.mul_small(1_220_703_125);
+            exponent -= 13;
+        }
+        self.mul_small(5u32.pow(exponent));
+    }
+
+    fn shl(&mut self, bits: usize) {
+        if self.is_zero() {
+            return;
+        }
+        let (limbs, bits) = (bits / 32, bits % 32);
+        if bits > 0 {
+            let mut carry = 0;
+            for limb in &mut self.0 {
+                let shifted = *limb << bits | carry;
+                carry = *limb >> (32 - bits);
+                *limb = shifted;
+            }
+            if carry != 0 {
+                self.0.push(carry);
+            }
+        }
+        self.0.splice(0..0, core::iter::repeat(0).take(limbs));
+    }
+
+    /// Shifts right by `bits` and returns whether any of the bits shifted out were set
+    fn shr(&mut self, bits: usize) -> bool {
+        let (limbs, bits) = (bits / 32, bits % 32);
+        let limbs = limbs.min(self.0.len());
+        let mut sticky = self.0.drain(..limbs).any(|limb| limb != 0);
+        if bits > 0 {
+            let mut carry = 0;
+            for limb in self.0.iter_mut().rev() {
+                let shifted = *limb >> bits | carry;
+                carry = *limb << (32 - bits);
+                *limb = shifted;
+            }
+            sticky |= carry != 0;
+        }
+        self.trim();
+        sticky
+    }
+
+    fn sub(&mut self, other: &Big) {
+        let mut borrow = false;
+        for (i, limb) in self.0.iter_mut().enumerate() {
+            let subtrahend = other.0.get(i).cloned().unwrap_or(0);
+            if subtrahend == 0 && !borrow && i >= other.0.len() {
+                break;
+            }
+            let (difference, overflow1) = limb.overflowing_sub(subtrahend);
+            let (difference, overflow2) = difference.overflowing_sub(borrow as u32);
+            *limb = difference;
+            borrow = overflow1 || overflow2;
+        }
+        self.trim();
+    }
+
+    fn cmp(&self, other: &Big) -> Ordering {
+        self.0
+            .len()
+            .cmp(&other.0.len())
+            .then_with(|| self.0.iter().rev().cmp(other.0.iter().rev()))
+    }
+
+    /// Divides by `divisor`, where the quotient must fit into 64 bits, and leaves the remainder
+    fn div_u64(&mut self, mut divisor: Big) -> u64 {
+        let mut quotient = 0;
+        divisor.shl(63);
+        for bit in (0..64).rev() {
+            if self.cmp(&divisor) != Ordering::Less {
+                self.sub(&divisor);
+                quotient |= 1 << bit;
+            }
+            divisor.shr(1);
+        }
+        quotient
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse;
+
+    fn check(text: &str) {
+        assert_eq!(
+            parse::<f64>(text.as_bytes()).map(f64::to_bits),
+            text.parse::<f64>()
+                .ok()
+                .filter(|v| v.is_finite())
+                .map(f64::to_bits),
+            "f64 {}",
+            text
+        );
+        assert_eq!(
+            parse::<f32>(text.as_bytes()).map(f32::to_bits),
+            text.parse::<f32>()
+                .ok()
+                .filter(|v| v.is_finite())
+                .map(f32::to_bits),
+            "f32 {}",
+            text
+        );
+    }
+
+    #[test]
+    fn parse_exact_and_edge_cases() {
+        for text in &[
+            "0",
+            "-0",
+            "0.0",
+            "1",
+            "-1",
+            "1.5",
+            "0.1",
+            "0.3",
+            "123456789",
+            "1e23",
+            "8.589973e9",
+            "9007199254740993",
+            "9007199254740992.5",
+            "2.2250738585072011e-308",
+            "2.2250738585072014e-308",
+            "4.9406564584124654e-324",
+            "2.4703282292062327e-324",
+            "2.4703282292062328e-324",
+            "1e-400",
+            "1.7976931348623157e308",
+            "1.7976931348623158e308",
+            "1.7976931348623159e308",
+            "1e309",
+            "3.4028235e38",
+            "3.4028236e38",
+            "1.4e-45",
+            "7e-46",
+            "1.17549435e-38",
+            "0.000000000000000000000000000000000000000000001",
+            "179769313486231580793728971405303415079934132710037826936173778980444968292764750946649017977587207096330286416692887910946555547851940402630657488671505820681908902000708383676273854845817711531764475730270069855571366959622842914819860834936475292719074168444365510704342711559699508093042880177904174497791",
+            "1e1000000000000",
+            "1e-1000000000000",
+        ] {
+            check(text);
+        }
+        // halfway between 1 and the next f64, decided by the last of many digits
+        let mut text = "1.00000000000000011102230246251565404236316680908203125".to_string();
+        check(&text);
+        text.push_str(&"0".repeat(1000));
+        check(&text);
+        text.push('1');
+        check(&text);
+    }
+
+    #[test]
+    fn parse_random_numbers() {
+        let mut state = 0x2545_f491_4f6c_dd1du64;
+        let mut next = move || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state
+        };
+        for _ in 0..20_000 {
+            let digits = next() % 25 + 1;
+            let point = next() % digits;
+            let mut text = String::new();
+            for i in 0..digits {
+                if i == point && i > 0 {
+                    text.push('.');
+                }
+                text.push(char::from(b'0' + (next() % 10) as u8));
+            }
+            if next() % 2 == 0 {
+                text.push_str(&format!("e{}", (next() % 700) as i64 - 350));
+            }
+            check(&text);
+        }
+    }
+}
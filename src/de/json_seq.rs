@@ -0,0 +1,107 @@
+use core::marker::PhantomData;
+
+use serde::de;
+
+use crate::de::{from_slice, Error, Result};
+
+/// Record separator that starts every JSON text in a sequence
+pub(crate) const RS: u8 = 0x1E;
+
+/// Returns an iterator that deserializes one `T` from every record of a JSON text sequence
+/// (RFC 7464, `application/json-seq`)
+///
+/// Each record starts with an ASCII record separator (0x1E). Records that fail to deserialize
+/// yield an error, after which iteration resumes at the next record separator. As required by
+/// the RFC, a top-level number, `true`, `false` or `null` that is not followed by whitespace is
+/// considered truncated and fails with [`Error::EofWhileParsingValue`]. Empty records are
+/// skipped.
+pub fn json_seq<'a, T>(input: &'a [u8]) -> JsonSeq<'a, T>
+where
+    T: de::Deserialize<'a>,
+{
+    JsonSeq {
+        rest: input,
+        output: PhantomData,
+    }
+}
+
+/// Iterator over the records of a JSON text sequence, created by [`json_seq`]
+pub struct JsonSeq<'a, T> {
+    rest: &'a [u8],
+    output: PhantomData<T>,
+}
+
+impl<'a, T> Iterator for JsonSeq<'a, T>
+where
+    T: de::Deserialize<'a>,
+{
+    type Item = Result<T>;
+
+    fn next(&mut self) -> Option<Result<T>> {
+        loop {
+            if self.rest.is_empty() {
+                return None;
+            }
+            let rest = match self.rest.split_first() {
+                Some((&RS, rest)) => rest,
+                _ => self.rest,
+            };
+            let (record, rest) = match rest.iter().position(|&b| b == RS) {
+                Some(end) => (&rest[..end], &rest[end..]),
+                None => (rest, &rest[rest.len()..]),
+            };
+            self.rest = rest;
+
+            let first = match record.iter().position(|&b| !is_whitespace(b)) {
+                Some(first) => record[first],
+                None => continue,
+            };
+            let self_delimited = first == b'[' || first == b'{' || first == b'"';
+            return Some(match record.last() {
+                Some(&last) if !self_delimited && !is_whitespace(last) => {
+                    Err(Error::EofWhileParsingValue)
+                }
+                _ => from_slice(record),
+            });
+        }
+    }
+}
+
+fn is_whitespace(b: u8) -> bool {
+    b == b' ' || b == b'\n' || b == b'\t' || b == b'\r'
+}
+
+#[cfg(test)]
+mod tests {
+    use super::json_seq;
+    use crate::de::Error;
+
+    #[test]
+    fn json_seq_works() {
+        let values: Result<Vec<Vec<u8>>, _> =
+            json_seq(b"\x1e[1]\n\x1e[2, 3]\n\x1e\x1e\n\x1e[]\n").collect();
+        assert_eq!(values, Ok(vec![vec![1], vec![2, 3], vec![]]));
+
+        let values: Result<Vec<u8>, _> = json_seq(b"\x1e1\n\x1e 2 \n").collect();
+        assert_eq!(values, Ok(vec![1, 2]));
+
+        assert_eq!(json_seq::<u8>(b"").next(), None);
+        assert_eq!(json_seq::<u8>(b"\x1e\n").next(), None);
+    }
+
+    #[test]
+    fn json_seq_skips_damaged_records() {
+        let mut values = json_seq::<Vec<u8>>(b"\x1e[1]\n\x1e[2, \x1e[3]\n\x1e[4");
+        assert_eq!(values.next(), Some(Ok(vec![1])));
+        assert_eq!(values.next(), Some(Err(Error::EofWhileParsingValue)));
+        assert_eq!(values.next(), Some(Ok(vec![3])));
+        assert_eq!(values.next(), Some(Err(Error::EofWhileParsingList)));
+        assert_eq!(values.next(), None);
+
+        // a number at the end of a record may have been cut off
+        let mut values = json_seq::<u32>(b"\x1e123\x1e456\n");
+        assert_eq!(values.next(), Some(Err(Error::EofWhileParsingValue)));
+        assert_eq!(values.next(), Some(Ok(456)));
+        assert_eq!(values.next(), None);
+    }
+}
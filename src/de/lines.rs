@@ -0,0 +1,113 @@
+use core::marker::PhantomData;
+
+use serde::de;
+
+use crate::de::{from_slice, ErrorWithLine};
+
+/// Returns an iterator that deserializes one `T` from every line of newline-delimited JSON
+/// (also known as NDJSON or JSON Lines)
+///
+/// Lines are separated by `\n` or `\r\n`. Lines containing only whitespace are skipped. A line
+/// that fails to deserialize yields an error carrying its line number, after which iteration
+/// continues with the next line.
+pub fn lines<'a, T>(input: &'a [u8]) -> Lines<'a, T>
+where
+    T: de::Deserialize<'a>,
+{
+    Lines {
+        rest: input,
+        line: 0,
+        output: PhantomData,
+    }
+}
+
+/// Iterator over the records of newline-delimited JSON, created by [`lines`]
+pub struct Lines<'a, T> {
+    rest: &'a [u8],
+    /// Number of the last line returned, starting at 1
+    line: usize,
+    output: PhantomData<T>,
+}
+
+impl<'a, T> Iterator for Lines<'a, T>
+where
+    T: de::Deserialize<'a>,
+{
+    type Item = Result<T, ErrorWithLine>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if self.rest.is_empty() {
+                return None;
+            }
+            let (line, rest) = match self.rest.iter().position(|&b| b == b'\n') {
+                Some(newline) => (&self.rest[..newline], &self.rest[newline + 1..]),
+                None => (self.rest, &self.rest[self.rest.len()..]),
+            };
+            self.rest = rest;
+            self.line += 1;
+
+            if line.iter().all(|&b| b == b' ' || b == b'\t' || b == b'\r') {
+                continue;
+            }
+            let line_number = self.line;
+            return Some(from_slice(line).map_err(|error| ErrorWithLine {
+                error,
+                line: line_number,
+            }));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::lines;
+    use crate::de::Error;
+    use serde_derive::Deserialize;
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Record<'a> {
+        denom: &'a str,
+        amount: u64,
+    }
+
+    #[test]
+    fn ndjson() {
+        let input = b"{ \"denom\": \"uatom\", \"amount\": 1 }\r\n\n  \n{ \"denom\": \"ujuno\", \"amount\": 2 }\n";
+        let records: Result<Vec<Record<'_>>, _> = lines(input).collect();
+        assert_eq!(
+            records,
+            Ok(vec![
+                Record {
+                    denom: "uatom",
+                    amount: 1
+                },
+                Record {
+                    denom: "ujuno",
+                    amount: 2
+                }
+            ])
+        );
+
+        assert_eq!(lines::<u8>(b"").next(), None);
+        assert_eq!(lines::<u8>(b"\n\n").next(), None);
+        let numbers: Result<Vec<u8>, _> = lines(b"1\n2").collect();
+        assert_eq!(numbers, Ok(vec![1, 2]));
+    }
+
+    #[test]
+    fn ndjson_errors() {
+        let mut records = lines::<Record<'_>>(
+            b"{ \"denom\": \"uatom\", \"amount\": 1 }\n\n{ \"denom\": \"ujuno\" }\n{ \"denom\": \"uosmo\", \"amount\": 3 } 4\n{ \"denom\": \"uosmo\", \"amount\": 3 }",
+        );
+        assert!(records.next().unwrap().is_ok());
+        let err = records.next().unwrap().unwrap_err();
+        assert_eq!(err.line, 3);
+        assert_eq!(err.error, Error::MissingField("amount"));
+        assert_eq!(err.to_string(), "missing field `amount` on line 3");
+        let err = records.next().unwrap().unwrap_err();
+        assert_eq!((err.error, err.line), (Error::TrailingCharacters, 4));
+        assert!(records.next().unwrap().is_ok());
+        assert!(records.next().is_none());
+    }
+}
@@ -1,49 +1,160 @@
-use serde::de::{self, Visitor};
+use serde::de::{self, value::BorrowedStrDeserializer, IntoDeserializer, Visitor};
 
-use crate::de::{Deserializer, Error};
+use alloc::collections::BTreeSet;
+use alloc::string::String;
 
-pub struct MapAccess<'a, 'b> {
-    de: &'a mut Deserializer<'b>,
+use crate::de::read::Read;
+use crate::de::{Deserializer, DuplicateKeys, Error, PathSegment, StringLike};
+
+pub struct MapAccess<'a, 'b, R> {
+    de: &'a mut Deserializer<'b, R>,
     first: bool,
+    /// Byte range of the last key, without quotes
+    key: (usize, usize),
+    /// Number of members so far
+    len: usize,
+    /// Keys seen so far, unless duplicate keys are passed on
+    seen: BTreeSet<String>,
+    /// Field names of the struct being deserialized, if unknown fields are denied
+    fields: Option<&'static [&'static str]>,
 }
 
-impl<'a, 'b> MapAccess<'a, 'b> {
-    pub(crate) fn new(de: &'a mut Deserializer<'b>) -> Self {
-        MapAccess { de, first: true }
+impl<'a, 'b, R: Read<'b>> MapAccess<'a, 'b, R> {
+    pub(crate) fn new(de: &'a mut Deserializer<'b, R>) -> Self {
+        MapAccess {
+            de,
+            first: true,
+            key: (0, 0),
+            len: 0,
+            seen: BTreeSet::new(),
+            fields: None,
+        }
+    }
+
+    /// Creates an access to the members of a struct with the given fields
+    pub(crate) fn for_struct(
+        de: &'a mut Deserializer<'b, R>,
+        fields: &'static [&'static str],
+    ) -> Self {
+        let deny_unknown_fields = de.options.deny_unknown_fields;
+        let mut access = MapAccess::new(de);
+        if deny_unknown_fields {
+            access.fields = Some(fields);
+        }
+        access
+    }
+
+    /// Whether keys need to be looked at before they are passed on
+    fn checks_keys(&self) -> bool {
+        self.fields.is_some() || self.de.options.duplicate_keys != DuplicateKeys::LastWins
+    }
+
+    /// Applies [`Options::deny_unknown_fields`](crate::de::Options::deny_unknown_fields) and
+    /// [`Options::duplicate_keys`](crate::de::Options::duplicate_keys) to the member whose key
+    /// starts at the current position. Returns whether the member was a duplicate that was
+    /// skipped.
+    fn check_key(&mut self) -> Result<bool, Error> {
+        let start = self.de.read.checkpoint();
+        let key = match self.de.peek() {
+            Some(c) if self.de.is_quote(c) => self.de.parse_string()?.into_string(),
+            _ => match self.de.parse_unquoted_key() {
+                Some(key) => key.into_string(),
+                // not a key, which is reported by the caller
+                None => {
+                    self.de.read.release();
+                    return Ok(false);
+                }
+            },
+        };
+        if let Some(fields) = self.fields {
+            if !fields.contains(&key.as_str()) {
+                return Err(Error::UnknownField(key));
+            }
+        }
+        if self.de.options.duplicate_keys == DuplicateKeys::LastWins || self.seen.insert(key) {
+            self.de.read.rewind(start);
+            return Ok(false);
+        }
+        self.de.read.release();
+        match self.de.options.duplicate_keys {
+            DuplicateKeys::Reject => Err(Error::DuplicateKey),
+            _ => {
+                self.de.parse_object_colon()?;
+                self.de.skip_value()?;
+                Ok(true)
+            }
+        }
     }
 }
 
-impl<'a, 'de> de::MapAccess<'de> for MapAccess<'a, 'de> {
+impl<'a, 'de, R: Read<'de>> de::MapAccess<'de> for MapAccess<'a, 'de, R> {
     type Error = Error;
 
     fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>, Error>
     where
         K: de::DeserializeSeed<'de>,
     {
-        let peek = match self
-            .de
-            .parse_whitespace()
-            .ok_or(Error::EofWhileParsingObject)?
-        {
-            b'}' => return Ok(None),
-            b',' if !self.first => {
-                self.de.eat_char();
-                self.de.parse_whitespace()
-            }
-            b => {
-                if self.first {
-                    self.first = false;
-                    Some(b)
-                } else {
-                    return Err(Error::ExpectedObjectCommaOrEnd);
+        loop {
+            let peek = match self
+                .de
+                .parse_whitespace()
+                .ok_or(Error::EofWhileParsingObject)?
+            {
+                b'}' => return Ok(None),
+                b',' if !self.first => {
+                    self.de.eat_char();
+                    self.de.parse_whitespace()
+                }
+                b => {
+                    if self.first {
+                        self.first = false;
+                        Some(b)
+                    } else {
+                        return Err(Error::ExpectedObjectCommaOrEnd);
+                    }
+                }
+            };
+
+            let peek = peek.ok_or(Error::EofWhileParsingValue)?;
+            if peek != b'}' {
+                self.len += 1;
+                if let Some(max_object_len) = self.de.options.max_object_len {
+                    if self.len > max_object_len {
+                        return Err(Error::ObjectTooLong);
+                    }
+                }
+                if self.checks_keys() && self.check_key()? {
+                    continue;
                 }
             }
-        };
 
-        match peek.ok_or(Error::EofWhileParsingValue)? {
-            b'"' => seed.deserialize(MapKey { de: &mut *self.de }).map(Some),
-            b'}' => Err(Error::TrailingComma),
-            _ => Err(Error::KeyMustBeAString),
+            return match peek {
+                c if self.de.is_quote(c) => {
+                    let start = self.de.read.position() + 1;
+                    let key = seed.deserialize(MapKey { de: &mut *self.de })?;
+                    self.key = (start, self.de.read.position() - 1);
+                    Ok(Some(key))
+                }
+                b'}' if self.de.options.allow_trailing_commas => Ok(None),
+                b'}' => Err(Error::TrailingComma),
+                _ => {
+                    let start = self.de.read.position();
+                    let key = self
+                        .de
+                        .parse_unquoted_key()
+                        .ok_or(Error::KeyMustBeAString)?;
+                    let end = start + key.len();
+                    let key = match key {
+                        StringLike::Borrowed(key) => {
+                            let key = self.de.intern(key);
+                            seed.deserialize(BorrowedStrDeserializer::new(key))?
+                        }
+                        key => seed.deserialize(key.as_str().into_deserializer())?,
+                    };
+                    self.key = (start, end);
+                    Ok(Some(key))
+                }
+            };
         }
     }
 
@@ -53,22 +164,45 @@ impl<'a, 'de> de::MapAccess<'de> for MapAccess<'a, 'de> {
     {
         self.de.parse_object_colon()?;
 
-        seed.deserialize(&mut *self.de)
+        seed.deserialize(&mut *self.de).map_err(|e| {
+            self.de
+                .record_path(PathSegment::Key(self.key.0, self.key.1));
+            e
+        })
     }
 }
 
-struct MapKey<'a, 'b> {
-    de: &'a mut Deserializer<'b>,
+struct MapKey<'a, 'b, R> {
+    de: &'a mut Deserializer<'b, R>,
+}
+
+/// Integer keys are quoted, e.g. `{"42": true}`. The number is parsed directly between the
+/// quotes without any surrounding whitespace.
+macro_rules! deserialize_numeric_key {
+    ($self:ident, $visitor:ident, $deserialize:ident) => {{
+        // opening quote
+        let quote = $self.de.next_char();
+        match $self.de.peek() {
+            Some(b'-') | Some(b'0'..=b'9') => {}
+            _ => return Err(Error::InvalidNumber),
+        }
+        let value = $self.de.$deserialize($visitor)?;
+        if $self.de.next_char() == quote {
+            Ok(value)
+        } else {
+            Err(Error::InvalidNumber)
+        }
+    }};
 }
 
-impl<'de, 'a> de::Deserializer<'de> for MapKey<'a, 'de> {
+impl<'de, 'a, R: Read<'de>> de::Deserializer<'de> for MapKey<'a, 'de, R> {
     type Error = Error;
 
-    fn deserialize_any<V>(self, _visitor: V) -> Result<V::Value, Error>
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Error>
     where
         V: Visitor<'de>,
     {
-        unreachable!()
+        self.deserialize_str(visitor)
     }
 
     fn deserialize_bool<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
@@ -78,60 +212,74 @@ impl<'de, 'a> de::Deserializer<'de> for MapKey<'a, 'de> {
         unreachable!()
     }
 
-    fn deserialize_i8<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
+    fn deserialize_i8<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        unreachable!()
+        deserialize_numeric_key!(self, visitor, deserialize_i8)
     }
 
-    fn deserialize_i16<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
+    fn deserialize_i16<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        unreachable!()
+        deserialize_numeric_key!(self, visitor, deserialize_i16)
     }
 
-    fn deserialize_i32<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
+    fn deserialize_i32<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        unreachable!()
+        deserialize_numeric_key!(self, visitor, deserialize_i32)
     }
 
-    fn deserialize_i64<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
+    fn deserialize_i64<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        unreachable!()
+        deserialize_numeric_key!(self, visitor, deserialize_i64)
     }
 
-    fn deserialize_u8<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
+    fn deserialize_i128<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        unreachable!()
+        deserialize_numeric_key!(self, visitor, deserialize_i128)
     }
 
-    fn deserialize_u16<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
+    fn deserialize_u8<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        unreachable!()
+        deserialize_numeric_key!(self, visitor, deserialize_u8)
     }
 
-    fn deserialize_u32<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
+    fn deserialize_u16<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        unreachable!()
+        deserialize_numeric_key!(self, visitor, deserialize_u16)
     }
 
-    fn deserialize_u64<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
+    fn deserialize_u32<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        unreachable!()
+        deserialize_numeric_key!(self, visitor, deserialize_u32)
+    }
+
+    fn deserialize_u64<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        deserialize_numeric_key!(self, visitor, deserialize_u64)
+    }
+
+    fn deserialize_u128<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        deserialize_numeric_key!(self, visitor, deserialize_u128)
     }
 
     fn deserialize_f32<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
@@ -148,11 +296,11 @@ impl<'de, 'a> de::Deserializer<'de> for MapKey<'a, 'de> {
         unreachable!()
     }
 
-    fn deserialize_char<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
+    fn deserialize_char<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        unreachable!()
+        self.de.deserialize_char(visitor)
     }
 
     fn deserialize_str<V>(self, visitor: V) -> Result<V::Value, Self::Error>
@@ -162,11 +310,11 @@ impl<'de, 'a> de::Deserializer<'de> for MapKey<'a, 'de> {
         self.de.deserialize_str(visitor)
     }
 
-    fn deserialize_string<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
+    fn deserialize_string<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        unreachable!()
+        self.de.deserialize_string(visitor)
     }
 
     fn deserialize_bytes<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
@@ -266,14 +414,15 @@ impl<'de, 'a> de::Deserializer<'de> for MapKey<'a, 'de> {
 
     fn deserialize_enum<V>(
         self,
-        _name: &'static str,
-        _variants: &'static [&'static str],
-        _visitor: V,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
     ) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        unreachable!()
+        // Keys are always strings, so only unit variants can be found here
+        self.de.deserialize_enum(name, variants, visitor)
     }
 
     fn deserialize_identifier<V>(self, visitor: V) -> Result<V::Value, Self::Error>
@@ -1,44 +1,383 @@
 //! Deserialize JSON data to a Rust data structure
 
+mod array_iter;
+#[cfg(feature = "async")]
+mod async_read;
+#[cfg(feature = "embedded-io")]
+mod embedded;
 mod enum_;
 mod errors;
+mod events;
+#[cfg(feature = "float")]
+mod float;
+mod json_seq;
+mod lines;
 mod map;
+mod options;
+mod pointer;
+mod push;
+mod read;
 mod seq;
+mod stream;
 mod unescape;
-
-pub use errors::{Error, Result};
+#[cfg(feature = "utf16")]
+mod utf16;
+
+pub use array_iter::{array_iter, ArrayIter};
+#[cfg(feature = "async")]
+pub use async_read::{
+    from_async_read_fn, from_async_read_fn_with_options, from_async_reader,
+    from_async_reader_with_options, FromAsyncReadFn,
+};
+#[cfg(feature = "embedded-io")]
+pub use embedded::{from_embedded_io, from_embedded_io_with_options};
+#[cfg(feature = "type-context")]
+pub use errors::ErrorWithContext;
+pub use errors::{Error, ErrorWithLine, ErrorWithOffset, ErrorWithPath, JsonType, Result};
+pub use events::{events, Event, Events};
+pub use json_seq::{json_seq, JsonSeq};
+pub use lines::{lines, Lines};
+pub use options::{DuplicateKeys, LoneSurrogates, Options, DEFAULT_MAX_DEPTH};
+pub use pointer::{get_field, pointer, select};
+pub use push::{from_chunks, from_read_fn, from_read_fn_with_options, Feed, PushDeserializer};
+pub use stream::{raw_values, RawValues, StreamDeserializer};
+#[cfg(feature = "utf16")]
+pub use utf16::{from_utf16_slice, from_utf16le};
 
 use serde::de::{self, Visitor};
 
 use self::enum_::{StructVariantAccess, UnitVariantAccess};
 use self::map::MapAccess;
+use self::read::{Read, Reference, SliceRead};
 use self::seq::SeqAccess;
-use std::str::from_utf8;
+use crate::Category;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::marker::PhantomData;
+use core::str::{from_utf8, Utf8Error};
+
+/// UTF-8 encoding of U+FEFF, see [`Options::allow_byte_order_mark`]
+const BYTE_ORDER_MARK: &[u8] = b"\xEF\xBB\xBF";
 
 /// Deserializer will parse serde-json-wasm flavored JSON into a
 /// serde-annotated struct
-pub struct Deserializer<'b> {
-    slice: &'b [u8],
-    index: usize,
+///
+/// The `from_*` functions cover the usual cases. The deserializer can also be driven directly,
+/// e.g. by wrappers that instrument deserialization:
+///
+/// ```
+/// use serde::Deserialize;
+/// use serde_json_wasm::de::Deserializer;
+///
+/// let mut de = Deserializer::new(b"[1, 2]");
+/// let value = Vec::<u8>::deserialize(&mut de).unwrap();
+/// de.end().unwrap();
+/// assert_eq!(value, [1, 2]);
+/// ```
+///
+/// The input comes from a slice unless one of the streaming `from_*` functions set up another
+/// source for it.
+pub struct Deserializer<'b, R = SliceRead<'b>> {
+    read: R,
+    /// Set when the strings in the input were already unescaped by
+    /// `unescape::unescape_strings_in_place`
+    unescaped_in_place: bool,
+    /// Set to record `path` while an error propagates
+    track_path: bool,
+    /// Location of the last error, innermost segment first
+    path: Vec<PathSegment>,
+    /// Set to record `context` while an error propagates
+    #[cfg(feature = "type-context")]
+    track_context: bool,
+    /// Types and variants that were being deserialized when the last error occurred, innermost
+    /// first
+    #[cfg(feature = "type-context")]
+    context: Vec<ContextSegment>,
+    options: Options,
+    /// Number of arrays and objects that may still be opened
+    remaining_depth: usize,
+    /// Set when a block comment runs to the end of the input, which `end` reports
+    unterminated_comment: bool,
+    /// Copies unescaped strings to storage that lives as long as the input, see
+    /// [`from_slice_with_arena`]
+    str_arena: Option<StrArena<'b>>,
+    /// Set to pass strings without escape sequences to `str_arena` too, see
+    /// [`from_slice_with_interner`]
+    intern_strings: bool,
+    /// Buffer that strings with escape sequences are unescaped into, which is reused once the
+    /// string has been visited or copied by `str_arena`
+    scratch: Vec<u8>,
+}
+
+/// Storage that strings are copied to, so that they live for `'a`
+#[derive(Clone, Copy)]
+enum StrArena<'a> {
+    /// See [`from_slice_in`]
+    #[cfg(feature = "bumpalo")]
+    Bump(&'a bumpalo::Bump),
+    /// See [`from_slice_with_arena`] and [`from_slice_with_interner`]
+    Fn(&'a dyn Fn(&str) -> &'a str),
+}
+
+impl<'a> StrArena<'a> {
+    fn alloc_str(self, string: &str) -> &'a str {
+        match self {
+            #[cfg(feature = "bumpalo")]
+            StrArena::Bump(bump) => bump.alloc_str(string),
+            StrArena::Fn(alloc_str) => alloc_str(string),
+        }
+    }
 }
 
-enum StringLike<'a> {
+/// A type or enum variant that was being deserialized, see [`from_slice_with_context`]
+#[cfg(feature = "type-context")]
+enum ContextSegment {
+    /// Name of a struct or enum
+    Type(&'static str),
+    /// Byte range of the name of an enum variant in the input
+    Variant(usize, usize),
+}
+
+/// A step from a JSON container into one of its values
+enum PathSegment {
+    /// Index of an array element
+    Index(usize),
+    /// Byte range of an object key, without quotes
+    Key(usize, usize),
+}
+
+/// A string from the input, which is borrowed from it for `'a` if possible
+enum StringLike<'a, 's> {
     Borrowed(&'a str),
+    /// In a buffer of the source, see [`Reference::Copied`]
+    Copied(&'s str),
     Owned(String),
 }
 
+impl<'a, 's> StringLike<'a, 's> {
+    /// Checks that the bytes of the input in `reference` are UTF-8
+    fn from_utf8(reference: Reference<'a, 's>) -> core::result::Result<Self, Utf8Error> {
+        Ok(match reference {
+            Reference::Borrowed(bytes) => StringLike::Borrowed(from_utf8(bytes)?),
+            Reference::Copied(bytes) => StringLike::Copied(from_utf8(bytes)?),
+        })
+    }
+
+    fn as_str(&self) -> &str {
+        match self {
+            StringLike::Borrowed(string) => string,
+            StringLike::Copied(string) => string,
+            StringLike::Owned(string) => string,
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.as_str().len()
+    }
+
+    fn into_string(self) -> String {
+        match self {
+            StringLike::Owned(string) => string,
+            string => string.as_str().to_string(),
+        }
+    }
+
+    /// Passes the string to `visitor`, as borrowed from the input if it is
+    fn visit<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'a>,
+    {
+        match self {
+            StringLike::Borrowed(string) => visitor.visit_borrowed_str(string),
+            StringLike::Copied(string) => visitor.visit_str(string),
+            StringLike::Owned(string) => visitor.visit_string(string),
+        }
+    }
+}
+
 impl<'a> Deserializer<'a> {
-    fn new(slice: &'a [u8]) -> Deserializer<'_> {
-        Deserializer { slice, index: 0 }
+    /// Creates a deserializer that parses `slice` with the default [`Options`]
+    pub fn new(slice: &'a [u8]) -> Deserializer<'a> {
+        Deserializer::from_read(SliceRead::new(slice))
+    }
+
+    /// Creates a deserializer that parses `slice` using `options`
+    ///
+    /// [`Options::max_input_len`] is not checked here, but by
+    /// [`from_slice_with_options`] before it creates the deserializer.
+    pub fn with_options(slice: &'a [u8], options: &Options) -> Deserializer<'a> {
+        Deserializer::with_read(SliceRead::new(slice), options)
+    }
+
+    /// Returns the text of the next value, borrowed from the input
+    fn parse_raw_slice(&mut self) -> Result<&'a str> {
+        self.parse_whitespace().ok_or(Error::EofWhileParsingValue)?;
+        let start = self.read.index;
+        self.skip_value_checked(true)?;
+        from_utf8(&self.read.slice[start..self.read.index])
+            .map_err(|_| Error::InvalidUnicodeCodePoint)
+    }
+
+    /// Returns the text of the number at the current position, borrowed from the input
+    fn parse_number_slice(&mut self) -> Result<&'a str> {
+        let start = self.read.index;
+        self.parse_number_text()?;
+        // numbers are ASCII
+        Ok(from_utf8(&self.read.slice[start..self.read.index]).unwrap_or_default())
+    }
+
+    /// Renders the recorded path like `messages[3].amount.denom`
+    fn path_to_string(&self) -> String {
+        let mut out = String::new();
+        for segment in self.path.iter().rev() {
+            match *segment {
+                PathSegment::Index(index) => {
+                    out.push('[');
+                    out.push_str(&index.to_string());
+                    out.push(']');
+                }
+                PathSegment::Key(start, end) => {
+                    if !out.is_empty() {
+                        out.push('.');
+                    }
+                    out.push_str(&String::from_utf8_lossy(&self.read.slice[start..end]));
+                }
+            }
+        }
+        out
+    }
+
+    /// Renders the recorded context like `ExecuteMsg::Transfer > Coin`
+    #[cfg(feature = "type-context")]
+    fn context_to_string(&self) -> String {
+        let mut out = String::new();
+        for segment in self.context.iter().rev() {
+            match *segment {
+                ContextSegment::Type(name) => {
+                    if !out.is_empty() {
+                        out.push_str(" > ");
+                    }
+                    out.push_str(name);
+                }
+                ContextSegment::Variant(start, end) => {
+                    out.push_str("::");
+                    out.push_str(&String::from_utf8_lossy(&self.read.slice[start..end]));
+                }
+            }
+        }
+        out
+    }
+}
+
+impl<'a, R: Read<'a>> Deserializer<'a, R> {
+    /// Creates a deserializer that parses the input of `read` with the default [`Options`]
+    fn from_read(read: R) -> Self {
+        Deserializer {
+            read,
+            unescaped_in_place: false,
+            track_path: false,
+            path: Vec::new(),
+            #[cfg(feature = "type-context")]
+            track_context: false,
+            #[cfg(feature = "type-context")]
+            context: Vec::new(),
+            options: Options::default(),
+            remaining_depth: DEFAULT_MAX_DEPTH,
+            unterminated_comment: false,
+            str_arena: None,
+            intern_strings: false,
+            scratch: Vec::new(),
+        }
+    }
+
+    /// Creates a deserializer that parses the input of `read` using `options`
+    ///
+    /// [`Options::max_input_len`] is left to `read`, or to the caller for slices.
+    fn with_read(read: R, options: &Options) -> Self {
+        let mut de = Deserializer::from_read(read);
+        de.options = options.clone();
+        de.remaining_depth = options.max_depth;
+        if options.allow_byte_order_mark && de.peek() == Some(BYTE_ORDER_MARK[0]) {
+            let start = de.read.checkpoint();
+            if BYTE_ORDER_MARK.iter().all(|&b| de.next_char() == Some(b)) {
+                de.read.release();
+            } else {
+                de.read.rewind(start);
+            }
+        }
+        de
+    }
+
+    /// Returns the error of the source that ended the input early, which caused `result` to fail
+    /// if it did, or else `result`
+    fn check_read<T>(&mut self, result: Result<T>) -> Result<T> {
+        match self.read.take_error() {
+            Some(error) => Err(error),
+            None => result,
+        }
+    }
+
+    /// Records that the error currently propagating occurred inside `segment`
+    fn record_path(&mut self, segment: PathSegment) {
+        if self.track_path {
+            self.path.push(segment);
+        }
+    }
+
+    /// Runs `deserialize`, recording the type `name` if it fails
+    #[cfg_attr(not(feature = "type-context"), allow(unused_variables))]
+    fn in_type<T>(
+        &mut self,
+        name: &'static str,
+        deserialize: impl FnOnce(&mut Self) -> Result<T>,
+    ) -> Result<T> {
+        let result = deserialize(self);
+        #[cfg(feature = "type-context")]
+        {
+            // struct variants are deserialized as structs without a name
+            if result.is_err() && self.track_context && !name.is_empty() {
+                self.context.push(ContextSegment::Type(name));
+            }
+        }
+        result
+    }
+
+    /// Records that the error currently propagating occurred in the enum variant whose name is
+    /// at `start..end`
+    #[cfg_attr(not(feature = "type-context"), allow(unused_variables))]
+    fn record_variant(&mut self, start: usize, end: usize) {
+        #[cfg(feature = "type-context")]
+        {
+            if self.track_context {
+                self.context.push(ContextSegment::Variant(start, end));
+            }
+        }
     }
 
     fn eat_char(&mut self) {
-        self.index += 1;
+        self.read.discard();
+    }
+
+    /// Accounts for entering an array or object
+    fn enter_nested(&mut self) -> Result<()> {
+        self.remaining_depth = self
+            .remaining_depth
+            .checked_sub(1)
+            .ok_or(Error::RecursionLimitExceeded)?;
+        Ok(())
+    }
+
+    /// Accounts for leaving an array or object entered with `enter_nested`
+    fn leave_nested(&mut self) {
+        self.remaining_depth += 1;
     }
 
-    fn end(&mut self) -> Result<()> {
+    /// Checks that only whitespace is left after the deserialized value. Call this once the
+    /// value was deserialized to reject trailing data like [`from_slice`] does.
+    pub fn end(&mut self) -> Result<()> {
         match self.parse_whitespace() {
             Some(_) => Err(Error::TrailingCharacters),
+            None if self.unterminated_comment => Err(Error::EofWhileParsingComment),
             None => Ok(()),
         }
     }
@@ -52,6 +391,10 @@ impl<'a> Deserializer<'a> {
             b',' => {
                 self.eat_char();
                 match self.parse_whitespace() {
+                    Some(b']') if self.options.allow_trailing_commas => {
+                        self.eat_char();
+                        Ok(())
+                    }
                     Some(b']') => Err(Error::TrailingComma),
                     _ => Err(Error::TrailingCharacters),
                 }
@@ -69,19 +412,23 @@ impl<'a> Deserializer<'a> {
                 self.eat_char();
                 Ok(())
             }
+            b',' if self.options.allow_trailing_commas => {
+                self.eat_char();
+                match self.parse_whitespace() {
+                    Some(b'}') => {
+                        self.eat_char();
+                        Ok(())
+                    }
+                    _ => Err(Error::TrailingCharacters),
+                }
+            }
             b',' => Err(Error::TrailingComma),
             _ => Err(Error::TrailingCharacters),
         }
     }
 
     fn next_char(&mut self) -> Option<u8> {
-        let ch = self.slice.get(self.index);
-
-        if ch.is_some() {
-            self.index += 1;
-        }
-
-        ch.cloned()
+        self.read.next()
     }
 
     fn parse_ident(&mut self, ident: &[u8]) -> Result<()> {
@@ -107,36 +454,47 @@ impl<'a> Deserializer<'a> {
         }
     }
 
-    fn parse_string(&mut self) -> Result<StringLike<'a>> {
-        let start = self.index;
+    /// Parses the string at the current position, starting with its opening quote
+    fn parse_string(&mut self) -> Result<StringLike<'a, '_>> {
+        let quote = self.next_char().ok_or(Error::EofWhileParsingString)?;
+        let max_string_len = self.options.max_string_len;
+        if self.unescaped_in_place && self.peek() == Some(unescape::UNESCAPED_MARKER) {
+            self.eat_char();
+            let start = self.read.checkpoint();
+            loop {
+                match self.next_char() {
+                    Some(c) if c == unescape::UNESCAPED_MARKER => break,
+                    Some(_) => {}
+                    None => return Err(Error::EofWhileParsingString),
+                }
+            }
+            let end = self.read.position() - 1;
+            self.read.release();
+            check_string_len(end - start, max_string_len)?;
+            return StringLike::from_utf8(self.read.bytes(start, end))
+                .map_err(|e| Error::InvalidUtf8(start + e.valid_up_to()));
+        }
+
+        let start = self.read.checkpoint();
         let mut contains_backslash = false;
         let mut escaped = false;
         loop {
             match self.peek() {
-                Some(b'"') => {
-                    if escaped {
-                        escaped = false;
-                        self.eat_char(); // just continue
-                    } else {
-                        let end = self.index;
-                        self.eat_char();
-                        return if contains_backslash {
-                            Ok(StringLike::Owned(unescape::unescape(
-                                &self.slice[start..end],
-                            )?))
-                        } else {
-                            Ok(StringLike::Borrowed(
-                                from_utf8(&self.slice[start..end])
-                                    .map_err(|_| Error::InvalidUnicodeCodePoint)?,
-                            ))
-                        };
+                Some(c) if c == quote => {
+                    if !escaped {
+                        break;
                     }
+                    escaped = false;
+                    self.eat_char(); // just continue
                 }
                 Some(b'\\') => {
                     contains_backslash = true;
                     escaped = !escaped;
                     self.eat_char()
                 }
+                Some(0x00..=0x1F) if !self.options.allow_control_characters => {
+                    return Err(Error::ControlCharacterInString);
+                }
                 Some(_) => {
                     escaped = false;
                     self.eat_char()
@@ -144,6 +502,111 @@ impl<'a> Deserializer<'a> {
                 None => return Err(Error::EofWhileParsingString),
             }
         }
+        let end = self.read.position();
+        self.eat_char();
+        self.read.release();
+
+        let options = &self.options;
+        let content = self.read.bytes(start, end);
+        // escape sequences are ASCII, so this also checks escaped strings
+        let checked = StringLike::from_utf8(content);
+        if let Err(e) = &checked {
+            if !options.replace_invalid_utf8 {
+                return Err(Error::InvalidUtf8(start + e.valid_up_to()));
+            }
+        }
+        let string = if contains_backslash {
+            let rules = unescape::StringRules {
+                quote,
+                allow_control_characters: options.allow_control_characters,
+                replace_lone_surrogates: options.lone_surrogates == LoneSurrogates::Replace,
+                replace_invalid_utf8: options.replace_invalid_utf8,
+                max_len: max_string_len,
+            };
+            match self.str_arena {
+                Some(arena) => {
+                    unescape::unescape_into(&content, rules, &mut self.scratch)?;
+                    StringLike::Borrowed(match from_utf8(&self.scratch) {
+                        Ok(string) => arena.alloc_str(string),
+                        Err(_) if rules.replace_invalid_utf8 => {
+                            arena.alloc_str(&String::from_utf8_lossy(&self.scratch))
+                        }
+                        // unescaping keeps invalid UTF-8 as it is, so the content was invalid
+                        // already
+                        Err(_) => {
+                            let valid_up_to = checked.err().map_or(0, |e| e.valid_up_to());
+                            return Err(Error::InvalidUtf8(start + valid_up_to));
+                        }
+                    })
+                }
+                None => StringLike::Owned(unescape::unescape(
+                    &content,
+                    rules,
+                    core::mem::take(&mut self.scratch),
+                )?),
+            }
+        } else {
+            check_string_len(content.len(), max_string_len)?;
+            match (checked, self.str_arena) {
+                // strings from the input are interned like the unescaped ones
+                (Ok(string), Some(arena)) if self.intern_strings => {
+                    StringLike::Borrowed(arena.alloc_str(string.as_str()))
+                }
+                (Ok(string), _) => string,
+                (Err(_), _) => StringLike::Owned(String::from_utf8_lossy(&content).into_owned()),
+            }
+        };
+        // replacing invalid UTF-8 may have made the string longer
+        check_string_len(string.len(), max_string_len)?;
+        Ok(string)
+    }
+
+    /// Passes a string from the input to `str_arena` if all strings are interned
+    fn intern(&self, string: &'a str) -> &'a str {
+        match self.str_arena {
+            Some(arena) if self.intern_strings => arena.alloc_str(string),
+            _ => string,
+        }
+    }
+
+    /// Returns whether `c` starts a string
+    fn is_quote(&self, c: u8) -> bool {
+        c == b'"' || (c == b'\'' && self.options.allow_single_quotes)
+    }
+
+    /// Parses an unquoted object key, like `amount` in `{amount: "1"}`, if unquoted keys are
+    /// allowed and the next character starts one. Keys are limited to ASCII identifiers.
+    fn parse_unquoted_key(&mut self) -> Option<StringLike<'a, '_>> {
+        if !self.options.allow_unquoted_keys {
+            return None;
+        }
+        match self.peek() {
+            Some(c) if c.is_ascii_alphabetic() || c == b'_' || c == b'$' => {}
+            _ => return None,
+        }
+        let start = self.read.checkpoint();
+        while let Some(c) = self.peek() {
+            if !(c.is_ascii_alphanumeric() || c == b'_' || c == b'$') {
+                break;
+            }
+            self.eat_char();
+        }
+        let end = self.read.position();
+        self.read.release();
+        // ASCII only, so this cannot fail
+        StringLike::from_utf8(self.read.bytes(start, end)).ok()
+    }
+
+    /// Consumes the `x` of a hexadecimal integer after its leading `0`, if hexadecimal integers
+    /// are allowed
+    fn eat_hex_prefix(&mut self) -> bool {
+        match self.peek() {
+            Some(b'x') | Some(b'X') if self.options.allow_hex_integers => {
+                self.eat_char();
+                true
+            }
+            _ => false,
+        }
     }
 
     /// Consumes all the whitespace characters and returns a peek into the next character
@@ -153,6 +616,7 @@ impl<'a> Deserializer<'a> {
                 Some(b' ') | Some(b'\n') | Some(b'\t') | Some(b'\r') => {
                     self.eat_char();
                 }
+                Some(b'/') if self.options.allow_comments && self.skip_comment() => {}
                 other => {
                     return other;
                 }
@@ -160,444 +624,1820 @@ impl<'a> Deserializer<'a> {
         }
     }
 
-    fn peek(&mut self) -> Option<u8> {
-        self.slice.get(self.index).cloned()
-    }
-}
-
-// NOTE(deserialize_*signed) we avoid parsing into u64 and then casting to a smaller integer, which
-// is what upstream does, to avoid pulling in 64-bit compiler intrinsics, which waste a few KBs of
-// Flash, when targeting non 64-bit architectures
-macro_rules! deserialize_unsigned {
-    ($self:ident, $visitor:ident, $uxx:ident, $visit_uxx:ident) => {{
-        let peek = $self
-            .parse_whitespace()
-            .ok_or(Error::EofWhileParsingValue)?;
-
-        match peek {
-            b'-' => Err(Error::InvalidNumber),
-            b'0' => {
-                $self.eat_char();
-                $visitor.$visit_uxx(0)
-            }
-            b'1'..=b'9' => {
-                $self.eat_char();
-
-                let mut number = (peek - b'0') as $uxx;
+    /// Skips the comment starting at the current `/`, if there is one. An unterminated block
+    /// comment extends to the end of the input and is reported by `end`.
+    fn skip_comment(&mut self) -> bool {
+        let start = self.read.checkpoint();
+        self.eat_char();
+        match self.next_char() {
+            Some(b'/') => while self.next_char().map_or(false, |c| c != b'\n') {},
+            Some(b'*') => {
+                let mut star = false;
                 loop {
-                    match $self.peek() {
-                        Some(c @ b'0'..=b'9') => {
-                            $self.eat_char();
-                            number = number
-                                .checked_mul(10)
-                                .ok_or(Error::InvalidNumber)?
-                                .checked_add((c - b'0') as $uxx)
-                                .ok_or(Error::InvalidNumber)?;
+                    match self.next_char() {
+                        Some(b'/') if star => break,
+                        Some(c) => star = c == b'*',
+                        None => {
+                            self.unterminated_comment = true;
+                            break;
                         }
-                        _ => return $visitor.$visit_uxx(number),
                     }
                 }
             }
-            _ => Err(Error::InvalidType),
+            _ => {
+                self.read.rewind(start);
+                return false;
+            }
         }
-    }};
-}
+        self.read.release();
+        true
+    }
 
-macro_rules! deserialize_signed {
-    ($self:ident, $visitor:ident, $ixx:ident, $visit_ixx:ident) => {{
-        let signed = match $self
-            .parse_whitespace()
-            .ok_or(Error::EofWhileParsingValue)?
+    fn peek(&mut self) -> Option<u8> {
+        self.read.peek()
+    }
+
+    /// Parses an integer of unknown size, using the smallest of `u64`/`i64` and `u128`/`i128`
+    /// that fits
+    fn deserialize_any_integer<V>(&mut self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'a>,
+    {
+        #[cfg_attr(not(feature = "float"), allow(unused_variables))]
+        let number_start = self.read.checkpoint();
+        let negative = self.peek() == Some(b'-');
+        if negative {
+            self.eat_char();
+        }
+        #[cfg(feature = "float")]
         {
-            b'-' => {
-                $self.eat_char();
-                true
+            if negative && self.peek() == Some(b'I') {
+                self.read.rewind(number_start);
+                return de::Deserializer::deserialize_f64(self, visitor);
             }
-            _ => false,
-        };
-
-        match $self.peek().ok_or(Error::EofWhileParsingValue)? {
-            b'0' => {
-                $self.eat_char();
-                $visitor.$visit_ixx(0)
+        }
+        let mut start = self.read.position();
+        let mut radix = 10;
+        match self.next_char() {
+            Some(b'0') if self.eat_hex_prefix() => {
+                start = self.read.position();
+                radix = 16;
+                while self.peek().map_or(false, |c| c.is_ascii_hexdigit()) {
+                    self.eat_char();
+                }
             }
-            c @ b'1'..=b'9' => {
-                $self.eat_char();
-
-                let mut number = (c - b'0') as $ixx * if signed { -1 } else { 1 };
-                loop {
-                    match $self.peek() {
-                        Some(c @ b'0'..=b'9') => {
-                            $self.eat_char();
-                            number = number
-                                .checked_mul(10)
-                                .ok_or(Error::InvalidNumber)?
-                                .checked_add((c - b'0') as $ixx * if signed { -1 } else { 1 })
-                                .ok_or(Error::InvalidNumber)?;
-                        }
-                        _ => return $visitor.$visit_ixx(number),
-                    }
+            Some(b'0') => {}
+            Some(b'1'..=b'9') => self.eat_digits(),
+            _ => return Err(Error::InvalidNumber),
+        }
+        if let Some(b'.') | Some(b'e') | Some(b'E') = self.peek() {
+            self.check_not_float()?;
+            #[cfg(feature = "float")]
+            {
+                if radix == 10 {
+                    self.read.rewind(number_start);
+                    return de::Deserializer::deserialize_f64(self, visitor);
                 }
             }
-            _ => return Err(Error::InvalidType),
+            // floats are not supported
+            return Err(Error::InvalidNumber);
         }
-    }};
-}
-
-impl<'a, 'de> de::Deserializer<'de> for &'a mut Deserializer<'de> {
-    type Error = Error;
+        self.check_number_end()?;
 
-    /// Unsupported. We rely on typed deserialization methods, even if a JSON
-    /// has enough type information to detect types in many cases.
-    ///
-    /// See https://serde.rs/impl-deserialize.html to learn more about the differentiation
-    /// between `deserialize_{type}` and `deserialize_any`.
-    fn deserialize_any<V>(self, _visitor: V) -> Result<V::Value>
-    where
-        V: Visitor<'de>,
-    {
-        unreachable!()
+        let end = self.read.position();
+        self.read.release();
+        let digits = self.read.bytes(start, end);
+        if digits.is_empty() {
+            return Err(Error::InvalidNumber);
+        }
+        if negative {
+            let number = parse_negative_integer(&digits, radix).ok_or(Error::NumberOutOfRange)?;
+            if number >= core::i64::MIN as i128 {
+                visitor.visit_i64(number as i64)
+            } else {
+                visitor.visit_i128(number)
+            }
+        } else {
+            let number = parse_unsigned_integer(&digits, radix).ok_or(Error::NumberOutOfRange)?;
+            if number <= core::u64::MAX as u128 {
+                visitor.visit_u64(number as u64)
+            } else {
+                visitor.visit_u128(number)
+            }
+        }
     }
 
-    fn deserialize_bool<V>(self, visitor: V) -> Result<V::Value>
-    where
-        V: Visitor<'de>,
-    {
-        let peek = self.parse_whitespace().ok_or(Error::EofWhileParsingValue)?;
+    /// Consumes a number with an optional fraction and exponent and returns its text
+    fn parse_number_text(&mut self) -> Result<StringLike<'a, '_>> {
+        let start = self.read.checkpoint();
+        self.parse_number()?;
+        let end = self.read.position();
+        self.read.release();
+        // ASCII only, so this cannot fail
+        StringLike::from_utf8(self.read.bytes(start, end)).map_err(|_| Error::InvalidNumber)
+    }
 
-        match peek {
-            b't' => {
-                self.eat_char();
-                self.parse_ident(b"rue")?;
-                visitor.visit_bool(true)
-            }
-            b'f' => {
+    /// Consumes a number with an optional fraction and exponent, following the JSON grammar
+    fn parse_number(&mut self) -> Result<()> {
+        if self.peek() == Some(b'-') {
+            self.eat_char();
+        }
+        match self.next_char() {
+            Some(b'0') => {}
+            Some(b'1'..=b'9') => self.eat_digits(),
+            _ => return Err(Error::InvalidNumber),
+        }
+        self.check_not_float()?;
+        if self.peek() == Some(b'.') {
+            self.eat_char();
+            self.eat_required_digits()?;
+        }
+        if let Some(b'e') | Some(b'E') = self.peek() {
+            self.eat_char();
+            if let Some(b'+') | Some(b'-') = self.peek() {
                 self.eat_char();
-                self.parse_ident(b"alse")?;
-                visitor.visit_bool(false)
             }
-            _ => Err(Error::InvalidType),
+            self.eat_required_digits()?;
         }
+        self.check_number_end()
     }
 
-    fn deserialize_i8<V>(self, visitor: V) -> Result<V::Value>
-    where
-        V: Visitor<'de>,
-    {
-        deserialize_signed!(self, visitor, i8, visit_i8)
+    /// Consumes a number like [`Deserializer::parse_number`], or one of the hexadecimal integers
+    /// and non-finite floats allowed by the options
+    fn skip_number(&mut self) -> Result<()> {
+        #[cfg(feature = "float")]
+        {
+            if self.parse_non_finite()?.is_some() {
+                return Ok(());
+            }
+        }
+        let start = self.read.checkpoint();
+        if self.peek() == Some(b'-') {
+            self.eat_char();
+        }
+        if self.peek() == Some(b'0') {
+            self.eat_char();
+            if self.eat_hex_prefix() {
+                self.read.release();
+                let digits = self.read.position();
+                while self.peek().map_or(false, |c| c.is_ascii_hexdigit()) {
+                    self.eat_char();
+                }
+                if self.read.position() == digits {
+                    return Err(Error::InvalidNumber);
+                }
+                return self.check_number_end();
+            }
+        }
+        self.read.rewind(start);
+        self.parse_number()
     }
 
-    fn deserialize_i16<V>(self, visitor: V) -> Result<V::Value>
-    where
-        V: Visitor<'de>,
-    {
-        deserialize_signed!(self, visitor, i16, visit_i16)
+    /// With [`Options::allow_exact_float_integers`], consumes a number with a fraction or
+    /// exponent whose value is an integer, like `2.5e2`, and returns its digits. Other numbers are
+    /// left to be parsed as usual, and `None` is returned without consuming anything.
+    fn parse_exact_integer(&mut self) -> Result<Option<ExactInteger>> {
+        match self.peek() {
+            Some(b'-') | Some(b'0'..=b'9') if self.options.allow_exact_float_integers => {}
+            _ => return Ok(None),
+        }
+        let start = self.read.checkpoint();
+        let parsed = self.parse_number().is_ok();
+        let end = self.read.position();
+        let text = self.read.bytes(start, end);
+        if !parsed || !text.iter().any(|&c| c == b'.' || c == b'e' || c == b'E') {
+            self.read.rewind(start);
+            return Ok(None);
+        }
+        let integer = exact_integer(&text);
+        self.read.release();
+        integer.map(Some)
     }
 
-    fn deserialize_i32<V>(self, visitor: V) -> Result<V::Value>
-    where
-        V: Visitor<'de>,
-    {
-        deserialize_signed!(self, visitor, i32, visit_i32)
-    }
-
-    fn deserialize_i64<V>(self, visitor: V) -> Result<V::Value>
-    where
-        V: Visitor<'de>,
-    {
-        deserialize_signed!(self, visitor, i64, visit_i64)
-    }
-
-    fn deserialize_u8<V>(self, visitor: V) -> Result<V::Value>
-    where
-        V: Visitor<'de>,
-    {
-        deserialize_unsigned!(self, visitor, u8, visit_u8)
-    }
-
-    fn deserialize_u16<V>(self, visitor: V) -> Result<V::Value>
-    where
-        V: Visitor<'de>,
-    {
-        deserialize_unsigned!(self, visitor, u16, visit_u16)
+    /// With [`Options::strict_numbers`], rejects a number that continues after the part that
+    /// was parsed, like `01` or `1.`
+    fn check_number_end(&mut self) -> Result<()> {
+        self.check_not_float()?;
+        match self.peek() {
+            Some(b'0'..=b'9') | Some(b'.') | Some(b'e') | Some(b'E')
+                if self.options.strict_numbers =>
+            {
+                Err(Error::InvalidNumber)
+            }
+            _ => Ok(()),
+        }
     }
 
-    fn deserialize_u32<V>(self, visitor: V) -> Result<V::Value>
-    where
-        V: Visitor<'de>,
-    {
-        deserialize_unsigned!(self, visitor, u32, visit_u32)
+    /// With [`Options::deny_floats`], rejects a fraction or exponent after the integer part of a
+    /// number
+    fn check_not_float(&mut self) -> Result<()> {
+        match self.peek() {
+            Some(b'.') | Some(b'e') | Some(b'E') if self.options.deny_floats => {
+                Err(Error::FloatNotAllowed)
+            }
+            _ => Ok(()),
+        }
     }
 
-    fn deserialize_u64<V>(self, visitor: V) -> Result<V::Value>
-    where
-        V: Visitor<'de>,
-    {
-        deserialize_unsigned!(self, visitor, u64, visit_u64)
+    /// Returns the error for the unexpected byte `c` where a number was expected
+    fn unexpected_number_byte(&self, c: u8) -> Error {
+        match c {
+            b'+' | b'.' if self.options.strict_numbers => Error::InvalidNumber,
+            _ => Error::unexpected_byte(c),
+        }
     }
 
-    fn deserialize_f32<V>(self, _visitor: V) -> Result<V::Value>
-    where
-        V: Visitor<'de>,
-    {
-        unreachable!()
+    /// Consumes `NaN`, `Infinity` or `-Infinity`, if non-finite floats are allowed and the input
+    /// continues with one of them
+    #[cfg(feature = "float")]
+    fn parse_non_finite(&mut self) -> Result<Option<NonFinite>> {
+        if !self.options.allow_non_finite_floats {
+            return Ok(None);
+        }
+        let (ident, non_finite): (&[u8], _) = match self.peek() {
+            Some(b'N') => (b"NaN", NonFinite::NaN),
+            Some(b'I') => (b"Infinity", NonFinite::Infinity),
+            Some(b'-') => {
+                let start = self.read.checkpoint();
+                self.eat_char();
+                if self.peek() != Some(b'I') {
+                    self.read.rewind(start);
+                    return Ok(None);
+                }
+                self.read.release();
+                (b"Infinity", NonFinite::NegInfinity)
+            }
+            _ => return Ok(None),
+        };
+        self.parse_ident(ident)?;
+        Ok(Some(non_finite))
     }
 
-    fn deserialize_f64<V>(self, _visitor: V) -> Result<V::Value>
-    where
-        V: Visitor<'de>,
-    {
-        unreachable!()
+    fn eat_digits(&mut self) {
+        while let Some(b'0'..=b'9') = self.peek() {
+            self.eat_char();
+        }
     }
 
-    fn deserialize_char<V>(self, _visitor: V) -> Result<V::Value>
-    where
-        V: Visitor<'de>,
-    {
-        unreachable!()
+    /// Consumes one or more digits
+    fn eat_required_digits(&mut self) -> Result<()> {
+        match self.next_char() {
+            Some(b'0'..=b'9') => {
+                self.eat_digits();
+                Ok(())
+            }
+            _ => Err(Error::InvalidNumber),
+        }
     }
 
-    fn deserialize_str<V>(self, visitor: V) -> Result<V::Value>
-    where
-        V: Visitor<'de>,
-    {
-        self.deserialize_string(visitor)
+    /// Skips over the next JSON value
+    ///
+    /// Nested arrays and objects are tracked on an explicit stack rather than by recursion, so
+    /// skipping deeply nested input cannot overflow the call stack.
+    fn skip_value(&mut self) -> Result<()> {
+        self.skip_value_checked(false)
     }
 
-    fn deserialize_string<V>(self, visitor: V) -> Result<V::Value>
-    where
-        V: Visitor<'de>,
-    {
-        let peek = self.parse_whitespace().ok_or(Error::EofWhileParsingValue)?;
+    /// Skips over a value like [`Deserializer::skip_value`]. With `validate`, the numbers and
+    /// literals in it are checked to be valid JSON.
+    fn skip_value_checked(&mut self, validate: bool) -> Result<()> {
+        // Closing brackets and numbers of elements or members of the arrays and objects that are
+        // currently open
+        let mut open: Vec<(u8, usize)> = Vec::new();
+        let mut after_comma = false;
+        loop {
+            // expecting a value
+            match self.parse_whitespace().ok_or(Error::EofWhileParsingValue)? {
+                c @ b'[' | c @ b'{' => {
+                    if open.len() >= self.remaining_depth {
+                        return Err(Error::RecursionLimitExceeded);
+                    }
+                    self.eat_char();
+                    after_comma = false;
+                    let close = if c == b'[' { b']' } else { b'}' };
+                    match self.parse_whitespace() {
+                        Some(c) if c == close => {
+                            self.eat_char();
+                        }
+                        _ => {
+                            self.check_len(close, 1)?;
+                            open.push((close, 1));
+                            if close == b'}' {
+                                self.skip_object_key()?;
+                            }
+                            continue;
+                        }
+                    }
+                }
+                c if self.is_quote(c) => {
+                    self.parse_string()?;
+                }
+                b']' | b'}' if after_comma => return Err(Error::TrailingComma),
+                c @ b',' | c @ b'}' | c @ b']' => return Err(Error::ExpectedSomeValue(c)),
+                b'-' | b'0'..=b'9' if validate => self.skip_number()?,
+                #[cfg(feature = "float")]
+                b'N' | b'I' if validate && self.options.allow_non_finite_floats => {
+                    self.skip_number()?
+                }
+                b'-' | b'0'..=b'9' if self.options.strict_numbers || self.options.deny_floats => {
+                    self.parse_number()?
+                }
+                b'+' | b'.' if validate || self.options.strict_numbers => {
+                    return Err(Error::InvalidNumber)
+                }
+                b't' if validate => self.parse_ident(b"true")?,
+                b'f' if validate => self.parse_ident(b"false")?,
+                b'n' if validate => self.parse_ident(b"null")?,
+                c if validate => return Err(Error::ExpectedSomeValue(c)),
+                // If it’s something else then we chomp until we get to an end delimiter.
+                // This does technically allow for illegal JSON since we’re just ignoring
+                // characters rather than parsing them.
+                _ => loop {
+                    match self.peek() {
+                        Some(b',') | Some(b'}') | Some(b']') => break,
+                        Some(b'/') if self.options.allow_comments => break,
+                        Some(_) => self.eat_char(),
+                        None => return Err(Error::EofWhileParsingString),
+                    }
+                },
+            }
 
-        match peek {
-            b'"' => {
-                self.eat_char();
-                let str_like = self.parse_string()?;
-                match str_like {
-                    StringLike::Borrowed(str) => visitor.visit_borrowed_str(str),
-                    StringLike::Owned(string) => visitor.visit_string(string),
+            // after a value, close containers until one continues with a comma
+            loop {
+                let (close, len) = match open.last_mut() {
+                    Some(&mut (close, ref mut len)) => (close, len),
+                    None => return Ok(()),
+                };
+                match self.parse_whitespace() {
+                    Some(b',') => {
+                        self.eat_char();
+                        if self.options.allow_trailing_commas
+                            && self.parse_whitespace() == Some(close)
+                        {
+                            continue;
+                        }
+                        *len += 1;
+                        let len = *len;
+                        self.check_len(close, len)?;
+                        if close == b'}' {
+                            self.skip_object_key()?;
+                        }
+                        after_comma = close == b']';
+                        break;
+                    }
+                    Some(c) if c == close => {
+                        self.eat_char();
+                        open.pop();
+                    }
+                    Some(_) if close == b']' => return Err(Error::ExpectedListCommaOrEnd),
+                    Some(_) => return Err(Error::ExpectedObjectCommaOrEnd),
+                    None if close == b']' => return Err(Error::EofWhileParsingList),
+                    None => return Err(Error::EofWhileParsingObject),
                 }
             }
-            _ => Err(Error::InvalidType),
         }
     }
 
-    /// Unsupported
-    fn deserialize_bytes<V>(self, _visitor: V) -> Result<V::Value>
-    where
-        V: Visitor<'de>,
-    {
-        unreachable!()
-    }
-
-    /// Unsupported
-    fn deserialize_byte_buf<V>(self, _visitor: V) -> Result<V::Value>
-    where
-        V: Visitor<'de>,
-    {
-        unreachable!()
+    /// Consumes a value, which is checked to be valid JSON, and returns its text
+    fn parse_raw_value(&mut self) -> Result<StringLike<'a, '_>> {
+        self.parse_whitespace().ok_or(Error::EofWhileParsingValue)?;
+        let start = self.read.checkpoint();
+        self.skip_value_checked(true)?;
+        let end = self.read.position();
+        self.read.release();
+        StringLike::from_utf8(self.read.bytes(start, end))
+            .map_err(|_| Error::InvalidUnicodeCodePoint)
     }
 
-    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value>
+    /// Deserializes a number from the content of the string at the current position, like
+    /// `"12345"`, using `deserialize` to parse it
+    ///
+    /// The content must be the whole number, without whitespace. Errors in it are reported as
+    /// [`Error::InvalidNumber`].
+    fn deserialize_quoted_number<V, F>(&mut self, visitor: V, deserialize: F) -> Result<V::Value>
     where
-        V: Visitor<'de>,
+        V: Visitor<'a>,
+        F: FnOnce(&mut Deserializer<'_>, V) -> Result<V::Value>,
     {
-        match self.parse_whitespace().ok_or(Error::EofWhileParsingValue)? {
-            b'n' => {
-                self.eat_char();
-                self.parse_ident(b"ull")?;
-                visitor.visit_none()
-            }
-            _ => visitor.visit_some(self),
+        let options = self.options.clone();
+        let string = self.parse_string()?;
+        let content = string.as_str().as_bytes();
+        let is_padding = |c: Option<&u8>| c.map_or(true, u8::is_ascii_whitespace);
+        if is_padding(content.first()) || is_padding(content.last()) {
+            return Err(Error::InvalidNumber);
         }
+        let mut de = Deserializer::new(content);
+        de.options = options;
+        deserialize(&mut de, visitor)
+            .and_then(|value| de.end().map(|()| value))
+            .map_err(|error| match error.classify() {
+                Category::Syntax | Category::Eof => Error::InvalidNumber,
+                _ => error,
+            })
     }
 
-    /// Resolves "null" to ()
-    fn deserialize_unit<V>(self, visitor: V) -> Result<V::Value>
-    where
-        V: Visitor<'de>,
-    {
-        let peek = self.parse_whitespace().ok_or(Error::EofWhileParsingValue)?;
-
-        if peek == b'n' {
-            self.eat_char();
-            self.parse_ident(b"ull")?;
-            let ret = visitor.visit_unit()?;
-            Ok(ret)
+    /// Checks the number of elements of an array or members of an object, given by its closing
+    /// bracket, against [`Options::max_array_len`] and [`Options::max_object_len`]
+    fn check_len(&self, close: u8, len: usize) -> Result<()> {
+        let (max_len, error) = if close == b']' {
+            (self.options.max_array_len, Error::ArrayTooLong)
         } else {
-            Err(Error::InvalidType)
+            (self.options.max_object_len, Error::ObjectTooLong)
+        };
+        match max_len {
+            Some(max_len) if len > max_len => Err(error),
+            _ => Ok(()),
         }
     }
 
-    /// Resolves "null" to requested unit struct
-    fn deserialize_unit_struct<V>(self, _name: &'static str, visitor: V) -> Result<V::Value>
-    where
-        V: Visitor<'de>,
-    {
-        self.deserialize_unit(visitor)
+    /// Skips over an object key and the colon following it
+    fn skip_object_key(&mut self) -> Result<()> {
+        match self
+            .parse_whitespace()
+            .ok_or(Error::EofWhileParsingObject)?
+        {
+            c if self.is_quote(c) => {
+                self.parse_string()?;
+                self.parse_object_colon()
+            }
+            b'}' => Err(Error::TrailingComma),
+            _ => match self.parse_unquoted_key() {
+                Some(_) => self.parse_object_colon(),
+                None => Err(Error::KeyMustBeAString),
+            },
+        }
     }
+}
 
-    /// Unsupported. We can’t parse newtypes because we don’t know the underlying type.
-    fn deserialize_newtype_struct<V>(self, _name: &'static str, visitor: V) -> Result<V::Value>
-    where
-        V: Visitor<'de>,
-    {
-        visitor.visit_newtype_struct(self)
+/// Checks a string of `len` bytes against [`Options::max_string_len`]
+fn check_string_len(len: usize, max_string_len: Option<usize>) -> Result<()> {
+    match max_string_len {
+        Some(max_string_len) if len > max_string_len => Err(Error::StringTooLong),
+        _ => Ok(()),
     }
+}
 
-    fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value>
-    where
-        V: Visitor<'de>,
-    {
-        match self.parse_whitespace().ok_or(Error::EofWhileParsingValue)? {
-            b'[' => {
-                self.eat_char();
-                let ret = visitor.visit_seq(SeqAccess::new(self))?;
-
-                self.end_seq()?;
-
-                Ok(ret)
-            }
-            _ => Err(Error::InvalidType),
-        }
+/// Parses ASCII digits in base `radix` into an `u128`, or `None` on overflow
+fn parse_unsigned_integer(digits: &[u8], radix: u32) -> Option<u128> {
+    let mut number: u128 = 0;
+    for &c in digits {
+        let digit = (c as char).to_digit(radix)?;
+        number = number
+            .checked_mul(radix as u128)?
+            .checked_add(digit as u128)?;
     }
+    Some(number)
+}
 
-    fn deserialize_tuple<V>(self, _len: usize, visitor: V) -> Result<V::Value>
-    where
-        V: Visitor<'de>,
-    {
-        self.deserialize_seq(visitor)
+/// Parses ASCII digits in base `radix` into a negative `i128`, or `None` on overflow
+fn parse_negative_integer(digits: &[u8], radix: u32) -> Option<i128> {
+    let mut number: i128 = 0;
+    for &c in digits {
+        let digit = (c as char).to_digit(radix)?;
+        number = number
+            .checked_mul(radix as i128)?
+            .checked_sub(digit as i128)?;
     }
+    Some(number)
+}
 
-    fn deserialize_tuple_struct<V>(
-        self,
-        _name: &'static str,
-        _len: usize,
-        visitor: V,
-    ) -> Result<V::Value>
-    where
-        V: Visitor<'de>,
-    {
-        self.deserialize_seq(visitor)
+/// Decodes the content of a string that is deserialized as bytes, as hexadecimal digits with
+/// [`Options::hex_bytes`]
+fn decode_bytes(content: &[u8], hex_bytes: bool) -> Option<Vec<u8>> {
+    if hex_bytes {
+        return decode_hex(content);
     }
+    #[cfg(feature = "base64")]
+    return crate::base64::decode(content);
+    // without the feature only hex strings are decoded
+    #[cfg(not(feature = "base64"))]
+    None
+}
 
-    /// Unsupported. Can’t make an arbitrary-sized map in no-std. Use a struct with a
-    /// known format, or implement a custom map deserializer / visitor:
-    /// https://serde.rs/deserialize-map.html
-    fn deserialize_map<V>(self, _visitor: V) -> Result<V::Value>
-    where
-        V: Visitor<'de>,
-    {
-        unreachable!()
+/// Decodes bytes from hexadecimal digits in either case, with an optional `0x` prefix, or returns
+/// `None` if `text` isn't valid
+fn decode_hex(text: &[u8]) -> Option<Vec<u8>> {
+    let digits = if text.starts_with(b"0x") {
+        &text[2..]
+    } else {
+        text
+    };
+    if digits.len() % 2 != 0 {
+        return None;
     }
+    digits
+        .chunks(2)
+        .map(|pair| {
+            let high = (pair[0] as char).to_digit(16)?;
+            let low = (pair[1] as char).to_digit(16)?;
+            Some((high << 4 | low) as u8)
+        })
+        .collect()
+}
 
-    fn deserialize_struct<V>(
-        self,
-        _name: &'static str,
-        _fields: &'static [&'static str],
-        visitor: V,
-    ) -> Result<V::Value>
-    where
-        V: Visitor<'de>,
-    {
-        let peek = self.parse_whitespace().ok_or(Error::EofWhileParsingValue)?;
-
-        if peek == b'{' {
-            self.eat_char();
+/// The decimal digits of an integer that was written with a fraction or exponent, see
+/// [`Deserializer::parse_exact_integer`]
+struct ExactInteger {
+    negative: bool,
+    /// Enough for the largest `u128` and the smallest `i128`
+    digits: [u8; 39],
+    len: usize,
+}
 
-            let ret = visitor.visit_map(MapAccess::new(self))?;
+impl ExactInteger {
+    /// Appends a digit, failing if the integer is out of range for every integer type
+    fn push(&mut self, digit: u8) -> Result<()> {
+        *self
+            .digits
+            .get_mut(self.len)
+            .ok_or(Error::NumberOutOfRange)? = digit;
+        self.len += 1;
+        Ok(())
+    }
 
-            self.end_map()?;
+    /// Returns the digits without leading zeros, which are empty for 0
+    fn digits(&self) -> &[u8] {
+        &self.digits[..self.len]
+    }
+}
 
-            Ok(ret)
-        } else {
-            Err(Error::InvalidType)
+/// Returns the digits of the integer that `text`, a number with a fraction or exponent, is equal
+/// to, see [`Deserializer::parse_exact_integer`]
+fn exact_integer(text: &[u8]) -> Result<ExactInteger> {
+    let negative = text[0] == b'-';
+    let text = if negative { &text[1..] } else { text };
+    let digits_len = |text: &[u8]| text.iter().take_while(|c| c.is_ascii_digit()).count();
+    let (int, rest) = text.split_at(digits_len(text));
+    let (frac, rest) = match rest.split_first() {
+        Some((&b'.', rest)) => rest.split_at(digits_len(rest)),
+        _ => rest.split_at(0),
+    };
+    let mut exponent: isize = 0;
+    if let Some((_, rest)) = rest.split_first() {
+        let (negative, digits) = match rest.split_first() {
+            Some((&b'-', digits)) => (true, digits),
+            Some((&b'+', digits)) => (false, digits),
+            _ => (false, rest),
+        };
+        for &c in digits {
+            exponent = exponent
+                .saturating_mul(10)
+                .saturating_add((c - b'0') as isize);
+        }
+        if negative {
+            exponent = -exponent;
         }
     }
 
-    fn deserialize_enum<V>(
-        self,
-        _name: &'static str,
-        _variants: &'static [&'static str],
-        visitor: V,
-    ) -> Result<V::Value>
-    where
-        V: Visitor<'de>,
-    {
-        match self.parse_whitespace().ok_or(Error::EofWhileParsingValue)? {
-            // if it is a string enum
-            b'"' => visitor.visit_enum(UnitVariantAccess::new(self)),
-            // if it is a struct enum
-            b'{' => {
-                self.eat_char();
-                visitor.visit_enum(StructVariantAccess::new(self))
-            }
-            _ => Err(Error::ExpectedSomeIdent),
+    // the value is the digits of `int` and `frac` times 10 to the power of `shift`
+    let digit = |i: usize| {
+        if i < int.len() {
+            int[i]
+        } else {
+            frac[i - int.len()]
         }
+    };
+    let len = int.len() + frac.len();
+    let shift = exponent.saturating_sub(frac.len() as isize);
+    let kept = (len as isize).saturating_add(shift.min(0)).max(0) as usize;
+    if (kept..len).any(|i| digit(i) != b'0') {
+        return Err(Error::InvalidNumber);
     }
-
-    fn deserialize_identifier<V>(self, visitor: V) -> Result<V::Value>
-    where
-        V: Visitor<'de>,
-    {
-        self.deserialize_str(visitor)
+    let mut integer = ExactInteger {
+        negative,
+        digits: [0; 39],
+        len: 0,
+    };
+    for i in (0..kept).skip_while(|&i| digit(i) == b'0') {
+        integer.push(digit(i))?;
     }
-
-    /// Used to throw out fields from JSON objects that we don’t want to
-    /// keep in our structs.
-    fn deserialize_ignored_any<V>(self, visitor: V) -> Result<V::Value>
-    where
-        V: Visitor<'de>,
-    {
-        match self.parse_whitespace().ok_or(Error::EofWhileParsingValue)? {
-            b'"' => self.deserialize_str(visitor),
-            b'[' => self.deserialize_seq(visitor),
-            b'{' => self.deserialize_struct("ignored", &[], visitor),
-            b',' | b'}' | b']' => Err(Error::ExpectedSomeValue),
-            // If it’s something else then we chomp until we get to an end delimiter.
-            // This does technically allow for illegal JSON since we’re just ignoring
-            // characters rather than parsing them.
-            _ => loop {
-                match self.peek() {
-                    // The visitor is expected to be UnknownAny’s visitor, which
-                    // implements visit_unit to return its unit Ok result.
-                    Some(b',') | Some(b'}') | Some(b']') => break visitor.visit_unit(),
-                    Some(_) => self.eat_char(),
-                    None => break Err(Error::EofWhileParsingString),
-                }
-            },
+    if integer.len > 0 {
+        for _ in 0..shift.max(0) {
+            integer.push(b'0')?;
         }
     }
+    Ok(integer)
 }
 
-/// Deserializes an instance of type `T` from bytes of JSON text
-pub fn from_slice<T>(v: &[u8]) -> Result<T>
-where
-    T: de::DeserializeOwned,
-{
-    let mut de = Deserializer::new(v);
-    let value = de::Deserialize::deserialize(&mut de)?;
-    de.end()?;
-
-    Ok(value)
+/// Evaluates the decimal `digits` of an exact integer like `parse_hex_digits`, adding each with
+/// the sign `$sign`
+macro_rules! parse_exact_digits {
+    ($digits:expr, $ixx:ident, $sign:expr) => {{
+        let mut number: $ixx = 0;
+        for &c in $digits {
+            number = number
+                .checked_mul(10)
+                .ok_or(Error::NumberOutOfRange)?
+                .checked_add((c - b'0') as $ixx * $sign)
+                .ok_or(Error::NumberOutOfRange)?;
+        }
+        number
+    }};
 }
 
-/// Deserializes an instance of type T from a string of JSON text
-pub fn from_str<T>(s: &str) -> Result<T>
-where
-    T: de::DeserializeOwned,
-{
-    from_slice(s.as_bytes())
+/// Parses the digits of a hexadecimal integer after its `0x` prefix. Each digit is added with
+/// the sign `$sign`, like in `deserialize_signed`, so that the minimum value doesn't overflow.
+macro_rules! parse_hex_digits {
+    ($self:ident, $ixx:ident, $sign:expr) => {{
+        let mut number: $ixx = 0;
+        let mut empty = true;
+        while let Some(digit) = $self.peek().and_then(|c| (c as char).to_digit(16)) {
+            $self.eat_char();
+            empty = false;
+            number = number
+                .checked_mul(16)
+                .ok_or(Error::NumberOutOfRange)?
+                .checked_add(digit as $ixx * $sign)
+                .ok_or(Error::NumberOutOfRange)?;
+        }
+        if empty {
+            return Err(Error::InvalidNumber);
+        }
+        number
+    }};
 }
 
-#[cfg(test)]
-mod tests {
-    use super::from_str;
-    use serde_derive::Deserialize;
-
-    #[derive(Debug, Deserialize, PartialEq)]
-    enum Type {
+// NOTE(deserialize_*signed) we avoid parsing into u64 and then casting to a smaller integer, which
+// is what upstream does, to avoid pulling in 64-bit compiler intrinsics, which waste a few KBs of
+// Flash, when targeting non 64-bit architectures
+macro_rules! deserialize_unsigned {
+    ($self:ident, $visitor:ident, $uxx:ident, $visit_uxx:ident) => {{
+        match $self.parse_whitespace() {
+            Some(c) if $self.options.allow_quoted_integers && $self.is_quote(c) => $self
+                .deserialize_quoted_number($visitor, |de, visitor| {
+                    deserialize_unsigned!(@number de, visitor, $uxx, $visit_uxx)
+                }),
+            _ => deserialize_unsigned!(@number $self, $visitor, $uxx, $visit_uxx),
+        }
+    }};
+    (@number $self:ident, $visitor:ident, $uxx:ident, $visit_uxx:ident) => {{
+        let peek = $self
+            .parse_whitespace()
+            .ok_or(Error::EofWhileParsingValue)?;
+
+        if let Some(integer) = $self.parse_exact_integer()? {
+            if integer.negative {
+                return Err(Error::NumberOutOfRange);
+            }
+            return $visitor.$visit_uxx(parse_exact_digits!(integer.digits(), $uxx, 1));
+        }
+
+        match peek {
+            b'-' => {
+                $self.eat_char();
+                match $self.peek() {
+                    Some(b'0'..=b'9') => Err(Error::NumberOutOfRange),
+                    _ => Err(Error::InvalidNumber),
+                }
+            }
+            b'0' => {
+                $self.eat_char();
+                if $self.eat_hex_prefix() {
+                    let number = parse_hex_digits!($self, $uxx, 1);
+                    return $visitor.$visit_uxx(number);
+                }
+                $self.check_number_end()?;
+                $visitor.$visit_uxx(0)
+            }
+            b'1'..=b'9' => {
+                $self.eat_char();
+
+                let mut number = (peek - b'0') as $uxx;
+                loop {
+                    match $self.peek() {
+                        Some(c @ b'0'..=b'9') => {
+                            $self.eat_char();
+                            number = number
+                                .checked_mul(10)
+                                .ok_or(Error::NumberOutOfRange)?
+                                .checked_add((c - b'0') as $uxx)
+                                .ok_or(Error::NumberOutOfRange)?;
+                        }
+                        _ => {
+                            $self.check_number_end()?;
+                            return $visitor.$visit_uxx(number);
+                        }
+                    }
+                }
+            }
+            _ => Err($self.unexpected_number_byte(peek)),
+        }
+    }};
+}
+
+macro_rules! deserialize_signed {
+    ($self:ident, $visitor:ident, $ixx:ident, $visit_ixx:ident) => {{
+        match $self.parse_whitespace() {
+            Some(c) if $self.options.allow_quoted_integers && $self.is_quote(c) => $self
+                .deserialize_quoted_number($visitor, |de, visitor| {
+                    deserialize_signed!(@number de, visitor, $ixx, $visit_ixx)
+                }),
+            _ => deserialize_signed!(@number $self, $visitor, $ixx, $visit_ixx),
+        }
+    }};
+    (@number $self:ident, $visitor:ident, $ixx:ident, $visit_ixx:ident) => {{
+        $self.parse_whitespace();
+        if let Some(integer) = $self.parse_exact_integer()? {
+            let sign = if integer.negative { -1 } else { 1 };
+            return $visitor.$visit_ixx(parse_exact_digits!(integer.digits(), $ixx, sign));
+        }
+
+        let signed = match $self
+            .parse_whitespace()
+            .ok_or(Error::EofWhileParsingValue)?
+        {
+            b'-' => {
+                $self.eat_char();
+                true
+            }
+            _ => false,
+        };
+
+        match $self.peek() {
+            Some(b'0') => {
+                $self.eat_char();
+                if $self.eat_hex_prefix() {
+                    let number = parse_hex_digits!($self, $ixx, if signed { -1 } else { 1 });
+                    return $visitor.$visit_ixx(number);
+                }
+                $self.check_number_end()?;
+                $visitor.$visit_ixx(0)
+            }
+            Some(c @ b'1'..=b'9') => {
+                $self.eat_char();
+
+                let mut number = (c - b'0') as $ixx * if signed { -1 } else { 1 };
+                loop {
+                    match $self.peek() {
+                        Some(c @ b'0'..=b'9') => {
+                            $self.eat_char();
+                            number = number
+                                .checked_mul(10)
+                                .ok_or(Error::NumberOutOfRange)?
+                                .checked_add((c - b'0') as $ixx * if signed { -1 } else { 1 })
+                                .ok_or(Error::NumberOutOfRange)?;
+                        }
+                        _ => {
+                            $self.check_number_end()?;
+                            return $visitor.$visit_ixx(number);
+                        }
+                    }
+                }
+            }
+            _ if signed => Err(Error::InvalidNumber),
+            Some(c) => Err($self.unexpected_number_byte(c)),
+            None => Err(Error::EofWhileParsingValue),
+        }
+    }};
+}
+
+/// `NaN`, `Infinity` or `-Infinity`, see [`Options::allow_non_finite_floats`]
+#[cfg(feature = "float")]
+enum NonFinite {
+    NaN,
+    Infinity,
+    NegInfinity,
+}
+
+#[cfg(feature = "float")]
+macro_rules! deserialize_float {
+    ($self:ident, $visitor:ident, $fxx:ident, $visit_fxx:ident) => {{
+        match $self.parse_whitespace() {
+            Some(c) if $self.options.allow_quoted_floats && $self.is_quote(c) => $self
+                .deserialize_quoted_number($visitor, |de, visitor| {
+                    deserialize_float!(@number de, visitor, $fxx, $visit_fxx)
+                }),
+            _ => deserialize_float!(@number $self, $visitor, $fxx, $visit_fxx),
+        }
+    }};
+    (@number $self:ident, $visitor:ident, $fxx:ident, $visit_fxx:ident) => {{
+        match $self
+            .parse_whitespace()
+            .ok_or(Error::EofWhileParsingValue)?
+        {
+            b'-' | b'0'..=b'9' => {}
+            b'N' | b'I' if $self.options.allow_non_finite_floats => {}
+            c => return Err($self.unexpected_number_byte(c)),
+        }
+        match $self.parse_non_finite()? {
+            Some(NonFinite::NaN) => $visitor.$visit_fxx(core::$fxx::NAN),
+            Some(NonFinite::Infinity) => $visitor.$visit_fxx(core::$fxx::INFINITY),
+            Some(NonFinite::NegInfinity) => $visitor.$visit_fxx(core::$fxx::NEG_INFINITY),
+            None => {
+                let text = $self.parse_number_text()?;
+                // `None` if out of range
+                let value: $fxx =
+                    float::parse(text.as_str().as_bytes()).ok_or(Error::NumberOutOfRange)?;
+                $visitor.$visit_fxx(value)
+            }
+        }
+    }};
+}
+
+impl<'de, R: Read<'de>> de::Deserializer<'de> for &mut Deserializer<'de, R> {
+    type Error = Error;
+
+    /// Deserializes whatever the next JSON value is, using the type information available in the
+    /// JSON itself. Typed deserialization methods are preferred whenever the target type is known.
+    ///
+    /// Integers are passed to the visitor as `u64` (or `i64` if negative) and as `u128` (or `i128`)
+    /// if they don't fit into 64 bits. Numbers with a fraction or exponent are only supported with
+    /// the `float` feature, which passes them as `f64`.
+    ///
+    /// See https://serde.rs/impl-deserialize.html to learn more about the differentiation
+    /// between `deserialize_{type}` and `deserialize_any`.
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        match self.parse_whitespace().ok_or(Error::EofWhileParsingValue)? {
+            b'n' => self.deserialize_unit(visitor),
+            b't' | b'f' => self.deserialize_bool(visitor),
+            c if self.is_quote(c) => self.deserialize_str(visitor),
+            b'[' => self.deserialize_seq(visitor),
+            b'{' => self.deserialize_map(visitor),
+            b'-' | b'0'..=b'9' => self.deserialize_any_integer(visitor),
+            #[cfg(feature = "float")]
+            b'N' | b'I' if self.options.allow_non_finite_floats => self.deserialize_f64(visitor),
+            c => Err(self.unexpected_number_byte(c)),
+        }
+    }
+
+    fn deserialize_bool<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        let peek = self.parse_whitespace().ok_or(Error::EofWhileParsingValue)?;
+
+        match peek {
+            b't' => {
+                self.eat_char();
+                self.parse_ident(b"rue")?;
+                visitor.visit_bool(true)
+            }
+            b'f' => {
+                self.eat_char();
+                self.parse_ident(b"alse")?;
+                visitor.visit_bool(false)
+            }
+            _ => Err(Error::unexpected_byte(peek)),
+        }
+    }
+
+    fn deserialize_i8<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        deserialize_signed!(self, visitor, i8, visit_i8)
+    }
+
+    fn deserialize_i16<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        deserialize_signed!(self, visitor, i16, visit_i16)
+    }
+
+    fn deserialize_i32<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        deserialize_signed!(self, visitor, i32, visit_i32)
+    }
+
+    fn deserialize_i64<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        deserialize_signed!(self, visitor, i64, visit_i64)
+    }
+
+    fn deserialize_i128<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        deserialize_signed!(self, visitor, i128, visit_i128)
+    }
+
+    fn deserialize_u8<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        deserialize_unsigned!(self, visitor, u8, visit_u8)
+    }
+
+    fn deserialize_u16<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        deserialize_unsigned!(self, visitor, u16, visit_u16)
+    }
+
+    fn deserialize_u32<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        deserialize_unsigned!(self, visitor, u32, visit_u32)
+    }
+
+    fn deserialize_u64<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        deserialize_unsigned!(self, visitor, u64, visit_u64)
+    }
+
+    fn deserialize_u128<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        deserialize_unsigned!(self, visitor, u128, visit_u128)
+    }
+
+    #[cfg(feature = "float")]
+    fn deserialize_f32<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        deserialize_float!(self, visitor, f32, visit_f32)
+    }
+
+    /// Fails with [`Error::FloatNotSupported`], so that no float code gets compiled in
+    #[cfg(not(feature = "float"))]
+    fn deserialize_f32<V>(self, _visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        Err(Error::FloatNotSupported)
+    }
+
+    #[cfg(feature = "float")]
+    fn deserialize_f64<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        deserialize_float!(self, visitor, f64, visit_f64)
+    }
+
+    /// Fails with [`Error::FloatNotSupported`], so that no float code gets compiled in
+    #[cfg(not(feature = "float"))]
+    fn deserialize_f64<V>(self, _visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        Err(Error::FloatNotSupported)
+    }
+
+    /// Resolves a string containing exactly one character, which may be an escape sequence
+    fn deserialize_char<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_str(visitor)
+    }
+
+    fn deserialize_str<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_string(visitor)
+    }
+
+    fn deserialize_string<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        let peek = self.parse_whitespace().ok_or(Error::EofWhileParsingValue)?;
+
+        match peek {
+            c if self.is_quote(c) => {
+                let reuse_string_buffer = self.options.reuse_string_buffer;
+                match self.parse_string()? {
+                    StringLike::Owned(string) if reuse_string_buffer => {
+                        // the visitor copies what it needs, so that the buffer can be reused for
+                        // the next string with escape sequences
+                        let ret = visitor.visit_str(&string);
+                        self.scratch = string.into_bytes();
+                        ret
+                    }
+                    string => string.visit(visitor),
+                }
+            }
+            _ => Err(Error::unexpected_byte(peek)),
+        }
+    }
+
+    /// Decodes bytes from an array of numbers like serde_json, from a hex string with
+    /// [`Options::hex_bytes`], or else from a base64 string with the `base64` feature
+    fn deserialize_bytes<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        let peek = self.parse_whitespace().ok_or(Error::EofWhileParsingValue)?;
+
+        match peek {
+            c if self.is_quote(c) && (self.options.hex_bytes || cfg!(feature = "base64")) => {
+                let hex_bytes = self.options.hex_bytes;
+                let bytes = decode_bytes(self.parse_string()?.as_str().as_bytes(), hex_bytes);
+                visitor.visit_byte_buf(bytes.ok_or(Error::InvalidBytes)?)
+            }
+            b'[' => {
+                let bytes: Vec<u8> = de::Deserialize::deserialize(&mut *self)?;
+                visitor.visit_byte_buf(bytes)
+            }
+            _ => Err(Error::unexpected_byte(peek)),
+        }
+    }
+
+    fn deserialize_byte_buf<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_bytes(visitor)
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        match self.parse_whitespace().ok_or(Error::EofWhileParsingValue)? {
+            b'n' => {
+                self.eat_char();
+                self.parse_ident(b"ull")?;
+                visitor.visit_none()
+            }
+            _ => visitor.visit_some(self),
+        }
+    }
+
+    /// Resolves "null" to ()
+    fn deserialize_unit<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        let peek = self.parse_whitespace().ok_or(Error::EofWhileParsingValue)?;
+
+        if peek == b'n' {
+            self.eat_char();
+            self.parse_ident(b"ull")?;
+            let ret = visitor.visit_unit()?;
+            Ok(ret)
+        } else {
+            Err(Error::unexpected_byte(peek))
+        }
+    }
+
+    /// Resolves "null" to requested unit struct
+    fn deserialize_unit_struct<V>(self, name: &'static str, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        self.in_type(name, |de| de.deserialize_unit(visitor))
+    }
+
+    /// Unsupported. We can’t parse newtypes because we don’t know the underlying type.
+    /// A [`Number`](crate::Number) is deserialized from the text of a number and a
+    /// [`RawValue`](crate::RawValue) from the text of any value
+    fn deserialize_newtype_struct<V>(self, name: &'static str, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        if name == crate::number::TOKEN {
+            return match self.parse_whitespace().ok_or(Error::EofWhileParsingValue)? {
+                b'-' | b'0'..=b'9' => self.parse_number_text()?.visit(visitor),
+                c => Err(self.unexpected_number_byte(c)),
+            };
+        }
+        if name == crate::raw::TOKEN {
+            if self.unescaped_in_place {
+                return Err(de::Error::custom(
+                    "RawValue cannot be borrowed from a buffer that was unescaped in place",
+                ));
+            }
+            return self.parse_raw_value()?.visit(visitor);
+        }
+        self.in_type(name, |de| visitor.visit_newtype_struct(de))
+    }
+
+    fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        match self.parse_whitespace().ok_or(Error::EofWhileParsingValue)? {
+            b'[' => {
+                self.enter_nested()?;
+                self.eat_char();
+                let ret = visitor.visit_seq(SeqAccess::new(self))?;
+                self.leave_nested();
+
+                self.end_seq()?;
+
+                Ok(ret)
+            }
+            c => Err(Error::unexpected_byte(c)),
+        }
+    }
+
+    fn deserialize_tuple<V>(self, _len: usize, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_tuple_struct<V>(
+        self,
+        name: &'static str,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        self.in_type(name, |de| de.deserialize_seq(visitor))
+    }
+
+    fn deserialize_map<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        let peek = self.parse_whitespace().ok_or(Error::EofWhileParsingValue)?;
+
+        if peek == b'{' {
+            self.enter_nested()?;
+            self.eat_char();
+
+            let ret = visitor.visit_map(MapAccess::new(self))?;
+            self.leave_nested();
+
+            self.end_map()?;
+
+            Ok(ret)
+        } else {
+            Err(Error::unexpected_byte(peek))
+        }
+    }
+
+    /// Resolves an object to the requested struct. Like serde_json, an array is accepted as well
+    /// and its elements are assigned to the fields in declaration order.
+    fn deserialize_struct<V>(
+        self,
+        name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        self.in_type(name, |de| {
+            match de.parse_whitespace().ok_or(Error::EofWhileParsingValue)? {
+                b'{' => {
+                    de.enter_nested()?;
+                    de.eat_char();
+
+                    let ret = visitor.visit_map(MapAccess::for_struct(de, fields))?;
+                    de.leave_nested();
+
+                    de.end_map()?;
+
+                    Ok(ret)
+                }
+                b'[' => {
+                    de.enter_nested()?;
+                    de.eat_char();
+
+                    let ret = visitor.visit_seq(SeqAccess::new(de))?;
+                    de.leave_nested();
+
+                    de.end_seq()?;
+
+                    Ok(ret)
+                }
+                c => Err(Error::unexpected_byte(c)),
+            }
+        })
+    }
+
+    fn deserialize_enum<V>(
+        self,
+        name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        self.in_type(name, |de| {
+            match de.parse_whitespace().ok_or(Error::EofWhileParsingValue)? {
+                // if it is a string enum
+                c if de.is_quote(c) => {
+                    visitor.visit_enum(UnitVariantAccess::new(de, JsonType::String))
+                }
+                // if it is a unit variant encoded as an integer
+                b'0'..=b'9' => visitor.visit_enum(UnitVariantAccess::new(de, JsonType::Number)),
+                // if it is a struct enum
+                b'{' => {
+                    de.enter_nested()?;
+                    de.eat_char();
+                    let ret = visitor.visit_enum(StructVariantAccess::new(de))?;
+                    de.leave_nested();
+                    Ok(ret)
+                }
+                _ => Err(Error::ExpectedSomeIdent),
+            }
+        })
+    }
+
+    /// Resolves a string, or an integer for identifiers encoded by their index
+    fn deserialize_identifier<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        match self.parse_whitespace().ok_or(Error::EofWhileParsingValue)? {
+            b'0'..=b'9' => self.deserialize_u64(visitor),
+            _ => self.deserialize_str(visitor),
+        }
+    }
+
+    /// Used to throw out fields from JSON objects that we don’t want to
+    /// keep in our structs.
+    fn deserialize_ignored_any<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        self.skip_value()?;
+        visitor.visit_unit()
+    }
+}
+
+/// Deserializes an instance of type `T` from bytes of JSON text
+///
+/// Borrowed fields (`&str`, or `Cow<str>` with `#[serde(borrow)]`) point into `v`. Strings
+/// containing escape sequences cannot be borrowed: they fail to deserialize into `&str` and
+/// become `Cow::Owned`.
+pub fn from_slice<'a, T>(v: &'a [u8]) -> Result<T>
+where
+    T: de::Deserialize<'a>,
+{
+    let mut de = Deserializer::new(v);
+    let value = de::Deserialize::deserialize(&mut de)?;
+    de.end()?;
+
+    Ok(value)
+}
+
+/// Deserializes an instance of type `T` from the first JSON value in `v`
+///
+/// Unlike [`from_slice`], data after the value is not an error. Returns the value together with
+/// the number of bytes it took up, including leading whitespace, so the next value can be read
+/// from `&v[consumed..]`.
+pub fn from_slice_partial<'a, T>(v: &'a [u8]) -> Result<(T, usize)>
+where
+    T: de::Deserialize<'a>,
+{
+    let mut de = Deserializer::new(v);
+    let value = de::Deserialize::deserialize(&mut de)?;
+
+    Ok((value, de.read.index))
+}
+
+/// Deserializes an instance of type `T` from bytes of JSON text, using the given [`Options`]
+pub fn from_slice_with_options<'a, T>(v: &'a [u8], options: &Options) -> Result<T>
+where
+    T: de::Deserialize<'a>,
+{
+    from_read(slice_read(v, options)?, options)
+}
+
+/// Returns a source for `v`, failing if it is longer than [`Options::max_input_len`]
+fn slice_read<'a>(v: &'a [u8], options: &Options) -> Result<SliceRead<'a>> {
+    read::check_len(v.len(), options.max_input_len)?;
+    Ok(SliceRead::new(v))
+}
+
+/// Deserializes an instance of type `T` from the whole input of `read`
+fn from_read<'a, R, T>(read: R, options: &Options) -> Result<T>
+where
+    R: Read<'a>,
+    T: de::Deserialize<'a>,
+{
+    from_read_seed(read, PhantomData, options)
+}
+
+/// Deserializes the whole input of `read` with `seed`
+fn from_read_seed<'a, R, S>(read: R, seed: S, options: &Options) -> Result<S::Value>
+where
+    R: Read<'a>,
+    S: de::DeserializeSeed<'a>,
+{
+    let mut de = Deserializer::with_read(read, options);
+    let result = seed
+        .deserialize(&mut de)
+        .and_then(|value| de.end().map(|()| value));
+    de.check_read(result)
+}
+
+/// Deserializes an instance of type `T` from the start of the input of `read`, leaving what
+/// follows the value unread, and returns it together with `read`
+fn from_read_prefix<'a, R, T>(read: R, options: &Options) -> Result<(T, R)>
+where
+    R: Read<'a>,
+    T: de::Deserialize<'a>,
+{
+    let mut de = Deserializer::with_read(read, options);
+    let result = de::Deserialize::deserialize(&mut de);
+    let value = de.check_read(result)?;
+    Ok((value, de.read))
+}
+
+/// Deserializes an instance of type `T` from bytes of JSON text, reporting the byte offset of
+/// errors
+///
+/// This works like [`from_slice`], but the error tells where in `v` parsing failed.
+pub fn from_slice_with_offset<'a, T>(v: &'a [u8]) -> core::result::Result<T, ErrorWithOffset>
+where
+    T: de::Deserialize<'a>,
+{
+    let mut de = Deserializer::new(v);
+    de::Deserialize::deserialize(&mut de)
+        .and_then(|value| de.end().map(|()| value))
+        .map_err(|error| ErrorWithOffset::new(error, v, de.read.index))
+}
+
+/// Deserializes an instance of type `T` from bytes of JSON text, reporting the path to the value
+/// that failed
+///
+/// This works like [`from_slice`], but the error tells in which field or element parsing failed,
+/// e.g. `messages[3].amount.denom`. Object keys are reported as they are written in the input.
+pub fn from_slice_with_path<'a, T>(v: &'a [u8]) -> core::result::Result<T, ErrorWithPath>
+where
+    T: de::Deserialize<'a>,
+{
+    let mut de = Deserializer::new(v);
+    de.track_path = true;
+    de::Deserialize::deserialize(&mut de)
+        .and_then(|value| de.end().map(|()| value))
+        .map_err(|error| ErrorWithPath {
+            error,
+            path: de.path_to_string(),
+        })
+}
+
+/// Deserializes an instance of type T from bytes of JSON text, reporting the types and enum
+/// variants that were being deserialized when an error occurred
+///
+/// ```
+/// use serde_derive::Deserialize;
+/// use serde_json_wasm::de::from_slice_with_context;
+///
+/// #[derive(Debug, Deserialize)]
+/// struct Coin {
+///     denom: String,
+///     amount: u64,
+/// }
+///
+/// #[derive(Debug, Deserialize)]
+/// #[serde(rename_all = "snake_case")]
+/// enum ExecuteMsg {
+///     Transfer { amount: Coin },
+/// }
+///
+/// let err = from_slice_with_context::<ExecuteMsg>(
+///     br#"{"transfer":{"amount":{"denom":"uatom","amount":"1"}}}"#,
+/// )
+/// .unwrap_err();
+/// assert_eq!(err.context, "ExecuteMsg::transfer > Coin");
+/// assert_eq!(
+///     err.to_string(),
+///     "while deserializing ExecuteMsg::transfer > Coin: Invalid type, found string"
+/// );
+/// ```
+#[cfg(feature = "type-context")]
+pub fn from_slice_with_context<'a, T>(v: &'a [u8]) -> core::result::Result<T, ErrorWithContext>
+where
+    T: de::Deserialize<'a>,
+{
+    let mut de = Deserializer::new(v);
+    de.track_context = true;
+    de::Deserialize::deserialize(&mut de)
+        .and_then(|value| de.end().map(|()| value))
+        .map_err(|error| ErrorWithContext {
+            error,
+            context: de.context_to_string(),
+        })
+}
+
+/// Deserializes an instance of type `T` from bytes of JSON text, unescaping strings in place
+///
+/// Unlike [`from_slice`], strings containing escape sequences can be deserialized into `&str`
+/// without allocating. The buffer is overwritten with the unescaped strings in the process, so
+/// its content is no longer valid JSON afterwards.
+pub fn from_slice_mut<'a, T>(v: &'a mut [u8]) -> Result<T>
+where
+    T: de::Deserialize<'a>,
+{
+    unescape::unescape_strings_in_place(v)?;
+    let mut de = Deserializer::new(v);
+    de.unescaped_in_place = true;
+    let value = de::Deserialize::deserialize(&mut de)?;
+    de.end()?;
+
+    Ok(value)
+}
+
+/// Deserializes an instance of type `T` from bytes of JSON text, copying strings that contain
+/// escape sequences with `alloc_str`
+///
+/// Strings without escape sequences are borrowed from `v` as usual. The ones with escape
+/// sequences are unescaped into a buffer that is reused for all of them and then passed to
+/// `alloc_str`, which returns a copy that lives as long as `v`, so they can be deserialized into
+/// `&str` too. This way a value with thousands of short strings, like block data, needs no
+/// allocation per string when `alloc_str` puts them into an arena. For a `bumpalo::Bump`, use
+/// `from_slice_in` with the `bumpalo` feature.
+///
+/// ```
+/// use serde_json_wasm::de::from_slice_with_arena;
+///
+/// // an arena that is never freed, for the sake of the example
+/// let alloc_str = |s: &str| &*Box::leak(s.into());
+/// let memos: Vec<&str> = from_slice_with_arena(br#"["plain", "line\nbreak"]"#, &alloc_str).unwrap();
+/// assert_eq!(memos, ["plain", "line\nbreak"]);
+/// ```
+pub fn from_slice_with_arena<'a, T, F>(v: &'a [u8], alloc_str: &'a F) -> Result<T>
+where
+    T: de::Deserialize<'a>,
+    F: Fn(&str) -> &'a str + 'a,
+{
+    from_slice_with_str_arena(v, StrArena::Fn(alloc_str), false)
+}
+
+/// Deserializes an instance of type `T` from bytes of JSON text, copying strings that contain
+/// escape sequences into `bump`
+///
+/// This works like [`from_slice_with_arena`] with `|s| bump.alloc_str(s)`: strings with escape
+/// sequences are unescaped into a reused buffer and copied into `bump`, so `&str` fields work
+/// for them without an allocation per string.
+///
+/// ```
+/// use bumpalo::Bump;
+/// use serde_json_wasm::de::from_slice_in;
+///
+/// let bump = Bump::new();
+/// let memos: Vec<&str> = from_slice_in(br#"["plain", "line\nbreak"]"#, &bump).unwrap();
+/// assert_eq!(memos, ["plain", "line\nbreak"]);
+/// assert!(bump.allocated_bytes() > 0);
+/// ```
+#[cfg(feature = "bumpalo")]
+pub fn from_slice_in<'a, T>(v: &'a [u8], bump: &'a bumpalo::Bump) -> Result<T>
+where
+    T: de::Deserialize<'a>,
+{
+    from_slice_with_str_arena(v, StrArena::Bump(bump), false)
+}
+
+/// Deserializes an instance of type `T` from bytes of JSON text, copying strings to `arena`
+fn from_slice_with_str_arena<'a, T>(
+    v: &'a [u8],
+    arena: StrArena<'a>,
+    intern_strings: bool,
+) -> Result<T>
+where
+    T: de::Deserialize<'a>,
+{
+    let mut de = Deserializer::new(v);
+    de.str_arena = Some(arena);
+    de.intern_strings = intern_strings;
+    let value = de::Deserialize::deserialize(&mut de)?;
+    de.end()?;
+
+    Ok(value)
+}
+
+/// Deserializes an instance of type `T` from bytes of JSON text, passing every string to
+/// `intern`
+///
+/// String values and object keys are visited as the string that `intern` returns for them, like
+/// strings with escape sequences in [`from_slice_with_arena`]. This lets callers map the many
+/// repetitions of strings like denoms and addresses in block data to one copy in shared storage.
+///
+/// ```
+/// use serde_json_wasm::de::from_slice_with_interner;
+/// use std::cell::RefCell;
+/// use std::collections::BTreeSet;
+///
+/// // keeps one copy of every string, which is leaked for the sake of the example
+/// fn intern<'a>(interned: &RefCell<BTreeSet<&'a str>>, s: &str) -> &'a str {
+///     let mut interned = interned.borrow_mut();
+///     match interned.get(s) {
+///         Some(copy) => copy,
+///         None => {
+///             let copy: &'a str = Box::leak(s.into());
+///             interned.insert(copy);
+///             copy
+///         }
+///     }
+/// }
+///
+/// let interned = RefCell::new(BTreeSet::new());
+/// let input = br#"["uatom", "uosmo", "uatom"]"#;
+/// let intern_str = |s: &str| intern(&interned, s);
+/// let denoms: Vec<&str> = from_slice_with_interner(input, &intern_str).unwrap();
+/// assert_eq!(denoms, ["uatom", "uosmo", "uatom"]);
+/// assert_eq!(denoms[0].as_ptr(), denoms[2].as_ptr());
+/// assert_eq!(interned.borrow().len(), 2);
+/// ```
+pub fn from_slice_with_interner<'a, T, F>(v: &'a [u8], intern: &'a F) -> Result<T>
+where
+    T: de::Deserialize<'a>,
+    F: Fn(&str) -> &'a str + 'a,
+{
+    from_slice_with_str_arena(v, StrArena::Fn(intern), true)
+}
+
+/// Deserializes bytes of JSON text with a [`DeserializeSeed`](de::DeserializeSeed)
+///
+/// This works like [`from_slice`], but the seed can carry state into the deserialization, e.g.
+/// an arena, an interner or a schema.
+pub fn from_slice_seed<'a, S>(v: &'a [u8], seed: S) -> Result<S::Value>
+where
+    S: de::DeserializeSeed<'a>,
+{
+    let mut de = Deserializer::new(v);
+    let value = seed.deserialize(&mut de)?;
+    de.end()?;
+
+    Ok(value)
+}
+
+/// Deserializes bytes of JSON text with a [`DeserializeSeed`](de::DeserializeSeed), using the
+/// given [`Options`]
+pub fn from_slice_seed_with_options<'a, S>(
+    v: &'a [u8],
+    seed: S,
+    options: &Options,
+) -> Result<S::Value>
+where
+    S: de::DeserializeSeed<'a>,
+{
+    from_read_seed(slice_read(v, options)?, seed, options)
+}
+
+/// Deserializes bytes of JSON text into an existing `place`
+///
+/// Types that support it, like `String` and `Vec`, reuse their allocation: a `Vec<String>`
+/// keeps both the vector and the strings it already holds. Derived structs deserialize their
+/// fields in place when `serde_derive` is built with its `deserialize_in_place` feature, and
+/// are replaced as a whole otherwise. Strings with escape sequences are unescaped into a new
+/// allocation, which replaces the old one. If deserialization fails, `place` is left in an
+/// unspecified but valid state.
+pub fn from_slice_in_place<'a, T>(v: &'a [u8], place: &mut T) -> Result<()>
+where
+    T: de::Deserialize<'a>,
+{
+    let mut de = Deserializer::new(v);
+    T::deserialize_in_place(&mut de, place)?;
+    de.end()
+}
+
+/// Deserializes bytes of JSON text into an existing `place`, using the given [`Options`]
+///
+/// See [`from_slice_in_place`].
+pub fn from_slice_in_place_with_options<'a, T>(
+    v: &'a [u8],
+    place: &mut T,
+    options: &Options,
+) -> Result<()>
+where
+    T: de::Deserialize<'a>,
+{
+    let mut de = Deserializer::with_read(slice_read(v, options)?, options);
+    T::deserialize_in_place(&mut de, place)?;
+    de.end()
+}
+
+/// Deserializes an instance of type `T` from bytes of JSON text that end at the first NUL byte
+///
+/// This is for buffers holding a C string, e.g. passed in by a host through FFI. Everything from
+/// the first NUL byte on is ignored. If there is no NUL byte, the whole buffer is used, like
+/// [`from_slice`] does.
+///
+/// ```
+/// use serde_json_wasm::de::from_nul_terminated;
+///
+/// let buf = b"[1, 2]\0\0\0\0";
+/// assert_eq!(from_nul_terminated::<Vec<u8>>(buf), Ok(vec![1, 2]));
+/// ```
+pub fn from_nul_terminated<'a, T>(v: &'a [u8]) -> Result<T>
+where
+    T: de::Deserialize<'a>,
+{
+    let len = v.iter().position(|&b| b == 0).unwrap_or(v.len());
+    from_slice(&v[..len])
+}
+
+/// Deserializes an instance of type `T` from a C string of JSON text
+///
+/// The terminating NUL byte is not part of the JSON text. Borrowed fields point into `s`.
+#[cfg(feature = "std")]
+pub fn from_cstr<'a, T>(s: &'a std::ffi::CStr) -> Result<T>
+where
+    T: de::Deserialize<'a>,
+{
+    from_slice(s.to_bytes())
+}
+
+/// Deserializes an instance of type `T` from an I/O stream of JSON text
+///
+/// The stream is read to the end, in chunks that are parsed as they come in: only the input that
+/// the parser needs in one piece, like a long string, is buffered beyond the current chunk.
+/// Borrowed fields are not supported since the buffer does not outlive the call.
+#[cfg(feature = "std")]
+pub fn from_reader<R, T>(reader: R) -> Result<T>
+where
+    R: std::io::Read,
+    T: de::DeserializeOwned,
+{
+    from_reader_with_options(reader, &Options::default())
+}
+
+/// Deserializes an instance of type `T` from an I/O stream of JSON text, using the given
+/// [`Options`]
+///
+/// With [`Options::max_input_len`], reading stops as soon as the stream turns out to be too long.
+#[cfg(feature = "std")]
+pub fn from_reader_with_options<R, T>(mut reader: R, options: &Options) -> Result<T>
+where
+    R: std::io::Read,
+    T: de::DeserializeOwned,
+{
+    use std::io::ErrorKind;
+
+    let read = move |buf: &mut [u8]| loop {
+        match std::io::Read::read(&mut reader, buf) {
+            Err(error) if error.kind() == ErrorKind::Interrupted => {}
+            result => return result.map_err(|error| Error::Io(error.kind())),
+        }
+    };
+    from_read(
+        read::IoRead::new(
+            read::ReadFn::new(read, READER_CHUNK_SIZE),
+            options.max_input_len,
+        ),
+        options,
+    )
+}
+
+/// Size of the chunks read by [`from_reader`]
+#[cfg(feature = "std")]
+const READER_CHUNK_SIZE: usize = 4096;
+
+/// Deserializes an instance of type T from a string of JSON text
+pub fn from_str<'a, T>(s: &'a str) -> Result<T>
+where
+    T: de::Deserialize<'a>,
+{
+    from_slice(s.as_bytes())
+}
+
+/// Deserializes an instance of type T from the first JSON value in a string, returning the
+/// number of bytes consumed
+pub fn from_str_partial<'a, T>(s: &'a str) -> Result<(T, usize)>
+where
+    T: de::Deserialize<'a>,
+{
+    from_slice_partial(s.as_bytes())
+}
+
+/// Deserializes an instance of type T from a string of JSON text, using the given [`Options`]
+pub fn from_str_with_options<'a, T>(s: &'a str, options: &Options) -> Result<T>
+where
+    T: de::Deserialize<'a>,
+{
+    from_slice_with_options(s.as_bytes(), options)
+}
+
+/// Deserializes an instance of type T from a string of JSON text, reporting the byte offset of
+/// errors
+pub fn from_str_with_offset<'a, T>(s: &'a str) -> core::result::Result<T, ErrorWithOffset>
+where
+    T: de::Deserialize<'a>,
+{
+    from_slice_with_offset(s.as_bytes())
+}
+
+/// Deserializes an instance of type T from a string of JSON text, reporting the path to the value
+/// that failed
+pub fn from_str_with_path<'a, T>(s: &'a str) -> core::result::Result<T, ErrorWithPath>
+where
+    T: de::Deserialize<'a>,
+{
+    from_slice_with_path(s.as_bytes())
+}
+
+/// Deserializes an instance of type T from a string of JSON text, reporting the types and enum
+/// variants that were being deserialized when an error occurred
+#[cfg(feature = "type-context")]
+pub fn from_str_with_context<'a, T>(s: &'a str) -> core::result::Result<T, ErrorWithContext>
+where
+    T: de::Deserialize<'a>,
+{
+    from_slice_with_context(s.as_bytes())
+}
+
+/// Deserializes a string of JSON text into an existing `place`, see [`from_slice_in_place`]
+pub fn from_str_in_place<'a, T>(s: &'a str, place: &mut T) -> Result<()>
+where
+    T: de::Deserialize<'a>,
+{
+    from_slice_in_place(s.as_bytes(), place)
+}
+
+/// Deserializes a string of JSON text into an existing `place`, using the given [`Options`]
+///
+/// See [`from_slice_in_place`].
+pub fn from_str_in_place_with_options<'a, T>(
+    s: &'a str,
+    place: &mut T,
+    options: &Options,
+) -> Result<()>
+where
+    T: de::Deserialize<'a>,
+{
+    from_slice_in_place_with_options(s.as_bytes(), place, options)
+}
+
+/// Deserializes a string of JSON text with a [`DeserializeSeed`](de::DeserializeSeed)
+pub fn from_str_seed<'a, S>(s: &'a str, seed: S) -> Result<S::Value>
+where
+    S: de::DeserializeSeed<'a>,
+{
+    from_slice_seed(s.as_bytes(), seed)
+}
+
+/// Deserializes a string of JSON text with a [`DeserializeSeed`](de::DeserializeSeed), using the
+/// given [`Options`]
+pub fn from_str_seed_with_options<'a, S>(s: &'a str, seed: S, options: &Options) -> Result<S::Value>
+where
+    S: de::DeserializeSeed<'a>,
+{
+    from_slice_seed_with_options(s.as_bytes(), seed, options)
+}
+
+/// Checks that `input` is one well-formed JSON value, without deserializing it
+///
+/// ```
+/// use serde_json_wasm::de::{validate, Error};
+///
+/// assert_eq!(validate(br#"{"amount": [1, 2]}"#), Ok(()));
+/// assert_eq!(validate(br#"{"amount": [1, 2}"#), Err(Error::ExpectedListCommaOrEnd));
+/// ```
+pub fn validate(input: &[u8]) -> Result<()> {
+    validate_with_options(input, &Options::default())
+}
+
+/// Checks that `input` is one well-formed JSON value, using the given [`Options`]
+///
+/// The syntax extensions that are enabled are accepted and the limits on nesting depth and
+/// lengths are checked. Options that depend on the type being deserialized are not:
+/// [`DuplicateKeys::Reject`] and [`Options::deny_unknown_fields`] need to know the fields, and
+/// the number options need to know the target type. An input that passes can therefore still
+/// fail to deserialize, e.g. because of a duplicate key or a missing field.
+pub fn validate_with_options(input: &[u8], options: &Options) -> Result<()> {
+    if let Some(max_input_len) = options.max_input_len {
+        if input.len() > max_input_len {
+            return Err(Error::InputTooLong);
+        }
+    }
+    let mut de = Deserializer::with_options(input, options);
+    de.skip_value_checked(true)?;
+    de.end()
+}
+
+/// Returns the type of the JSON value in `input` from its first character, or `None` if the
+/// input is empty or doesn't start with a value
+///
+/// Only leading whitespace and the first character are looked at, so the value may still turn
+/// out to be invalid when it is deserialized.
+///
+/// ```
+/// use serde_json_wasm::de::{peek_type, JsonType};
+///
+/// assert_eq!(peek_type(br#" {"transfer": {}}"#), Some(JsonType::Object));
+/// assert_eq!(peek_type(b"[1, 2]"), Some(JsonType::Array));
+/// ```
+pub fn peek_type(input: &[u8]) -> Option<JsonType> {
+    Deserializer::new(input)
+        .parse_whitespace()
+        .and_then(JsonType::from_first_byte)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::from_str;
+    use serde_derive::Deserialize;
+
+    #[derive(Debug, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+    enum Type {
         #[serde(rename = "boolean")]
         Boolean,
         #[serde(rename = "number")]
@@ -607,277 +2447,3100 @@ mod tests {
     }
 
     #[test]
-    fn parse_whitespace() {
-        assert_eq!(from_str(" true"), Ok(true));
-        assert_eq!(from_str("\ttrue"), Ok(true));
-        assert_eq!(from_str("\ntrue"), Ok(true));
-        assert_eq!(from_str("\rtrue"), Ok(true));
-        assert_eq!(from_str("\n\rtrue"), Ok(true));
-        assert_eq!(from_str("\r\ntrue"), Ok(true));
-        assert_eq!(from_str("true "), Ok(true));
-        assert_eq!(from_str("true\t"), Ok(true));
-        assert_eq!(from_str("true\n"), Ok(true));
-        assert_eq!(from_str("true\r"), Ok(true));
-        assert_eq!(from_str("true\n\r"), Ok(true));
-        assert_eq!(from_str("true\r\n"), Ok(true));
+    fn parse_whitespace() {
+        assert_eq!(from_str(" true"), Ok(true));
+        assert_eq!(from_str("\ttrue"), Ok(true));
+        assert_eq!(from_str("\ntrue"), Ok(true));
+        assert_eq!(from_str("\rtrue"), Ok(true));
+        assert_eq!(from_str("\n\rtrue"), Ok(true));
+        assert_eq!(from_str("\r\ntrue"), Ok(true));
+        assert_eq!(from_str("true "), Ok(true));
+        assert_eq!(from_str("true\t"), Ok(true));
+        assert_eq!(from_str("true\n"), Ok(true));
+        assert_eq!(from_str("true\r"), Ok(true));
+        assert_eq!(from_str("true\n\r"), Ok(true));
+        assert_eq!(from_str("true\r\n"), Ok(true));
+
+        assert_eq!(from_str("[4,5]"), Ok([4, 5]));
+        assert_eq!(from_str(" [4,5]"), Ok([4, 5]));
+        assert_eq!(from_str("\t[4,5]"), Ok([4, 5]));
+        assert_eq!(from_str("\n[4,5]"), Ok([4, 5]));
+        assert_eq!(from_str("\r[4,5]"), Ok([4, 5]));
+        assert_eq!(from_str("\n\r[4,5]"), Ok([4, 5]));
+        assert_eq!(from_str("\r\n[4,5]"), Ok([4, 5]));
+        assert_eq!(from_str("[ 4,5]"), Ok([4, 5]));
+        assert_eq!(from_str("[\t4,5]"), Ok([4, 5]));
+        assert_eq!(from_str("[\n4,5]"), Ok([4, 5]));
+        assert_eq!(from_str("[\r4,5]"), Ok([4, 5]));
+        assert_eq!(from_str("[\n\r4,5]"), Ok([4, 5]));
+        assert_eq!(from_str("[\r\n4,5]"), Ok([4, 5]));
+        assert_eq!(from_str("[4 ,5]"), Ok([4, 5]));
+        assert_eq!(from_str("[4\t,5]"), Ok([4, 5]));
+        assert_eq!(from_str("[4\n,5]"), Ok([4, 5]));
+        assert_eq!(from_str("[4\r,5]"), Ok([4, 5]));
+        assert_eq!(from_str("[4\n\r,5]"), Ok([4, 5]));
+        assert_eq!(from_str("[4\r\n,5]"), Ok([4, 5]));
+        assert_eq!(from_str("[4, 5]"), Ok([4, 5]));
+        assert_eq!(from_str("[4,\t5]"), Ok([4, 5]));
+        assert_eq!(from_str("[4,\n5]"), Ok([4, 5]));
+        assert_eq!(from_str("[4,\r5]"), Ok([4, 5]));
+        assert_eq!(from_str("[4,\n\r5]"), Ok([4, 5]));
+        assert_eq!(from_str("[4,\r\n5]"), Ok([4, 5]));
+        assert_eq!(from_str("[4,5 ]"), Ok([4, 5]));
+        assert_eq!(from_str("[4,5\t]"), Ok([4, 5]));
+        assert_eq!(from_str("[4,5\n]"), Ok([4, 5]));
+        assert_eq!(from_str("[4,5\r]"), Ok([4, 5]));
+        assert_eq!(from_str("[4,5\n\r]"), Ok([4, 5]));
+        assert_eq!(from_str("[4,5\r\n]"), Ok([4, 5]));
+        assert_eq!(from_str("[4,5] "), Ok([4, 5]));
+        assert_eq!(from_str("[4,5]\t"), Ok([4, 5]));
+        assert_eq!(from_str("[4,5]\n"), Ok([4, 5]));
+        assert_eq!(from_str("[4,5]\r"), Ok([4, 5]));
+        assert_eq!(from_str("[4,5]\n\r"), Ok([4, 5]));
+        assert_eq!(from_str("[4,5]\r\n"), Ok([4, 5]));
+    }
+
+    #[test]
+    fn array() {
+        assert_eq!(from_str::<[i32; 0]>("[]"), Ok([]));
+        assert_eq!(from_str("[0, 1, 2]"), Ok([0, 1, 2]));
+
+        // errors
+        assert!(from_str::<[i32; 2]>("[0, 1,]").is_err());
+    }
+
+    #[test]
+    #[allow(clippy::unit_cmp)]
+    fn tuple() {
+        type Pair = (i64, i64);
+        type Wrapped = (i64,); // Comma differentiates one element tuple from a primary type surrounded by parentheses
+        type Unit = ();
+
+        let pair: Pair = (1, 2);
+        assert_eq!(from_str("[1,2]"), Ok(pair));
+        assert_eq!(serde_json::from_str::<Pair>("[1,2]").unwrap(), pair);
+
+        let wrapped: Wrapped = (5,);
+        assert_eq!(from_str("[5]"), Ok(wrapped));
+        assert_eq!(serde_json::from_str::<Wrapped>("[5]").unwrap(), wrapped);
+
+        let unit: Unit = ();
+        assert_eq!(from_str("null"), Ok(unit));
+        assert_eq!(serde_json::from_str::<()>("null").unwrap(), unit);
+    }
+
+    #[test]
+    fn tuple_variant() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        enum Ops {
+            Exit(),
+            Square(i32),
+            Add(i64, i64),
+        }
+        assert_eq!(from_str(r#"{"Exit":[]}"#), Ok(Ops::Exit()));
+        assert_eq!(
+            serde_json::from_str::<Ops>(r#"{"Exit":[]}"#).unwrap(),
+            Ops::Exit()
+        );
+        assert_eq!(from_str(r#"{"Square":1}"#), Ok(Ops::Square(1)));
+        assert_eq!(
+            serde_json::from_str::<Ops>(r#"{"Square":1}"#).unwrap(),
+            Ops::Square(1)
+        );
+        assert_eq!(from_str(r#"{"Add":[2,3]}"#), Ok(Ops::Add(2, 3)));
+        assert_eq!(
+            serde_json::from_str::<Ops>(r#"{"Add":[2,3]}"#).unwrap(),
+            Ops::Add(2, 3)
+        );
+    }
+
+    #[test]
+    fn bool() {
+        assert_eq!(from_str("true"), Ok(true));
+        assert_eq!(from_str(" true"), Ok(true));
+        assert_eq!(from_str("true "), Ok(true));
+
+        assert_eq!(from_str("false"), Ok(false));
+        assert_eq!(from_str(" false"), Ok(false));
+        assert_eq!(from_str("false "), Ok(false));
+
+        // errors
+        assert!(from_str::<bool>("true false").is_err());
+        assert!(from_str::<bool>("tru").is_err());
+    }
+
+    #[test]
+    fn enum_clike() {
+        assert_eq!(from_str(r#" "boolean" "#), Ok(Type::Boolean));
+        assert_eq!(from_str(r#" "number" "#), Ok(Type::Number));
+        assert_eq!(from_str(r#" "thing" "#), Ok(Type::Thing));
+    }
+
+    #[test]
+    fn char() {
+        assert_eq!(from_str(r#" "a" "#), Ok('a'));
+        assert_eq!(from_str(r#" "👏" "#), Ok('👏'));
+        assert_eq!(from_str(r#" "\n" "#), Ok('\n'));
+        assert_eq!(from_str(r#" "\"" "#), Ok('"'));
+        assert_eq!(from_str(r#" "\u00e4" "#), Ok('ä'));
+        assert_eq!(from_str(r#" "\uD83D\uDC4F" "#), Ok('👏'));
+
+        // errors
+        assert!(from_str::<char>(r#" "" "#).is_err());
+        assert!(from_str::<char>(r#" "ab" "#).is_err());
+        assert!(from_str::<char>(r#" "\n\n" "#).is_err());
+        assert_eq!(
+            from_str::<char>(r#" 1 "#),
+            Err(crate::de::Error::InvalidType(crate::de::JsonType::Number))
+        );
+    }
+
+    #[test]
+    fn enum_clike_integer() {
+        // Derived enums accept the variant index
+        assert_eq!(from_str(r#" 0 "#), Ok(Type::Boolean));
+        assert_eq!(from_str(r#" 2 "#), Ok(Type::Thing));
+        assert_eq!(
+            from_str(r#"[1, "number"]"#),
+            Ok(vec![Type::Number, Type::Number])
+        );
+
+        // errors
+        assert!(from_str::<Type>(r#" 3 "#).is_err());
+        assert!(from_str::<Type>(r#" -1 "#).is_err());
+    }
+
+    #[test]
+    fn enum_unknown_variant() {
+        let err = from_str::<Type>(r#" "thnig" "#).unwrap_err();
+        match &err {
+            crate::de::Error::UnknownVariant { variant, .. } => assert_eq!(variant, "thnig"),
+            _ => panic!("unexpected error: {:?}", err),
+        }
+        #[cfg(not(feature = "expected-variants"))]
+        assert_eq!(err.to_string(), "unknown variant `thnig`");
+        #[cfg(feature = "expected-variants")]
+        assert_eq!(
+            err.to_string(),
+            "unknown variant `thnig`, expected one of `boolean`, `number`, `thing`"
+        );
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        #[serde(tag = "type", rename_all = "snake_case")]
+        enum Msg {
+            Transfer { amount: u64 },
+            Burn {},
+        }
+        let err = from_str::<Msg>(r#"{ "type": "trnsfer", "amount": 1 }"#).unwrap_err();
+        #[cfg(not(feature = "expected-variants"))]
+        assert_eq!(err.to_string(), "unknown variant `trnsfer`");
+        #[cfg(feature = "expected-variants")]
+        assert_eq!(
+            err.to_string(),
+            "unknown variant `trnsfer`, expected `transfer` or `burn`"
+        );
+    }
+
+    #[test]
+    fn string() {
+        assert_eq!(from_str(r#" "hello" "#), Ok("hello".to_string()));
+        assert_eq!(from_str(r#" "" "#), Ok("".to_string()));
+        assert_eq!(from_str(r#" " " "#), Ok(" ".to_string()));
+        assert_eq!(from_str(r#" "👏" "#), Ok("👏".to_string()));
+
+        // Unescapes things
+        assert_eq!(from_str(r#" "hel\tlo" "#), Ok("hel\tlo".to_string()));
+        assert_eq!(from_str(r#" "hel\\lo" "#), Ok("hel\\lo".to_string()));
+
+        // escaped " in the string content
+        assert_eq!(from_str(r#" "foo\"bar" "#), Ok(r#"foo"bar"#.to_string()));
+        assert_eq!(from_str(r#" "foo\\\"ba" "#), Ok(r#"foo\"ba"#.to_string()));
+        assert_eq!(from_str(r#" "foo\"\"ba" "#), Ok(r#"foo""ba"#.to_string()));
+        assert_eq!(from_str(r#" "\"bar" "#), Ok(r#""bar"#.to_string()));
+        assert_eq!(from_str(r#" "foo\"" "#), Ok(r#"foo""#.to_string()));
+        assert_eq!(from_str(r#" "\"" "#), Ok(r#"""#.to_string()));
+
+        // non-escaped " preceded by backslashes
+        assert_eq!(from_str(r#" "fooooo\\" "#), Ok(r#"fooooo\"#.to_string()));
+        assert_eq!(from_str(r#" "fooo\\\\" "#), Ok(r#"fooo\\"#.to_string()));
+        assert_eq!(from_str(r#" "fo\\\\\\" "#), Ok(r#"fo\\\"#.to_string()));
+        assert_eq!(from_str(r#" "\\\\\\\\" "#), Ok(r#"\\\\"#.to_string()));
+    }
+
+    #[test]
+    fn struct_string_escaped() {
+        #[derive(Debug, Deserialize, PartialEq, serde_derive::Serialize)]
+        struct Note {
+            text: String,
+        }
+
+        assert_eq!(
+            from_str(r#"{ "text": "line\none\ttab \"quoted\" back\\slash \/ \b\f\r" }"#),
+            Ok(Note {
+                text: "line\none\ttab \"quoted\" back\\slash / \u{8}\u{c}\r".to_string()
+            })
+        );
+
+        // round-trips everything the serializer escapes
+        let note = Note {
+            text: "\"\\\u{0}\u{1f}\u{8}\t\n\u{c}\r 👏".to_string(),
+        };
+        let json = crate::to_string(&note).unwrap();
+        assert_eq!(from_str::<Note>(&json), Ok(note));
+    }
+
+    #[test]
+    fn string_unicode_escapes() {
+        assert_eq!(from_str(r#" "\u0041" "#), Ok("A".to_string()));
+        assert_eq!(from_str(r#" "\u00e4\u0BEC" "#), Ok("ä௬".to_string()));
+        assert_eq!(from_str(r#" "\uD83D\uDC4F" "#), Ok("👏".to_string()));
+        assert_eq!(
+            crate::from_slice(br#" "clap \uD83D\uDC4F\uD83D\uDC4F!" "#),
+            Ok("clap 👏👏!".to_string())
+        );
+        assert_eq!(
+            from_str::<String>(r#" "\uD83E\uDD7A" "#).unwrap(),
+            serde_json::from_str::<String>(r#" "\uD83E\uDD7A" "#).unwrap()
+        );
+
+        // errors
+        assert_eq!(
+            from_str::<String>(r#" "\uD83D" "#),
+            Err(crate::de::Error::LoneSurrogateFound)
+        );
+        assert_eq!(
+            from_str::<String>(r#" "\uDC4F\uD83D" "#),
+            Err(crate::de::Error::ExpectedHighSurrogate)
+        );
+    }
+
+    #[test]
+    fn struct_cow_str() {
+        use std::borrow::Cow;
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Memo<'a> {
+            #[serde(borrow)]
+            text: Cow<'a, str>,
+        }
+
+        let memo: Memo<'_> = from_str(r#"{ "text": "no escapes here" }"#).unwrap();
+        assert_eq!(memo.text, "no escapes here");
+        if let Cow::Owned(_) = memo.text {
+            panic!("expected borrowed string");
+        }
+
+        let memo: Memo<'_> = from_str(r#"{ "text": "tab\there" }"#).unwrap();
+        assert_eq!(memo.text, "tab\there");
+        if let Cow::Borrowed(_) = memo.text {
+            panic!("expected owned string");
+        }
+
+        // plain &str borrows as long as there is nothing to unescape
+        assert_eq!(from_str::<&str>(r#" "hello" "#), Ok("hello"));
+        assert!(from_str::<&str>(r#" "hel\tlo" "#).is_err());
+    }
+
+    #[test]
+    fn struct_str_in_place() {
+        use super::from_slice_mut;
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Transfer<'a> {
+            recipient: &'a str,
+            memo: &'a str,
+            tags: Vec<&'a str>,
+        }
+
+        let mut buf =
+            br#"{ "recipient": "alice", "me\u006do": "say \"hi\"\n", "tags": ["a\\b", "\uD83D\uDC4F"] }"#
+                .to_vec();
+        assert_eq!(
+            from_slice_mut(&mut buf),
+            Ok(Transfer {
+                recipient: "alice",
+                memo: "say \"hi\"\n",
+                tags: vec!["a\\b", "👏"],
+            })
+        );
+
+        let mut buf = br#" "fo\"o" "#.to_vec();
+        assert_eq!(from_slice_mut::<String>(&mut buf), Ok("fo\"o".to_string()));
+
+        // errors
+        let mut buf = br#" "fo\qo" "#.to_vec();
+        assert_eq!(
+            from_slice_mut::<&str>(&mut buf),
+            Err(crate::de::Error::InvalidEscape)
+        );
+        let mut buf = br#" "fo\"o "#.to_vec();
+        assert_eq!(
+            from_slice_mut::<&str>(&mut buf),
+            Err(crate::de::Error::EofWhileParsingString)
+        );
+    }
+
+    #[test]
+    fn struct_bool() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Led {
+            led: bool,
+        }
+
+        assert_eq!(from_str(r#"{ "led": true }"#), Ok(Led { led: true }));
+        assert_eq!(from_str(r#"{ "led": false }"#), Ok(Led { led: false }));
+    }
+
+    #[test]
+    fn struct_i8() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Temperature {
+            temperature: i8,
+        }
+
+        assert_eq!(
+            from_str(r#"{ "temperature": -17 }"#),
+            Ok(Temperature { temperature: -17 })
+        );
+
+        assert_eq!(
+            from_str(r#"{ "temperature": -0 }"#),
+            Ok(Temperature { temperature: -0 })
+        );
+
+        assert_eq!(
+            from_str(r#"{ "temperature": 0 }"#),
+            Ok(Temperature { temperature: 0 })
+        );
+
+        // out of range
+        assert!(from_str::<Temperature>(r#"{ "temperature": 128 }"#).is_err());
+        assert!(from_str::<Temperature>(r#"{ "temperature": -129 }"#).is_err());
+    }
+
+    #[test]
+    fn struct_option() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Property {
+            description: Option<String>,
+        }
+
+        assert_eq!(
+            from_str(r#"{ "description": "An ambient temperature sensor" }"#),
+            Ok(Property {
+                description: Some("An ambient temperature sensor".to_string()),
+            })
+        );
+
+        assert_eq!(
+            from_str(r#"{ "description": null }"#),
+            Ok(Property { description: None })
+        );
+
+        assert_eq!(from_str(r#"{}"#), Ok(Property { description: None }));
+    }
+
+    #[test]
+    fn struct_u8() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Temperature {
+            temperature: u8,
+        }
+
+        assert_eq!(
+            from_str(r#"{ "temperature": 20 }"#),
+            Ok(Temperature { temperature: 20 })
+        );
+
+        assert_eq!(
+            from_str(r#"{ "temperature": 0 }"#),
+            Ok(Temperature { temperature: 0 })
+        );
+
+        // out of range
+        assert!(from_str::<Temperature>(r#"{ "temperature": 256 }"#).is_err());
+        assert!(from_str::<Temperature>(r#"{ "temperature": -1 }"#).is_err());
+    }
+
+    #[test]
+    fn integer128() {
+        assert_eq!(from_str::<i128>(r#"0"#), Ok(0));
+        assert_eq!(from_str::<i128>(r#"-1"#), Ok(-1));
+        assert_eq!(
+            from_str::<i128>(r#"170141183460469231731687303715884105727"#),
+            Ok(std::i128::MAX)
+        );
+        assert_eq!(
+            from_str::<i128>(r#"-170141183460469231731687303715884105728"#),
+            Ok(std::i128::MIN)
+        );
+        assert_eq!(from_str::<u128>(r#"0"#), Ok(0));
+        assert_eq!(
+            from_str::<u128>(r#"18446744073709551616"#),
+            Ok(std::u64::MAX as u128 + 1)
+        );
+        assert_eq!(
+            from_str::<u128>(r#"340282366920938463463374607431768211455"#),
+            Ok(std::u128::MAX)
+        );
+
+        // out of range
+        assert_eq!(
+            from_str::<i128>(r#"170141183460469231731687303715884105728"#),
+            Err(crate::de::Error::NumberOutOfRange)
+        );
+        assert_eq!(
+            from_str::<u128>(r#"340282366920938463463374607431768211456"#),
+            Err(crate::de::Error::NumberOutOfRange)
+        );
+        assert_eq!(
+            from_str::<u128>(r#"-1"#),
+            Err(crate::de::Error::NumberOutOfRange)
+        );
+    }
+
+    #[test]
+    fn struct_tuple() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Xy(i8, i8);
+
+        assert_eq!(from_str(r#"[10, 20]"#), Ok(Xy(10, 20)));
+        assert_eq!(from_str(r#"[10, -20]"#), Ok(Xy(10, -20)));
+
+        // wrong number of args
+        assert_eq!(
+            from_str::<Xy>(r#"[10]"#),
+            Err(crate::de::Error::InvalidLength(1))
+        );
+        assert_eq!(
+            from_str::<Xy>(r#"[10, 20, 30]"#),
+            Err(crate::de::Error::TrailingCharacters)
+        );
+    }
+
+    #[test]
+    fn struct_empty() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Empty {}
+
+        assert_eq!(from_str(r#"{}"#), Ok(Empty {}));
+        assert_eq!(serde_json::from_str::<Empty>(r#"{}"#).unwrap(), Empty {});
+    }
+
+    #[test]
+    fn struct_nothing() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Nothing;
+
+        assert_eq!(from_str(r#"null"#), Ok(Nothing));
+        assert_eq!(serde_json::from_str::<Nothing>(r#"null"#).unwrap(), Nothing);
+    }
+
+    #[test]
+    fn map() {
+        use std::collections::BTreeMap;
+
+        let mut expected = BTreeMap::new();
+        expected.insert("a".to_string(), 1u8);
+        expected.insert("b".to_string(), 2);
+        assert_eq!(from_str(r#"{ "a": 1, "b": 2 }"#), Ok(expected));
+        assert_eq!(from_str(r#"{}"#), Ok(BTreeMap::<String, u8>::new()));
+
+        // errors
+        assert_eq!(
+            from_str::<BTreeMap<String, u8>>(r#"{ "a": 1, }"#),
+            Err(crate::de::Error::TrailingComma)
+        );
+        assert_eq!(
+            from_str::<BTreeMap<String, u8>>(r#"[]"#),
+            Err(crate::de::Error::InvalidType(crate::de::JsonType::Array))
+        );
+    }
+
+    #[test]
+    fn map_integer_keys() {
+        use std::collections::BTreeMap;
+
+        let mut expected = BTreeMap::new();
+        expected.insert(1u64, "one".to_string());
+        expected.insert(42, "forty-two".to_string());
+        assert_eq!(
+            from_str(r#"{ "1": "one", "42": "forty-two" }"#),
+            Ok(expected.clone())
+        );
+        assert_eq!(
+            serde_json::from_str::<BTreeMap<u64, String>>(r#"{ "1": "one", "42": "forty-two" }"#)
+                .unwrap(),
+            expected
+        );
+
+        let mut expected = BTreeMap::new();
+        expected.insert(-5i32, true);
+        expected.insert(0, false);
+        assert_eq!(from_str(r#"{ "-5": true, "0": false }"#), Ok(expected));
+
+        let mut expected = BTreeMap::new();
+        expected.insert(std::u128::MAX, 1u8);
+        assert_eq!(
+            from_str(r#"{ "340282366920938463463374607431768211455": 1 }"#),
+            Ok(expected)
+        );
+
+        // errors
+        assert_eq!(
+            from_str::<BTreeMap<u8, bool>>(r#"{ "256": true }"#),
+            Err(crate::de::Error::NumberOutOfRange)
+        );
+        assert_eq!(
+            from_str::<BTreeMap<u8, bool>>(r#"{ "x": true }"#),
+            Err(crate::de::Error::InvalidNumber)
+        );
+        assert_eq!(
+            from_str::<BTreeMap<u8, bool>>(r#"{ "1x": true }"#),
+            Err(crate::de::Error::InvalidNumber)
+        );
+        assert_eq!(
+            from_str::<BTreeMap<u8, bool>>(r#"{ " 1": true }"#),
+            Err(crate::de::Error::InvalidNumber)
+        );
+        assert_eq!(
+            from_str::<BTreeMap<u8, bool>>(r#"{ 1: true }"#),
+            Err(crate::de::Error::KeyMustBeAString)
+        );
+    }
+
+    #[test]
+    fn map_enum_keys() {
+        use std::collections::BTreeMap;
+
+        #[derive(Debug, Deserialize, Clone, PartialEq, Eq, PartialOrd, Ord)]
+        #[serde(rename_all = "lowercase")]
+        enum Denom {
+            Uatom,
+            Ujuno,
+        }
+
+        let mut expected = BTreeMap::new();
+        expected.insert(Denom::Uatom, 100u32);
+        expected.insert(Denom::Ujuno, 5);
+        assert_eq!(
+            from_str(r#"{ "uatom": 100, "ujuno": 5 }"#),
+            Ok(expected.clone())
+        );
+        assert_eq!(
+            serde_json::from_str::<BTreeMap<Denom, u32>>(r#"{ "uatom": 100, "ujuno": 5 }"#)
+                .unwrap(),
+            expected
+        );
+
+        let mut expected = BTreeMap::new();
+        expected.insert(Type::Boolean, "b".to_string());
+        assert_eq!(from_str(r#"{ "boolean": "b" }"#), Ok(expected));
+
+        // errors
+        assert!(from_str::<BTreeMap<Denom, u32>>(r#"{ "uosmo": 1 }"#).is_err());
+    }
+
+    #[test]
+    fn any() {
+        use serde_json::{json, Value};
+
+        assert_eq!(from_str::<Value>(r#"null"#), Ok(Value::Null));
+        assert_eq!(from_str::<Value>(r#" true "#), Ok(json!(true)));
+        assert_eq!(from_str::<Value>(r#"false"#), Ok(json!(false)));
+        assert_eq!(from_str::<Value>(r#""foo\"bar""#), Ok(json!("foo\"bar")));
+        assert_eq!(from_str::<Value>(r#"0"#), Ok(json!(0)));
+        assert_eq!(from_str::<Value>(r#"-0"#), Ok(json!(0)));
+        assert_eq!(from_str::<Value>(r#"42"#), Ok(json!(42)));
+        assert_eq!(from_str::<Value>(r#"-42"#), Ok(json!(-42)));
+        assert_eq!(
+            from_str::<Value>(r#"18446744073709551615"#),
+            Ok(json!(std::u64::MAX))
+        );
+        assert_eq!(
+            from_str::<Value>(r#"-9223372036854775808"#),
+            Ok(json!(std::i64::MIN))
+        );
+        assert_eq!(from_str::<Value>(r#"[]"#), Ok(json!([])));
+        assert_eq!(from_str::<Value>(r#"{}"#), Ok(json!({})));
+
+        let input = r#"{
+            "type": "thing",
+            "count": 3,
+            "tags": ["a", null, -1, {"nested": [true, false]}],
+            "empty": {}
+        }"#;
+        assert_eq!(
+            from_str::<Value>(input),
+            Ok(serde_json::from_str::<Value>(input).unwrap())
+        );
+
+        // errors
+        #[cfg(not(feature = "float"))]
+        assert_eq!(
+            from_str::<Value>(r#"1.5"#),
+            Err(crate::de::Error::InvalidNumber)
+        );
+        assert_eq!(
+            from_str::<Value>(r#"-"#),
+            Err(crate::de::Error::InvalidNumber)
+        );
+        assert_eq!(
+            from_str::<Value>(r#"01"#),
+            Err(crate::de::Error::TrailingCharacters)
+        );
+        assert_eq!(
+            from_str::<Value>(r#"}"#),
+            Err(crate::de::Error::ExpectedSomeValue(b'}'))
+        );
+        assert_eq!(
+            from_str::<Value>(r#""#),
+            Err(crate::de::Error::EofWhileParsingValue)
+        );
+        assert_eq!(
+            from_str::<Value>(r#"[1,]"#),
+            Err(crate::de::Error::TrailingComma)
+        );
+    }
+
+    #[test]
+    fn untagged_enum() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        #[serde(untagged)]
+        enum Amount {
+            Native(u64),
+            Denominated { denom: String, amount: u64 },
+            Named(String),
+            List(Vec<u8>),
+            Nothing,
+        }
+
+        assert_eq!(from_str(r#"42"#), Ok(Amount::Native(42)));
+        assert_eq!(
+            from_str(r#"{ "denom": "ujuno", "amount": 7 }"#),
+            Ok(Amount::Denominated {
+                denom: "ujuno".to_string(),
+                amount: 7
+            })
+        );
+        assert_eq!(
+            from_str(r#""all\tof it""#),
+            Ok(Amount::Named("all\tof it".to_string()))
+        );
+        assert_eq!(from_str(r#"[1, 2]"#), Ok(Amount::List(vec![1, 2])));
+        assert_eq!(from_str(r#"null"#), Ok(Amount::Nothing));
+
+        // errors
+        assert!(from_str::<Amount>(r#"true"#).is_err());
+        assert!(from_str::<Amount>(r#"{ "denom": "ujuno" }"#).is_err());
+    }
+
+    #[test]
+    fn internally_tagged_enum() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Coin {
+            denom: String,
+            amount: u64,
+        }
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        #[serde(tag = "type", rename_all = "snake_case")]
+        enum Packet {
+            Ping,
+            Transfer { to: String, coins: Vec<Coin> },
+            Refund(Coin),
+        }
+
+        assert_eq!(from_str(r#"{ "type": "ping" }"#), Ok(Packet::Ping));
+        assert_eq!(
+            from_str(
+                r#"{ "to": "bob", "type": "transfer", "coins": [{ "denom": "ujuno", "amount": 1 }] }"#
+            ),
+            Ok(Packet::Transfer {
+                to: "bob".to_string(),
+                coins: vec![Coin {
+                    denom: "ujuno".to_string(),
+                    amount: 1
+                }]
+            })
+        );
+        assert_eq!(
+            from_str(r#"{ "type": "refund", "denom": "uatom", "amount": 2 }"#),
+            Ok(Packet::Refund(Coin {
+                denom: "uatom".to_string(),
+                amount: 2
+            }))
+        );
+
+        // errors
+        assert!(from_str::<Packet>(r#"{ "type": "pong" }"#).is_err());
+        assert!(from_str::<Packet>(r#"{ "to": "bob" }"#).is_err());
+        assert!(from_str::<Packet>(r#""ping""#).is_err());
+    }
+
+    #[test]
+    fn adjacently_tagged_enum() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        #[serde(tag = "t", content = "c", rename_all = "snake_case")]
+        enum Event {
+            Started,
+            Progress(u8),
+            Moved(i32, i32),
+            Finished { code: u32 },
+        }
+
+        assert_eq!(from_str(r#"{ "t": "started" }"#), Ok(Event::Started));
+        assert_eq!(
+            from_str(r#"{ "t": "progress", "c": 50 }"#),
+            Ok(Event::Progress(50))
+        );
+        assert_eq!(
+            from_str(r#"{ "c": [1, -1], "t": "moved" }"#),
+            Ok(Event::Moved(1, -1))
+        );
+        assert_eq!(
+            from_str(r#"{ "t": "finished", "c": { "code": 0 } }"#),
+            Ok(Event::Finished { code: 0 })
+        );
+        assert_eq!(
+            from_str(r#"{ "c": { "code": 7 }, "t": "finished" }"#),
+            Ok(Event::Finished { code: 7 })
+        );
+
+        // errors
+        assert!(from_str::<Event>(r#"{ "c": 50 }"#).is_err());
+        assert!(from_str::<Event>(r#"{ "t": "progress", "c": "50" }"#).is_err());
+    }
+
+    #[test]
+    fn struct_flatten() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Cw20Coin {
+            address: String,
+            amount: u64,
+        }
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Expiration {
+            at_height: Option<u64>,
+        }
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct AllowanceInfo {
+            #[serde(flatten)]
+            coin: Cw20Coin,
+            #[serde(flatten)]
+            expires: Expiration,
+            spender: String,
+        }
+
+        assert_eq!(
+            from_str(r#"{ "spender": "bob", "address": "alice", "amount": 5, "at_height": 10 }"#),
+            Ok(AllowanceInfo {
+                coin: Cw20Coin {
+                    address: "alice".to_string(),
+                    amount: 5
+                },
+                expires: Expiration {
+                    at_height: Some(10)
+                },
+                spender: "bob".to_string(),
+            })
+        );
+        assert_eq!(
+            from_str(r#"{ "address": "alice", "amount": 5, "spender": "bob" }"#),
+            Ok(AllowanceInfo {
+                coin: Cw20Coin {
+                    address: "alice".to_string(),
+                    amount: 5
+                },
+                expires: Expiration { at_height: None },
+                spender: "bob".to_string(),
+            })
+        );
+
+        // errors
+        assert!(from_str::<AllowanceInfo>(r#"{ "address": "alice", "spender": "bob" }"#).is_err());
+    }
+
+    #[test]
+    fn struct_flatten_catch_all() {
+        use serde_json::{json, Value};
+        use std::collections::BTreeMap;
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Msg {
+            sender: String,
+            #[serde(flatten)]
+            extra: BTreeMap<String, Value>,
+        }
+
+        let mut extra = BTreeMap::new();
+        extra.insert("funds".to_string(), json!([{ "denom": "uatom" }]));
+        extra.insert("memo".to_string(), json!("hi"));
+        extra.insert("nonce".to_string(), json!(7));
+        extra.insert("fee".to_string(), Value::Null);
+        assert_eq!(
+            from_str(
+                r#"{ "memo": "hi", "sender": "alice", "funds": [{ "denom": "uatom" }], "nonce": 7, "fee": null }"#
+            ),
+            Ok(Msg {
+                sender: "alice".to_string(),
+                extra
+            })
+        );
+        assert_eq!(
+            from_str(r#"{ "sender": "alice" }"#),
+            Ok(Msg {
+                sender: "alice".to_string(),
+                extra: BTreeMap::new()
+            })
+        );
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Labels {
+            name: String,
+            #[serde(flatten)]
+            labels: BTreeMap<String, String>,
+        }
+
+        let mut labels = BTreeMap::new();
+        labels.insert("env".to_string(), "prod".to_string());
+        assert_eq!(
+            from_str(r#"{ "env": "prod", "name": "node" }"#),
+            Ok(Labels {
+                name: "node".to_string(),
+                labels
+            })
+        );
+
+        // errors
+        assert!(from_str::<Labels>(r#"{ "name": "node", "replicas": 3 }"#).is_err());
+    }
+
+    #[test]
+    fn struct_from_array() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Record {
+            id: u32,
+            name: String,
+            active: bool,
+        }
+
+        assert_eq!(
+            from_str(r#"[1, "a", true]"#),
+            Ok(Record {
+                id: 1,
+                name: "a".to_string(),
+                active: true
+            })
+        );
+        assert_eq!(
+            serde_json::from_str::<Record>(r#"[1, "a", true]"#).unwrap(),
+            Record {
+                id: 1,
+                name: "a".to_string(),
+                active: true
+            }
+        );
+        assert_eq!(
+            from_str(r#"[[2, "b", false]]"#),
+            Ok(vec![Record {
+                id: 2,
+                name: "b".to_string(),
+                active: false
+            }])
+        );
+
+        // errors
+        assert_eq!(
+            from_str::<Record>(r#"[1, "a"]"#),
+            Err(crate::de::Error::InvalidLength(2))
+        );
+        assert_eq!(
+            from_str::<Record>(r#"[1, "a", true, 4]"#),
+            Err(crate::de::Error::TrailingCharacters)
+        );
+        assert_eq!(
+            from_str::<Record>(r#"[1, "a", true,]"#),
+            Err(crate::de::Error::TrailingComma)
+        );
+    }
+
+    #[test]
+    fn error_offset() {
+        use super::{from_str_with_offset, Error, JsonType};
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Temperature {
+            temperature: u8,
+        }
+
+        assert_eq!(
+            from_str_with_offset(r#"{ "temperature": 20 }"#),
+            Ok(Temperature { temperature: 20 })
+        );
+
+        let err = from_str_with_offset::<Temperature>(r#"{ "temperature": true }"#).unwrap_err();
+        assert_eq!(
+            (err.error, err.offset),
+            (Error::InvalidType(JsonType::Bool), 17)
+        );
+        let err = from_str_with_offset::<Temperature>(r#"{ "temperature" 20 }"#).unwrap_err();
+        assert_eq!((err.error, err.offset), (Error::ExpectedColon, 16));
+        let err = from_str_with_offset::<Temperature>(r#"{ "temperature": 20 } x"#).unwrap_err();
+        assert_eq!((err.error, err.offset), (Error::TrailingCharacters, 22));
+        let err = from_str_with_offset::<Temperature>(r#"{ "temperature": 20"#).unwrap_err();
+        assert_eq!((err.error, err.offset), (Error::EofWhileParsingObject, 19));
+
+        let err = from_str_with_offset::<Temperature>(r#""hot""#).unwrap_err();
+        assert_eq!(err.error, Error::InvalidType(JsonType::String));
+        #[cfg(not(feature = "line-column"))]
+        assert_eq!(
+            err.to_string(),
+            "Invalid type, found string at byte offset 0"
+        );
+    }
+
+    #[cfg(feature = "line-column")]
+    #[test]
+    fn error_line_column() {
+        use super::{from_str_with_offset, Error};
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Temperature {
+            temperature: u8,
+        }
+
+        let err = from_str_with_offset::<Temperature>("{\n  \"temperature\": 256\n}").unwrap_err();
+        assert_eq!(err.error, Error::NumberOutOfRange);
+        assert_eq!((err.offset, err.line, err.column), (22, 2, 21));
+        assert_eq!(
+            err.to_string(),
+            "Number is out of range for the type at line 2 column 21"
+        );
+
+        let err = from_str_with_offset::<Temperature>("\n\n x").unwrap_err();
+        assert_eq!((err.offset, err.line, err.column), (3, 3, 2));
+
+        let err = from_str_with_offset::<Temperature>("x").unwrap_err();
+        assert_eq!((err.offset, err.line, err.column), (0, 1, 1));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn reader() {
+        use super::{from_reader, Error};
+        use std::io;
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Temperature {
+            temperature: u8,
+        }
+
+        let input = br#"{ "temperature": 20 }"#;
+        assert_eq!(from_reader(&input[..]), Ok(Temperature { temperature: 20 }));
+        assert_eq!(
+            from_reader::<_, Temperature>(&input[..19]),
+            Err(Error::EofWhileParsingObject)
+        );
+
+        struct Broken;
+        impl io::Read for Broken {
+            fn read(&mut self, _buf: &mut [u8]) -> io::Result<usize> {
+                Err(io::Error::new(io::ErrorKind::ConnectionReset, "reset"))
+            }
+        }
+        let err = from_reader::<_, Temperature>(Broken).unwrap_err();
+        assert_eq!(err, Error::Io(io::ErrorKind::ConnectionReset));
+        assert_eq!(err.classify(), crate::Category::Io);
+
+        let options = super::Options::default().max_input_len(21);
+        assert_eq!(
+            super::from_reader_with_options(&input[..], &options),
+            Ok(Temperature { temperature: 20 })
+        );
+        let options = super::Options::default().max_input_len(20);
+        assert_eq!(
+            super::from_reader_with_options::<_, Temperature>(&input[..], &options),
+            Err(Error::InputTooLong)
+        );
+    }
+
+    #[test]
+    fn drive_deserializer() {
+        use super::{Deserializer, Error, Options};
+        use serde::Deserialize;
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Coin {
+            denom: String,
+            amount: u64,
+        }
+
+        let mut de = Deserializer::new(br#"{"denom": "uatom", "amount": 1} "#);
+        assert_eq!(
+            Coin::deserialize(&mut de),
+            Ok(Coin {
+                denom: "uatom".to_string(),
+                amount: 1
+            })
+        );
+        assert_eq!(de.end(), Ok(()));
+
+        let mut de = Deserializer::new(b"1 2");
+        assert_eq!(u8::deserialize(&mut de), Ok(1));
+        assert_eq!(de.end(), Err(Error::TrailingCharacters));
+
+        let options = Options::default().allow_trailing_commas(true);
+        let mut de = Deserializer::with_options(b"[1,]", &options);
+        assert_eq!(Vec::<u8>::deserialize(&mut de), Ok(vec![1]));
+        assert_eq!(de.end(), Ok(()));
+    }
+
+    #[test]
+    fn in_place() {
+        use super::{
+            from_slice_in_place, from_slice_in_place_with_options, from_str_in_place,
+            from_str_in_place_with_options, Error, Options,
+        };
+
+        let mut denoms: Vec<String> = Vec::with_capacity(8);
+        from_str_in_place(r#"["uatom", "ujuno"]"#, &mut denoms).unwrap();
+        assert_eq!(denoms, ["uatom", "ujuno"]);
+        let buffers: Vec<*const u8> = denoms.iter().map(|denom| denom.as_ptr()).collect();
+        let capacity = denoms.capacity();
+
+        from_str_in_place(r#"["uosmo", "ua"]"#, &mut denoms).unwrap();
+        assert_eq!(denoms, ["uosmo", "ua"]);
+        assert_eq!(denoms.capacity(), capacity);
+        let reused: Vec<*const u8> = denoms.iter().map(|denom| denom.as_ptr()).collect();
+        assert_eq!(reused, buffers);
+
+        from_slice_in_place(b"[]", &mut denoms).unwrap();
+        assert!(denoms.is_empty());
+        assert_eq!(
+            from_str_in_place(r#"["uatom"] 1"#, &mut denoms),
+            Err(Error::TrailingCharacters)
+        );
+
+        let mut count = 0u32;
+        from_str_in_place("7", &mut count).unwrap();
+        assert_eq!(count, 7);
+
+        let options = Options::default().allow_comments(true).max_input_len(16);
+        from_str_in_place_with_options(r#"["a"] // one"#, &mut denoms, &options).unwrap();
+        assert_eq!(denoms, ["a"]);
+        assert_eq!(
+            from_slice_in_place_with_options(br#"["a", "b", "c", "d"]"#, &mut denoms, &options),
+            Err(Error::InputTooLong)
+        );
+        assert_eq!(denoms, ["a"]);
+    }
+
+    #[test]
+    fn seed() {
+        use super::{
+            from_slice_seed, from_slice_seed_with_options, from_str_seed,
+            from_str_seed_with_options, Error, Options,
+        };
+        use serde::de::{DeserializeSeed, Deserializer, SeqAccess, Visitor};
+        use std::fmt;
+
+        /// Appends the elements of an array to an existing vector
+        struct ExtendVec<'a>(&'a mut Vec<u32>);
+
+        impl<'de, 'a> DeserializeSeed<'de> for ExtendVec<'a> {
+            type Value = usize;
+
+            fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<usize, D::Error> {
+                deserializer.deserialize_seq(self)
+            }
+        }
+
+        impl<'de, 'a> Visitor<'de> for ExtendVec<'a> {
+            type Value = usize;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+                formatter.write_str("an array of integers")
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<usize, A::Error> {
+                let mut count = 0;
+                while let Some(element) = seq.next_element()? {
+                    self.0.push(element);
+                    count += 1;
+                }
+                Ok(count)
+            }
+        }
+
+        let mut numbers = vec![1];
+        assert_eq!(from_str_seed("[2, 3]", ExtendVec(&mut numbers)), Ok(2));
+        assert_eq!(from_slice_seed(b" [4] ", ExtendVec(&mut numbers)), Ok(1));
+        assert_eq!(numbers, [1, 2, 3, 4]);
+        assert_eq!(
+            from_str_seed("[5] [6]", ExtendVec(&mut numbers)),
+            Err(Error::TrailingCharacters)
+        );
+        assert_eq!(
+            from_str_seed("[7, 8]", std::marker::PhantomData::<Vec<u8>>),
+            Ok(vec![7, 8])
+        );
+
+        let options = Options::default()
+            .allow_trailing_commas(true)
+            .max_input_len(8);
+        assert_eq!(
+            from_str_seed_with_options("[9, 10,]", ExtendVec(&mut numbers), &options),
+            Ok(2)
+        );
+        assert_eq!(
+            from_slice_seed_with_options(b"[11, 12, 13]", ExtendVec(&mut numbers), &options),
+            Err(Error::InputTooLong)
+        );
+        assert_eq!(numbers, [1, 2, 3, 4, 5, 9, 10]);
+    }
+
+    #[test]
+    fn partial() {
+        use super::{from_str_partial, Error};
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Temperature {
+            temperature: u8,
+        }
+
+        let input = r#" { "temperature": 20 }{ "temperature": 21 } [1]"#;
+        let (first, consumed) = from_str_partial::<Temperature>(input).unwrap();
+        assert_eq!((first, consumed), (Temperature { temperature: 20 }, 22));
+        let rest = &input[consumed..];
+        let (second, consumed) = from_str_partial::<Temperature>(rest).unwrap();
+        assert_eq!((second, consumed), (Temperature { temperature: 21 }, 21));
+        assert_eq!(from_str_partial(&rest[consumed..]), Ok((vec![1u8], 4)));
+
+        assert_eq!(from_str_partial("12 34"), Ok((12u8, 2)));
+        assert_eq!(from_str_partial(r#""a""b""#), Ok(("a", 3)));
+        assert_eq!(
+            from_str_partial::<Temperature>(r#"{ "temperature": 20 "#),
+            Err(Error::EofWhileParsingObject)
+        );
+        assert_eq!(
+            from_str_partial::<u8>("   "),
+            Err(Error::EofWhileParsingValue)
+        );
+    }
+
+    #[test]
+    fn hex_integers() {
+        use super::{from_str_with_options, Error, Options};
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Register {
+            address: u16,
+            value: u32,
+            offset: i8,
+        }
+
+        let options = Options::default().allow_hex_integers(true);
+        assert_eq!(
+            from_str_with_options(
+                r#"{"address": 0x1A2B, "value": 0XdeadBEEF, "offset": -0x80}"#,
+                &options
+            ),
+            Ok(Register {
+                address: 0x1A2B,
+                value: 0xDEAD_BEEF,
+                offset: -0x80
+            })
+        );
+        assert_eq!(
+            from_str_with_options("[0x0, 10]", &options),
+            Ok(vec![0u8, 10])
+        );
+        assert_eq!(
+            from_str_with_options::<u128>("0xffffffffffffffffffffffffffffffff", &options),
+            Ok(std::u128::MAX)
+        );
+        assert_eq!(
+            from_str_with_options::<u8>("0x100", &options),
+            Err(Error::NumberOutOfRange)
+        );
+        assert_eq!(
+            from_str_with_options::<u8>("0xg", &options),
+            Err(Error::InvalidNumber)
+        );
+        assert_eq!(from_str::<u16>("0x1A2B"), Err(Error::TrailingCharacters));
+    }
+
+    #[test]
+    fn exact_float_integers() {
+        use super::{from_str_with_options, Error, Options};
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Order {
+            amount: u64,
+            price: i32,
+        }
+
+        let options = Options::default().allow_exact_float_integers(true);
+        let parse = |input: &str| from_str_with_options::<i64>(input, &options);
+        assert_eq!(parse("1e3"), Ok(1000));
+        assert_eq!(parse("2.5e2"), Ok(250));
+        assert_eq!(parse("7.0"), Ok(7));
+        assert_eq!(parse("-1.2300E+2"), Ok(-123));
+        assert_eq!(parse("12300e-2"), Ok(123));
+        assert_eq!(parse("0.000e5"), Ok(0));
+        assert_eq!(parse("-0.0"), Ok(0));
+        assert_eq!(parse("0e-99999999999999999999"), Ok(0));
+        assert_eq!(parse("00.5e1"), Err(Error::TrailingCharacters));
+        assert_eq!(parse("2.5"), Err(Error::InvalidNumber));
+        assert_eq!(parse("1e-1"), Err(Error::InvalidNumber));
+        assert_eq!(
+            parse("1e99999999999999999999"),
+            Err(Error::NumberOutOfRange)
+        );
+        assert_eq!(parse("-9.223372036854775808e18"), Ok(std::i64::MIN));
+        assert_eq!(
+            parse("9.223372036854775808e18"),
+            Err(Error::NumberOutOfRange)
+        );
+        assert_eq!(parse("1."), Err(Error::TrailingCharacters));
+        assert_eq!(parse("12"), Ok(12));
+
+        assert_eq!(
+            from_str_with_options(r#"{"amount": 1.5e10, "price": -4E1}"#, &options),
+            Ok(Order {
+                amount: 15_000_000_000,
+                price: -40
+            })
+        );
+        assert_eq!(from_str_with_options("2.55e2", &options), Ok(255u8));
+        assert_eq!(
+            from_str_with_options::<u8>("2.56e2", &options),
+            Err(Error::NumberOutOfRange)
+        );
+        assert_eq!(
+            from_str_with_options::<u8>("-1e0", &options),
+            Err(Error::NumberOutOfRange)
+        );
+        assert_eq!(
+            from_str_with_options("3.40282366920938463463374607431768211455e38", &options),
+            Ok(std::u128::MAX)
+        );
+        assert_eq!(
+            from_str_with_options::<u8>("true", &options),
+            Err(Error::InvalidType(super::JsonType::Bool))
+        );
+        assert_eq!(
+            from_str_with_options::<u8>("1e3", &options.clone().deny_floats(true)),
+            Err(Error::FloatNotAllowed)
+        );
+
+        // off by default
+        assert_eq!(from_str::<u64>("1e3"), Err(Error::TrailingCharacters));
+    }
+
+    #[test]
+    fn nul_terminated() {
+        use super::{from_nul_terminated, Error};
+
+        assert_eq!(from_nul_terminated(b"[1, 2]\0garbage"), Ok(vec![1u8, 2]));
+        assert_eq!(from_nul_terminated(b" \"a\" \0\0"), Ok("a"));
+        assert_eq!(from_nul_terminated(b"true"), Ok(true));
+        assert_eq!(
+            from_nul_terminated::<u8>(b"\0 1"),
+            Err(Error::EofWhileParsingValue)
+        );
+        assert_eq!(
+            from_nul_terminated::<u8>(b"1 2\0"),
+            Err(Error::TrailingCharacters)
+        );
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn cstr() {
+        use super::from_cstr;
+        use std::ffi::CStr;
+
+        let cstr = CStr::from_bytes_with_nul(b"{\"denom\": \"uatom\"}\0").unwrap();
+        assert_eq!(
+            from_cstr::<std::collections::BTreeMap<&str, &str>>(cstr)
+                .unwrap()
+                .get("denom"),
+            Some(&"uatom")
+        );
+    }
+
+    #[test]
+    fn arena_strings() {
+        use super::{from_slice_with_arena, Error};
+        use std::cell::{Cell, RefCell};
+        use std::collections::BTreeMap;
+
+        // a toy arena, which hands out strings that are leaked
+        fn leak<'a>(s: &str) -> &'a str {
+            Box::leak(s.into())
+        }
+        let copies = Cell::new(0);
+        let alloc_str = |s: &str| {
+            copies.set(copies.get() + 1);
+            leak(s)
+        };
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Memo<'a> {
+            text: &'a str,
+            #[serde(borrow)]
+            tags: BTreeMap<&'a str, &'a str>,
+        }
+
+        let input = br#"{"text": "a\"b", "tags": {"k\u00e9y": "plain", "x": "\ud83d\ude00"}}"#;
+        let memo: Memo<'_> = from_slice_with_arena(input, &alloc_str).unwrap();
+        assert_eq!(memo.text, "a\"b");
+        assert_eq!(memo.tags.get("kéy"), Some(&"plain"));
+        assert_eq!(memo.tags.get("x"), Some(&"😀"));
+        assert_eq!(copies.get(), 3);
+
+        // plain strings are still borrowed from the input
+        let input = br#"["abc"]"#;
+        let strings: Vec<&str> = from_slice_with_arena(input, &alloc_str).unwrap();
+        assert_eq!(strings[0].as_ptr(), input[2..].as_ptr());
+        assert_eq!(copies.get(), 3);
+
+        let seen = RefCell::new(Vec::new());
+        let record = |s: &str| {
+            seen.borrow_mut().push(s.to_string());
+            leak(s)
+        };
+        let result = from_slice_with_arena::<Vec<&str>, _>(br#"["\n", "\q"]"#, &record);
+        assert_eq!(result, Err(Error::InvalidEscape));
+        assert_eq!(*seen.borrow(), ["\n"]);
+    }
+
+    #[cfg(feature = "bumpalo")]
+    #[test]
+    fn bump_strings() {
+        use super::{from_slice_in, Error};
+        use bumpalo::Bump;
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Memo<'a> {
+            text: &'a str,
+            tags: Vec<&'a str>,
+        }
+
+        let bump = Bump::new();
+        let input = br#"{"text": "a\"b", "tags": ["plain", "\ud83d\ude00"]}"#;
+        let memo: Memo<'_> = from_slice_in(input, &bump).unwrap();
+        assert_eq!(
+            memo,
+            Memo {
+                text: "a\"b",
+                tags: vec!["plain", "😀"]
+            }
+        );
+        assert!(bump.allocated_bytes() > 0);
+        // plain strings are still borrowed from the input
+        assert_eq!(memo.tags[0].as_ptr(), input[27..].as_ptr());
+
+        assert_eq!(
+            from_slice_in::<Vec<&str>>(b"[\"a\\n\xFF\"]", &bump),
+            Err(Error::InvalidUtf8(5))
+        );
+    }
+
+    #[test]
+    fn max_depth() {
+        use super::{from_str_with_options, Error, Options, DEFAULT_MAX_DEPTH};
+        use serde_json::Value;
+
+        let nested = |depth: usize| format!("{}{}", "[".repeat(depth), "]".repeat(depth));
+
+        assert!(from_str::<Value>(&nested(DEFAULT_MAX_DEPTH)).is_ok());
+        assert_eq!(
+            from_str::<Value>(&nested(DEFAULT_MAX_DEPTH + 1)),
+            Err(Error::RecursionLimitExceeded)
+        );
+        assert_eq!(
+            from_str::<Value>(&"[".repeat(100_000)),
+            Err(Error::RecursionLimitExceeded)
+        );
+        assert_eq!(
+            from_str::<Vec<Vec<u8>>>(r#"[[1, 2], [], [3]]"#),
+            Ok(vec![vec![1, 2], vec![], vec![3]])
+        );
+
+        // ignored values count as well
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Temperature {
+            temperature: u8,
+        }
+        let input = format!(r#"{{ "temperature": 20, "ignored": {} }}"#, nested(200));
+        assert_eq!(
+            from_str::<Temperature>(&input),
+            Err(Error::RecursionLimitExceeded)
+        );
+
+        let options = Options::default().max_depth(300);
+        assert_eq!(
+            from_str_with_options::<Temperature>(&input, &options),
+            Ok(Temperature { temperature: 20 })
+        );
+        let options = Options::default().max_depth(2);
+        assert_eq!(
+            from_str_with_options(r#"{ "a": [1] }"#, &options),
+            Ok(serde_json::json!({ "a": [1] }))
+        );
+        assert_eq!(
+            from_str_with_options::<Value>(r#"{ "a": [[1]] }"#, &options),
+            Err(Error::RecursionLimitExceeded)
+        );
+        let options = Options::default().max_depth(0);
+        assert_eq!(from_str_with_options(r#"1"#, &options), Ok(1u8));
+        assert_eq!(
+            from_str_with_options::<Vec<u8>>(r#"[]"#, &options),
+            Err(Error::RecursionLimitExceeded)
+        );
+    }
+
+    #[test]
+    fn trailing_commas() {
+        use super::{from_str_with_options, Error, Options};
+        use std::collections::BTreeMap;
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Temperature {
+            temperature: u8,
+        }
+
+        let lenient = Options::default().allow_trailing_commas(true);
+        assert_eq!(
+            from_str_with_options(r#"[1, 2, ]"#, &lenient),
+            Ok(vec![1u8, 2])
+        );
+        assert_eq!(
+            from_str_with_options(r#"[1, [2,],]"#, &lenient),
+            Ok((1u8, vec![2u8]))
+        );
+        assert_eq!(
+            from_str_with_options(r#"{ "temperature": 20, }"#, &lenient),
+            Ok(Temperature { temperature: 20 })
+        );
+        assert_eq!(
+            from_str_with_options(
+                r#"{ "temperature": 20, "ignored": [{ "a": [1,], }, 2,], }"#,
+                &lenient
+            ),
+            Ok(Temperature { temperature: 20 })
+        );
+        let mut expected = BTreeMap::new();
+        expected.insert("a".to_string(), 1u8);
+        assert_eq!(
+            from_str_with_options(r#"{ "a": 1 ,}"#, &lenient),
+            Ok(expected)
+        );
+
+        // only a single comma after the last value
+        assert_eq!(
+            from_str_with_options::<Vec<u8>>(r#"[,]"#, &lenient),
+            Err(Error::ExpectedSomeValue(b','))
+        );
+        assert_eq!(
+            from_str_with_options::<Vec<u8>>(r#"[1,,]"#, &lenient),
+            Err(Error::ExpectedSomeValue(b','))
+        );
+        assert_eq!(
+            from_str_with_options::<Temperature>(r#"{ "temperature": 20,, }"#, &lenient),
+            Err(Error::KeyMustBeAString)
+        );
+
+        // rejected by default
+        assert_eq!(
+            from_str::<Vec<u8>>(r#"[1, 2, ]"#),
+            Err(Error::TrailingComma)
+        );
+        assert_eq!(
+            from_str::<Temperature>(r#"{ "temperature": 20, }"#),
+            Err(Error::TrailingComma)
+        );
+    }
+
+    #[test]
+    fn comments() {
+        use super::{from_str_with_options, Error, Options};
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Config<'a> {
+            name: &'a str,
+            ports: Vec<u16>,
+        }
+
+        let options = Options::default().allow_comments(true);
+        let input = r#"
+            // service configuration
+            {
+                "name": "a//b/*c*/", // not a comment inside the string
+                /* "ignored": [1, */ "ports": [80, /* 8080, */ 443]
+                , "other": [1 /* ] */, 2] /**/
+            }
+            // end"#;
+        assert_eq!(
+            from_str_with_options(input, &options),
+            Ok(Config {
+                name: "a//b/*c*/",
+                ports: vec![80, 443]
+            })
+        );
+        assert_eq!(from_str_with_options("1/**/", &options), Ok(1u8));
+        assert_eq!(from_str_with_options("1//", &options), Ok(1u8));
+        assert_eq!(
+            from_str_with_options::<u8>("1 /*", &options),
+            Err(Error::EofWhileParsingComment)
+        );
+        assert_eq!(
+            from_str_with_options::<u8>("1 /* */ /* *", &options),
+            Err(Error::EofWhileParsingComment)
+        );
+        assert_eq!(
+            from_str_with_options::<Vec<u8>>("[1 /* ]", &options),
+            Err(Error::EofWhileParsingList)
+        );
+        assert_eq!(
+            from_str_with_options::<u8>("1 / 2", &options),
+            Err(Error::TrailingCharacters)
+        );
+        assert_eq!(
+            from_str_with_options::<u8>("/ 1", &options),
+            Err(Error::ExpectedSomeValue(b'/'))
+        );
+
+        // rejected by default
+        assert_eq!(from_str::<u8>("1 // one"), Err(Error::TrailingCharacters));
+    }
+
+    #[cfg(feature = "float")]
+    #[test]
+    fn floats() {
+        use super::{from_str_with_options, Error, JsonType, Options};
+
+        assert_eq!(from_str("1.5"), Ok(1.5f64));
+        assert_eq!(from_str("-0.25e2"), Ok(-25f32));
+        assert_eq!(from_str("[0, 1E-2, 2e+1]"), Ok(vec![0.0f64, 0.01, 20.0]));
+        assert_eq!(
+            from_str::<serde_json::Value>("[1, -1.5, 2e3]"),
+            Ok(serde_json::json!([1, -1.5, 2000.0]))
+        );
+
+        assert_eq!(from_str::<f64>("1."), Err(Error::InvalidNumber));
+        assert_eq!(from_str::<f64>("1e"), Err(Error::InvalidNumber));
+        assert_eq!(from_str::<f64>("-"), Err(Error::InvalidNumber));
+        assert_eq!(from_str::<f64>("1e400"), Err(Error::NumberOutOfRange));
+        assert_eq!(from_str::<f32>("1e39"), Err(Error::NumberOutOfRange));
+        assert_eq!(from_str::<f64>(".5"), Err(Error::ExpectedSomeValue(b'.')));
+        assert_eq!(
+            from_str::<f64>("\"1\""),
+            Err(Error::InvalidType(JsonType::String))
+        );
+        assert_eq!(from_str::<f64>("NaN"), Err(Error::ExpectedSomeValue(b'N')));
+        assert_eq!(from_str::<f64>("-Infinity"), Err(Error::InvalidNumber));
+
+        let options = Options::default().allow_non_finite_floats(true);
+        let values: Vec<f64> =
+            from_str_with_options("[NaN, Infinity, -Infinity, 1]", &options).unwrap();
+        assert!(values[0].is_nan());
+        assert_eq!(
+            values[1..],
+            [std::f64::INFINITY, std::f64::NEG_INFINITY, 1.0]
+        );
+        assert_eq!(
+            from_str_with_options("-Infinity", &options),
+            Ok(std::f32::NEG_INFINITY)
+        );
+        assert_eq!(
+            from_str_with_options::<serde_json::Value>("[Infinity]", &options)
+                .map(|v| v[0].is_null()),
+            // serde_json represents non-finite numbers as null
+            Ok(true)
+        );
+        assert_eq!(
+            from_str_with_options::<f64>("Inf", &options),
+            Err(Error::ExpectedSomeIdent)
+        );
+        assert_eq!(
+            from_str_with_options::<u8>("NaN", &options),
+            Err(Error::ExpectedSomeValue(b'N'))
+        );
+    }
+
+    #[cfg(not(feature = "float"))]
+    #[test]
+    fn floats_not_supported() {
+        use super::Error;
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Price {
+            price: f64,
+        }
+
+        assert_eq!(from_str::<f32>("1.5"), Err(Error::FloatNotSupported));
+        assert_eq!(from_str::<f64>("1"), Err(Error::FloatNotSupported));
+        assert_eq!(
+            from_str::<Price>(r#"{"price": 1.5}"#),
+            Err(Error::FloatNotSupported)
+        );
+    }
+
+    #[test]
+    fn float_error_messages() {
+        use super::Error;
+        use serde::de::{self, Unexpected};
+
+        // the value is left out, which doesn't need float formatting
+        assert_eq!(
+            <Error as de::Error>::invalid_type(Unexpected::Float(1.5), &"a string"),
+            Error::Custom("invalid type: floating point, expected a string".to_string())
+        );
+        assert_eq!(
+            <Error as de::Error>::invalid_value(Unexpected::Unsigned(7), &"a digit"),
+            Error::Custom("invalid value: integer `7`, expected a digit".to_string())
+        );
+        assert_eq!(
+            <Error as de::Error>::invalid_type(Unexpected::Str("a\"b"), &"a number"),
+            Error::Custom(r#"invalid type: string "a\"b", expected a number"#.to_string())
+        );
+    }
+
+    #[test]
+    fn interned_strings() {
+        use super::from_slice_with_interner;
+        use std::cell::RefCell;
+        use std::collections::BTreeMap;
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Coin<'a> {
+            denom: &'a str,
+            amount: &'a str,
+        }
+
+        // a toy interner, which leaks its copies
+        fn intern<'a>(interned: &RefCell<Vec<&'a str>>, s: &str) -> &'a str {
+            let mut interned = interned.borrow_mut();
+            match interned.iter().find(|copy| **copy == s) {
+                Some(copy) => copy,
+                None => {
+                    let copy: &'a str = Box::leak(s.into());
+                    interned.push(copy);
+                    copy
+                }
+            }
+        }
+
+        let interned = RefCell::new(Vec::new());
+        let intern_str = |s: &str| intern(&interned, s);
+        let input =
+            br#"[{"denom": "uatom", "amount": "1"}, {"amount": "1", "denom": "uat\u006fm"}]"#;
+        let coins: Vec<Coin<'_>> = from_slice_with_interner(input, &intern_str).unwrap();
+        assert_eq!(coins[0], coins[1]);
+        assert_eq!(coins[0].denom.as_ptr(), coins[1].denom.as_ptr());
+        assert_eq!(coins[0].amount.as_ptr(), coins[1].amount.as_ptr());
+        // field names are interned too, even though the derived visitor doesn't keep them
+        assert_eq!(*interned.borrow(), ["denom", "uatom", "amount", "1"]);
+
+        let input = br#"{"k": "v"}"#;
+        let map: BTreeMap<&str, &str> = from_slice_with_interner(input, &intern_str).unwrap();
+        let (key, value) = map.iter().next().unwrap();
+        assert!(interned
+            .borrow()
+            .iter()
+            .any(|copy| copy.as_ptr() == key.as_ptr()));
+        assert!(interned
+            .borrow()
+            .iter()
+            .any(|copy| copy.as_ptr() == value.as_ptr()));
+    }
+
+    #[test]
+    fn escaped_strings_reuse_buffer() {
+        use super::{Deserializer, Options};
+        use serde::de::{self, Visitor};
+        use std::fmt;
+
+        /// Records how a string was visited, like a type that stores short strings inline
+        #[derive(Debug, PartialEq)]
+        enum Visited {
+            Borrowed(String),
+            Copied(String),
+            Moved(String),
+        }
+
+        impl<'de> de::Deserialize<'de> for Visited {
+            fn deserialize<D: de::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                struct VisitedVisitor;
+
+                impl<'de> Visitor<'de> for VisitedVisitor {
+                    type Value = Visited;
+
+                    fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+                        formatter.write_str("a string")
+                    }
+
+                    fn visit_borrowed_str<E>(self, v: &'de str) -> Result<Visited, E> {
+                        Ok(Visited::Borrowed(v.to_string()))
+                    }
+
+                    fn visit_str<E>(self, v: &str) -> Result<Visited, E> {
+                        Ok(Visited::Copied(v.to_string()))
+                    }
+
+                    fn visit_string<E>(self, v: String) -> Result<Visited, E> {
+                        Ok(Visited::Moved(v))
+                    }
+                }
+
+                deserializer.deserialize_str(VisitedVisitor)
+            }
+        }
+
+        let json = br#"["a\nb", "c", "\u00e9"]"#;
+
+        // by default escaped strings are handed over, so that a `String` doesn't need a copy
+        let mut de = Deserializer::new(json);
+        let visited: Vec<Visited> = de::Deserialize::deserialize(&mut de).unwrap();
+        assert_eq!(
+            visited,
+            [
+                Visited::Moved("a\nb".to_string()),
+                Visited::Borrowed("c".to_string()),
+                Visited::Moved("é".to_string()),
+            ]
+        );
+        assert_eq!(de.scratch.capacity(), 0);
+
+        let options = Options::default().reuse_string_buffer(true);
+        let mut de = Deserializer::with_options(json, &options);
+        let visited: Vec<Visited> = de::Deserialize::deserialize(&mut de).unwrap();
+        assert_eq!(
+            visited,
+            [
+                Visited::Copied("a\nb".to_string()),
+                Visited::Borrowed("c".to_string()),
+                Visited::Copied("é".to_string()),
+            ]
+        );
+        // the buffer of the first escaped string is kept for later ones
+        assert!(de.scratch.capacity() >= "a\nb".len());
+
+        assert_eq!(from_str::<String>(r#""a\tb""#), Ok("a\tb".to_string()));
+        assert_eq!(
+            super::from_str_with_options::<String>(r#""a\tb""#, &options),
+            Ok("a\tb".to_string())
+        );
+    }
+
+    #[cfg(feature = "smol_str")]
+    #[test]
+    fn smol_str_strings() {
+        use super::{Deserializer, Options};
+        use smol_str::SmolStr;
+
+        let json = br#"["uatom", "u\u0061tom", "a string that is too long to be stored inline"]"#;
+        let options = Options::default().reuse_string_buffer(true);
+        let mut de = Deserializer::with_options(json, &options);
+        let denoms: Vec<SmolStr> = serde::Deserialize::deserialize(&mut de).unwrap();
+        assert_eq!(
+            denoms,
+            [
+                "uatom",
+                "uatom",
+                "a string that is too long to be stored inline"
+            ]
+        );
+        assert!(!denoms[0].is_heap_allocated());
+        assert!(!denoms[1].is_heap_allocated());
+        // the escaped string was decoded into the buffer of the deserializer and copied inline
+        assert!(de.scratch.capacity() >= "uatom".len());
+
+        assert_eq!(
+            from_str::<SmolStr>(r#""\u00e9""#),
+            Ok(SmolStr::new_inline("é"))
+        );
+    }
+
+    #[cfg(feature = "compact_str")]
+    #[test]
+    fn compact_str_strings() {
+        use super::{Deserializer, Options};
+        use compact_str::CompactString;
+
+        let json = br#"["uatom", "u\u0061tom", "a string that is too long to be stored inline"]"#;
+        let options = Options::default().reuse_string_buffer(true);
+        let mut de = Deserializer::with_options(json, &options);
+        let denoms: Vec<CompactString> = serde::Deserialize::deserialize(&mut de).unwrap();
+        assert_eq!(
+            denoms,
+            [
+                "uatom",
+                "uatom",
+                "a string that is too long to be stored inline"
+            ]
+        );
+        assert!(!denoms[0].is_heap_allocated());
+        assert!(!denoms[1].is_heap_allocated());
+        // the escaped string was decoded into the buffer of the deserializer and copied inline
+        assert!(de.scratch.capacity() >= "uatom".len());
+
+        assert_eq!(
+            from_str::<CompactString>(r#""\u00e9""#),
+            Ok(CompactString::new("é"))
+        );
+    }
+
+    #[test]
+    fn invalid_length() {
+        use super::Error;
+
+        assert_eq!(
+            from_str::<(u8, u8, u8)>("[1, 2]"),
+            Err(Error::InvalidLength(2))
+        );
+        assert!(Error::InvalidLength(2).is_data());
+    }
+
+    #[cfg(feature = "heapless")]
+    #[test]
+    fn heapless_types() {
+        use super::Error;
+        use heapless::{String, Vec};
+
+        let denom: String<5> = from_str(r#""uatom""#).unwrap();
+        assert_eq!(denom, "uatom");
+        assert_eq!(from_str::<String<5>>(r#""u\u0061tom""#).unwrap(), "uatom");
+        assert_eq!(
+            from_str::<String<4>>(r#""uatom""#),
+            Err(Error::InvalidLength(5))
+        );
+        assert_eq!(
+            from_str::<String<5>>(r#""\u00e9\u00e9\u00e9""#),
+            Err(Error::InvalidLength(6))
+        );
+
+        let amounts: Vec<u8, 2> = from_str("[1, 2]").unwrap();
+        assert_eq!(amounts, [1, 2]);
+        // the capacity plus one
+        assert_eq!(
+            from_str::<Vec<u8, 2>>("[1, 2, 3, 4]"),
+            Err(Error::InvalidLength(3))
+        );
+        let denoms: Vec<String<2>, 2> = from_str(r#"["ab", "cd"]"#).unwrap();
+        assert_eq!(denoms, ["ab", "cd"]);
+        assert_eq!(
+            from_str::<Vec<Vec<u8, 2>, 2>>("[[1], [1, 2, 3]]"),
+            Err(Error::InvalidLength(3))
+        );
+        assert_eq!(
+            from_str::<Vec<(u8, u8), 2>>("[[1, 2], [3]]"),
+            Err(Error::InvalidLength(1))
+        );
+    }
+
+    #[cfg(feature = "arrayvec")]
+    #[test]
+    fn arrayvec_types() {
+        use super::Error;
+        use arrayvec::{ArrayString, ArrayVec};
+
+        let denom: ArrayString<5> = from_str(r#""u\u0061tom""#).unwrap();
+        assert_eq!(denom.as_str(), "uatom");
+        // the length of the string
+        assert_eq!(
+            from_str::<ArrayString<4>>(r#""uatom""#),
+            Err(Error::InvalidLength(5))
+        );
+
+        let amounts: ArrayVec<u8, 2> = from_str("[1, 2]").unwrap();
+        assert_eq!(amounts.as_slice(), [1, 2]);
+        // the capacity plus one
+        assert_eq!(
+            from_str::<ArrayVec<u8, 2>>("[1, 2, 3, 4]"),
+            Err(Error::InvalidLength(3))
+        );
+        assert_eq!(
+            from_str::<ArrayVec<ArrayString<2>, 2>>(r#"["ab", "cde"]"#),
+            Err(Error::InvalidLength(3))
+        );
+    }
+
+    #[cfg(feature = "float")]
+    #[test]
+    fn quoted_floats() {
+        use super::{from_str_with_options, Error, JsonType, Options};
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Price {
+            price: f64,
+            volume: f32,
+        }
+
+        let options = Options::default().allow_quoted_floats(true);
+        assert_eq!(
+            from_str_with_options(r#"{"price": "0.25", "volume": 1e3}"#, &options),
+            Ok(Price {
+                price: 0.25,
+                volume: 1000.0
+            })
+        );
+        assert_eq!(from_str_with_options(r#""-3""#, &options), Ok(-3f64));
+        for input in &[r#""""#, r#"" 1.5""#, r#""1.5x""#, r#""NaN""#] {
+            assert_eq!(
+                from_str_with_options::<f64>(input, &options),
+                Err(Error::InvalidNumber),
+                "{}",
+                input
+            );
+        }
+        assert_eq!(
+            from_str_with_options::<f64>(r#""1e999""#, &options),
+            Err(Error::NumberOutOfRange)
+        );
+        let options = options.allow_non_finite_floats(true);
+        assert!(from_str_with_options::<f64>(r#""NaN""#, &options)
+            .unwrap()
+            .is_nan());
+
+        // integers are not affected
+        assert_eq!(
+            from_str_with_options::<u8>(r#""1""#, &options),
+            Err(Error::InvalidType(JsonType::String))
+        );
+        assert_eq!(
+            from_str::<f64>(r#""0.25""#),
+            Err(Error::InvalidType(JsonType::String))
+        );
+    }
+
+    #[test]
+    fn control_characters() {
+        use super::{from_slice_mut, from_str_with_options, Error, Options};
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Memo<'a> {
+            memo: &'a str,
+        }
+
+        assert_eq!(
+            from_str::<&str>("\"a\tb\""),
+            Err(Error::ControlCharacterInString)
+        );
+        assert_eq!(
+            from_str::<String>("\"a\\n\u{1}\""),
+            Err(Error::ControlCharacterInString)
+        );
+        assert_eq!(
+            from_str::<Memo<'_>>("{\"memo\": \"\", \"skipped\": \"\u{1f}\"}"),
+            Err(Error::ControlCharacterInString)
+        );
+        assert_eq!(
+            from_slice_mut::<String>(&mut b"\"\\n\x00\"".to_vec()),
+            Err(Error::ControlCharacterInString)
+        );
+        assert_eq!(from_str("\"a\\tb\\u0000\""), Ok("a\tb\u{0}".to_string()));
+        assert_eq!(from_str("\"\u{7f}\""), Ok("\u{7f}"));
+
+        let options = Options::default().allow_control_characters(true);
+        assert_eq!(from_str_with_options("\"a\tb\n\"", &options), Ok("a\tb\n"));
+        assert_eq!(
+            from_str_with_options("\"\u{0}\\\"\"", &options),
+            Ok("\u{0}\"".to_string())
+        );
+        assert_eq!(
+            from_str_with_options::<Memo<'_>>(
+                "{\"memo\": \"\r\n\", \"skipped\": \"\u{1}\"}",
+                &options
+            ),
+            Ok(Memo { memo: "\r\n" })
+        );
+    }
+
+    #[test]
+    fn lone_surrogates() {
+        use super::{from_str_with_options, Error, LoneSurrogates, Options};
+
+        let input = r#"["\ud83d\udc4f", "\ud83d!"]"#;
+        assert_eq!(
+            from_str::<Vec<String>>(input),
+            Err(Error::LoneSurrogateFound)
+        );
+        let options = Options::default().lone_surrogates(LoneSurrogates::Replace);
+        assert_eq!(
+            from_str_with_options(input, &options),
+            Ok(vec!["\u{1F44F}".to_string(), "\u{FFFD}!".to_string()])
+        );
+        assert_eq!(Options::default().lone_surrogates, LoneSurrogates::Reject);
+    }
+
+    #[test]
+    fn duplicate_keys() {
+        use super::{from_str_with_options, DuplicateKeys, Error, Options};
+        use std::collections::BTreeMap;
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Transfer<'a> {
+            recipient: &'a str,
+        }
+
+        let input = r#"{"recipient": "alice", "amount": 1, "recipient": "mallory"}"#;
+        let map = r#"{"a": 1, "b": {"c": 2, "c": 3}, "\u0061": 4}"#;
+
+        // by default serde decides
+        assert!(from_str::<Transfer<'_>>(input).is_err());
+        assert_eq!(
+            from_str::<BTreeMap<String, serde_json::Value>>(map).unwrap()["a"],
+            4
+        );
+
+        let options = Options::default().duplicate_keys(DuplicateKeys::FirstWins);
+        assert_eq!(
+            from_str_with_options(input, &options),
+            Ok(Transfer { recipient: "alice" })
+        );
+        assert_eq!(
+            from_str_with_options::<serde_json::Value>(map, &options),
+            Ok(serde_json::json!({"a": 1, "b": {"c": 2}}))
+        );
+
+        let options = Options::default().duplicate_keys(DuplicateKeys::Reject);
+        assert_eq!(
+            from_str_with_options::<Transfer<'_>>(input, &options),
+            Err(Error::DuplicateKey)
+        );
+        assert_eq!(
+            from_str_with_options::<serde_json::Value>(map, &options),
+            Err(Error::DuplicateKey)
+        );
+        assert_eq!(
+            from_str_with_options::<BTreeMap<u8, u8>>(r#"{"1": 1, "2": 2}"#, &options),
+            Ok(vec![(1, 1), (2, 2)].into_iter().collect())
+        );
+        assert_eq!(
+            from_str_with_options::<BTreeMap<&str, u8>>(r#"{"a": 1 "a": 2}"#, &options),
+            Err(Error::ExpectedObjectCommaOrEnd)
+        );
+        assert!(Error::DuplicateKey.is_data());
+    }
+
+    #[test]
+    fn deny_unknown_fields() {
+        use super::{from_str_with_options, Error, Options};
+        use std::collections::BTreeMap;
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Coin {
+            denom: String,
+            amount: u64,
+        }
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        enum Msg {
+            Send { to: String, coins: Vec<Coin> },
+        }
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        #[serde(deny_unknown_fields)]
+        struct Strict {
+            a: u8,
+        }
+
+        let options = Options::default().deny_unknown_fields(true);
+        let msg = r#"{"Send": {"to": "bob", "coins": [{"denom": "uatom", "amount": 1}]}}"#;
+        assert_eq!(
+            from_str_with_options(msg, &options),
+            Ok(Msg::Send {
+                to: "bob".to_string(),
+                coins: vec![Coin {
+                    denom: "uatom".to_string(),
+                    amount: 1
+                }]
+            })
+        );
+        assert_eq!(
+            from_str_with_options::<Msg>(
+                r#"{"Send": {"to": "bob", "coins": [{"denom": "uatom", "amount": 1, "memo": ""}]}}"#,
+                &options
+            ),
+            Err(Error::UnknownField("memo".to_string()))
+        );
+        assert_eq!(
+            from_str_with_options::<Coin>(r#"{"amount": 1, "\u0064enom": "x"}"#, &options),
+            Ok(Coin {
+                denom: "x".to_string(),
+                amount: 1
+            })
+        );
+        assert_eq!(
+            from_str_with_options::<BTreeMap<String, u8>>(r#"{"x": 1}"#, &options),
+            Ok(vec![("x".to_string(), 1)].into_iter().collect())
+        );
+
+        // unknown fields are skipped by default
+        assert_eq!(
+            from_str::<Coin>(r#"{"denom": "x", "amount": 1, "memo": ""}"#),
+            Ok(Coin {
+                denom: "x".to_string(),
+                amount: 1
+            })
+        );
+        // serde's own check reports the same error
+        assert_eq!(
+            from_str::<Strict>(r#"{"a": 1, "b": 2}"#),
+            Err(Error::UnknownField("b".to_string()))
+        );
+    }
+
+    #[test]
+    fn deny_floats() {
+        use super::{from_str_with_options, validate_with_options, Error, Options};
+        use crate::{Number, RawValue};
 
-        assert_eq!(from_str("[4,5]"), Ok([4, 5]));
-        assert_eq!(from_str(" [4,5]"), Ok([4, 5]));
-        assert_eq!(from_str("\t[4,5]"), Ok([4, 5]));
-        assert_eq!(from_str("\n[4,5]"), Ok([4, 5]));
-        assert_eq!(from_str("\r[4,5]"), Ok([4, 5]));
-        assert_eq!(from_str("\n\r[4,5]"), Ok([4, 5]));
-        assert_eq!(from_str("\r\n[4,5]"), Ok([4, 5]));
-        assert_eq!(from_str("[ 4,5]"), Ok([4, 5]));
-        assert_eq!(from_str("[\t4,5]"), Ok([4, 5]));
-        assert_eq!(from_str("[\n4,5]"), Ok([4, 5]));
-        assert_eq!(from_str("[\r4,5]"), Ok([4, 5]));
-        assert_eq!(from_str("[\n\r4,5]"), Ok([4, 5]));
-        assert_eq!(from_str("[\r\n4,5]"), Ok([4, 5]));
-        assert_eq!(from_str("[4 ,5]"), Ok([4, 5]));
-        assert_eq!(from_str("[4\t,5]"), Ok([4, 5]));
-        assert_eq!(from_str("[4\n,5]"), Ok([4, 5]));
-        assert_eq!(from_str("[4\r,5]"), Ok([4, 5]));
-        assert_eq!(from_str("[4\n\r,5]"), Ok([4, 5]));
-        assert_eq!(from_str("[4\r\n,5]"), Ok([4, 5]));
-        assert_eq!(from_str("[4, 5]"), Ok([4, 5]));
-        assert_eq!(from_str("[4,\t5]"), Ok([4, 5]));
-        assert_eq!(from_str("[4,\n5]"), Ok([4, 5]));
-        assert_eq!(from_str("[4,\r5]"), Ok([4, 5]));
-        assert_eq!(from_str("[4,\n\r5]"), Ok([4, 5]));
-        assert_eq!(from_str("[4,\r\n5]"), Ok([4, 5]));
-        assert_eq!(from_str("[4,5 ]"), Ok([4, 5]));
-        assert_eq!(from_str("[4,5\t]"), Ok([4, 5]));
-        assert_eq!(from_str("[4,5\n]"), Ok([4, 5]));
-        assert_eq!(from_str("[4,5\r]"), Ok([4, 5]));
-        assert_eq!(from_str("[4,5\n\r]"), Ok([4, 5]));
-        assert_eq!(from_str("[4,5\r\n]"), Ok([4, 5]));
-        assert_eq!(from_str("[4,5] "), Ok([4, 5]));
-        assert_eq!(from_str("[4,5]\t"), Ok([4, 5]));
-        assert_eq!(from_str("[4,5]\n"), Ok([4, 5]));
-        assert_eq!(from_str("[4,5]\r"), Ok([4, 5]));
-        assert_eq!(from_str("[4,5]\n\r"), Ok([4, 5]));
-        assert_eq!(from_str("[4,5]\r\n"), Ok([4, 5]));
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Amount {
+            amount: u64,
+        }
+
+        let options = Options::default().deny_floats(true);
+        assert_eq!(
+            from_str_with_options(r#"{"amount": 10, "other": [-1, 0]}"#, &options),
+            Ok(Amount { amount: 10 })
+        );
+        for input in &["1.5", "1e3", "-0.0", "2E-1"] {
+            assert_eq!(
+                from_str_with_options::<i64>(input, &options),
+                Err(Error::FloatNotAllowed),
+                "{}",
+                input
+            );
+            assert_eq!(
+                from_str_with_options::<serde_json::Value>(input, &options),
+                Err(Error::FloatNotAllowed),
+                "{}",
+                input
+            );
+            assert_eq!(
+                from_str_with_options::<Number>(input, &options),
+                Err(Error::FloatNotAllowed),
+                "{}",
+                input
+            );
+            assert_eq!(
+                from_str_with_options::<&RawValue>(input, &options),
+                Err(Error::FloatNotAllowed),
+                "{}",
+                input
+            );
+            let skipped = format!(r#"{{"amount": 1, "skipped": [{}]}}"#, input);
+            assert_eq!(
+                from_str_with_options::<Amount>(&skipped, &options),
+                Err(Error::FloatNotAllowed),
+                "{}",
+                input
+            );
+            assert_eq!(
+                validate_with_options(skipped.as_bytes(), &options),
+                Err(Error::FloatNotAllowed),
+                "{}",
+                input
+            );
+        }
+        #[cfg(feature = "float")]
+        {
+            assert_eq!(from_str_with_options("2", &options), Ok(2f64));
+            assert_eq!(
+                from_str_with_options::<f64>("2.5", &options),
+                Err(Error::FloatNotAllowed)
+            );
+        }
+
+        // allowed by default
+        assert_eq!(
+            from_str::<Number>("1.5").map(|n| n.to_string()),
+            Ok("1.5".to_string())
+        );
     }
 
     #[test]
-    fn array() {
-        assert_eq!(from_str::<[i32; 0]>("[]"), Ok([]));
-        assert_eq!(from_str("[0, 1, 2]"), Ok([0, 1, 2]));
+    fn strict_numbers() {
+        use super::{from_str_with_options, Error, Options};
+        use serde_json::Value;
 
-        // errors
-        assert!(from_str::<[i32; 2]>("[0, 1,]").is_err());
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Amount {
+            amount: u64,
+        }
+
+        let options = Options::default().strict_numbers(true);
+        assert_eq!(from_str_with_options("0", &options), Ok(0u8));
+        assert_eq!(from_str_with_options("-0", &options), Ok(0i8));
+        assert_eq!(
+            from_str_with_options("[10,-20]", &options),
+            Ok((10u8, -20i8))
+        );
+        assert_eq!(
+            from_str_with_options(
+                r#"{"amount": 10, "price": -1.5e-3, "n": [0, 0.0, 1E+2]}"#,
+                &options
+            ),
+            Ok(Amount { amount: 10 })
+        );
+        assert_eq!(
+            from_str_with_options::<Value>("[-1, 0, 1]", &options),
+            Ok(serde_json::json!([-1, 0, 1]))
+        );
+
+        for input in &["01", "-01", "1.", ".5", "+1", "-", "00", "1e"] {
+            assert_eq!(
+                from_str_with_options::<i32>(input, &options),
+                Err(Error::InvalidNumber),
+                "{}",
+                input
+            );
+            assert_eq!(
+                from_str_with_options::<Value>(input, &options),
+                Err(Error::InvalidNumber),
+                "{}",
+                input
+            );
+            let skipped = format!(r#"{{"amount": 1, "skipped": {}}}"#, input);
+            assert_eq!(
+                from_str_with_options::<Amount>(&skipped, &options),
+                Err(Error::InvalidNumber),
+                "{}",
+                input
+            );
+        }
+        assert_eq!(
+            from_str_with_options::<u32>("1e5", &options),
+            Err(Error::InvalidNumber)
+        );
+        assert_eq!(
+            from_str_with_options::<Amount>(r#"{"amount": 1, "skipped": 1x}"#, &options),
+            Err(Error::ExpectedObjectCommaOrEnd)
+        );
+        assert_eq!(
+            from_str_with_options::<Amount>(r#"{"amount": 1, "skipped": [1.e1]}"#, &options),
+            Err(Error::InvalidNumber)
+        );
+
+        // skipped numbers are not validated by default
+        assert_eq!(
+            from_str(r#"{"amount": 1, "skipped": 01.}"#),
+            Ok(Amount { amount: 1 })
+        );
+        assert_eq!(from_str::<i32>("01"), Err(Error::TrailingCharacters));
     }
 
     #[test]
-    fn tuple() {
-        type Pair = (i64, i64);
-        type Wrapped = (i64,); // Comma differentiates one element tuple from a primary type surrounded by parentheses
-        type Unit = ();
+    fn quoted_integers() {
+        use super::{from_str_with_options, Error, JsonType, Options};
 
-        let pair: Pair = (1, 2);
-        assert_eq!(from_str("[1,2]"), Ok(pair));
-        assert_eq!(serde_json::from_str::<Pair>("[1,2]").unwrap(), pair);
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Coin {
+            amount: u128,
+            fee: u64,
+            offset: i32,
+        }
 
-        let wrapped: Wrapped = (5,);
-        assert_eq!(from_str("[5]"), Ok(wrapped));
-        assert_eq!(serde_json::from_str::<Wrapped>("[5]").unwrap(), wrapped);
+        let options = Options::default().allow_quoted_integers(true);
+        assert_eq!(
+            from_str_with_options(
+                r#"{"amount": "340282366920938463463374607431768211455", "fee": 5, "offset": "-7"}"#,
+                &options
+            ),
+            Ok(Coin {
+                amount: u128::max_value(),
+                fee: 5,
+                offset: -7
+            })
+        );
+        assert_eq!(from_str_with_options(r#""0""#, &options), Ok(0u8));
+        assert_eq!(from_str_with_options(r#""\u0031""#, &options), Ok(1u8));
+        for input in &[r#""""#, r#"" 1""#, r#""1 ""#, r#""1x""#, r#""a""#] {
+            assert_eq!(
+                from_str_with_options::<u8>(input, &options),
+                Err(Error::InvalidNumber),
+                "{}",
+                input
+            );
+        }
+        for input in &[r#""256""#, r#""-1""#] {
+            assert_eq!(
+                from_str_with_options::<u8>(input, &options),
+                Err(Error::NumberOutOfRange),
+                "{}",
+                input
+            );
+        }
+        assert_eq!(
+            from_str_with_options::<u8>(r#""1"#, &options),
+            Err(Error::EofWhileParsingString)
+        );
 
-        let unit: Unit = ();
-        assert_eq!(from_str("null"), Ok(unit));
-        assert_eq!(serde_json::from_str::<()>("null").unwrap(), unit);
+        // quoted integers are strings by default
+        assert_eq!(
+            from_str::<u8>(r#""1""#),
+            Err(Error::InvalidType(JsonType::String))
+        );
     }
 
+    #[cfg(feature = "base64")]
     #[test]
-    fn tuple_variant() {
+    fn base64_bytes() {
+        use super::{Error, JsonType};
+        use serde_bytes::ByteBuf;
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Execute {
+            msg: ByteBuf,
+        }
+
+        assert_eq!(
+            from_str(r#"{"msg": "eyJwaW5nIjp7fX0="}"#),
+            Ok(Execute {
+                msg: ByteBuf::from(br#"{"ping":{}}"#.to_vec())
+            })
+        );
+        assert_eq!(from_str(r#""""#), Ok(ByteBuf::from(vec![])));
+        assert_eq!(from_str(r#""\/w==""#), Ok(ByteBuf::from(vec![0xff])));
+        assert_eq!(from_str::<ByteBuf>(r#""/w=""#), Err(Error::InvalidBytes));
+        assert_eq!(
+            from_str::<ByteBuf>("1"),
+            Err(Error::InvalidType(JsonType::Number))
+        );
+    }
+
+    #[test]
+    fn hex_bytes() {
+        use super::{decode_hex, from_str_with_options, Error, Options};
+        use serde_bytes::ByteBuf;
+
+        let options = Options::default().hex_bytes(true);
+        assert_eq!(
+            from_str_with_options(r#""00ff7A""#, &options),
+            Ok(ByteBuf::from(vec![0x00, 0xff, 0x7a]))
+        );
+        assert_eq!(
+            from_str_with_options(r#""0xdeadBEEF""#, &options),
+            Ok(ByteBuf::from(vec![0xde, 0xad, 0xbe, 0xef]))
+        );
+        assert_eq!(
+            from_str_with_options(r#""""#, &options),
+            Ok(ByteBuf::from(vec![]))
+        );
+        for input in &[r#""abc""#, r#""0x0g""#, r#""+1""#, r#""0X00""#] {
+            assert_eq!(
+                from_str_with_options::<ByteBuf>(input, &options),
+                Err(Error::InvalidBytes),
+                "{}",
+                input
+            );
+        }
+        #[cfg(not(feature = "base64"))]
+        assert_eq!(
+            from_str::<ByteBuf>(r#""00ff""#),
+            Err(Error::InvalidType(super::JsonType::String))
+        );
+        assert_eq!(decode_hex(b"0x"), Some(vec![]));
+    }
+
+    #[test]
+    fn bytes_from_arrays() {
+        use super::{Error, JsonType};
+        use serde_bytes::ByteBuf;
+
+        // as written by serde_json
+        assert_eq!(from_str("[1,2,255]"), Ok(ByteBuf::from(vec![1, 2, 255])));
+        assert_eq!(from_str(" [ ]"), Ok(ByteBuf::from(vec![])));
+        assert_eq!(from_str::<ByteBuf>("[256]"), Err(Error::NumberOutOfRange));
+        assert_eq!(
+            from_str::<ByteBuf>(r#"[1, "2"]"#),
+            Err(Error::InvalidType(JsonType::String))
+        );
+        assert_eq!(
+            from_str::<ByteBuf>("[1, 2"),
+            Err(Error::EofWhileParsingList)
+        );
+        assert_eq!(
+            from_str::<ByteBuf>("null"),
+            Err(Error::InvalidType(JsonType::Null))
+        );
+    }
+
+    #[test]
+    fn invalid_utf8_offset() {
+        use super::{from_slice, from_slice_mut, Error};
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Frame {
+            id: String,
+            body: String,
+        }
+
+        assert_eq!(
+            from_slice::<Frame>(b"{\"id\": \"a\", \"body\": \"ok \xC3(\"}"),
+            Err(Error::InvalidUtf8(24))
+        );
+        assert_eq!(
+            from_slice::<String>(b"\"\\n\xE2\x82\""),
+            Err(Error::InvalidUtf8(3))
+        );
+        assert_eq!(
+            from_slice::<Vec<String>>(b"[\"\", \"\xFF\"]"),
+            Err(Error::InvalidUtf8(6))
+        );
+        assert_eq!(
+            from_slice_mut::<String>(&mut b"\"\\t\xFF\"".to_vec()),
+            Err(Error::InvalidUtf8(3))
+        );
+    }
+
+    #[test]
+    fn replace_invalid_utf8() {
+        use super::{from_slice, from_slice_with_options, Error, Options};
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Log<'a> {
+            message: String,
+            #[serde(borrow)]
+            source: std::borrow::Cow<'a, str>,
+        }
+
+        let options = Options::default().replace_invalid_utf8(true);
+        assert_eq!(
+            from_slice_with_options(
+                b"{\"message\": \"disk \xFF full\", \"source\": \"\xC3(\"}",
+                &options
+            ),
+            Ok(Log {
+                message: "disk \u{FFFD} full".to_string(),
+                source: "\u{FFFD}(".into(),
+            })
+        );
+        assert_eq!(
+            from_slice_with_options(b"\"\\t\xE2\x82\"", &options),
+            Ok("\t\u{FFFD}".to_string())
+        );
+        // replaced strings cannot be borrowed
+        assert!(from_slice_with_options::<&str>(b"\"\xFF\"", &options).is_err());
+        assert_eq!(
+            from_slice::<String>(b"\"\xFF\""),
+            Err(Error::InvalidUtf8(1))
+        );
+    }
+
+    #[test]
+    fn byte_order_mark() {
+        use super::{from_slice_with_options, from_str_with_options, Error, Options};
+
+        let options = Options::default().allow_byte_order_mark(true);
+        assert_eq!(
+            from_str_with_options("\u{feff}[1, 2]", &options),
+            Ok([1u8, 2])
+        );
+        assert_eq!(
+            from_slice_with_options(b"\xEF\xBB\xBF 7", &options),
+            Ok(7u8)
+        );
+        assert_eq!(from_str_with_options("[1, 2]", &options), Ok([1u8, 2]));
+        // only at the start
+        assert_eq!(
+            from_str_with_options::<Vec<u8>>("[\u{feff}1]", &options),
+            Err(Error::ExpectedSomeValue(0xEF))
+        );
+        assert_eq!(
+            from_str::<Vec<u8>>("\u{feff}[1, 2]"),
+            Err(Error::ExpectedSomeValue(0xEF))
+        );
+    }
+
+    #[test]
+    fn byte_order_mark_in_chunks() {
+        use super::{from_read_fn_with_options, Error, Feed, Options, PushDeserializer};
+
+        let options = Options::default().allow_byte_order_mark(true);
+        let mut de = PushDeserializer::<Vec<u8>>::with_options(&options);
+        assert_eq!(de.push(b"\xEF\xBB"), Ok(Feed::NeedMoreData));
+        assert_eq!(de.push(b"\xBF[1,2]"), Ok(Feed::Value(vec![1, 2])));
+        // only at the start of the input, not of every value
+        assert_eq!(
+            de.push(b" \xEF\xBB\xBF[3]"),
+            Err(Error::ExpectedSomeValue(0xEF))
+        );
+
+        let mut de = PushDeserializer::<u8>::with_options(&options);
+        assert_eq!(de.push(b"\xEF\xBB\xBF7 "), Ok(Feed::Value(7)));
+
+        let mut input: &[u8] = b"\xEF\xBB\xBF[1,2]";
+        assert_eq!(
+            from_read_fn_with_options(|buf| std::io::Read::read(&mut input, buf), &options),
+            Ok(vec![1u8, 2])
+        );
+    }
+
+    #[test]
+    fn length_limits() {
+        use super::{from_str_with_options, Error, Options};
+        use std::collections::BTreeMap;
+
+        let options = Options::default().max_input_len(8);
+        assert_eq!(from_str_with_options("[1, 2]", &options), Ok([1u8, 2]));
+        assert_eq!(
+            from_str_with_options::<Vec<u8>>("[1, 2, 3]", &options),
+            Err(Error::InputTooLong)
+        );
+
+        // the limit applies to the decoded string
+        let options = Options::default().max_string_len(5);
+        assert_eq!(
+            from_str_with_options(r#""uatom""#, &options),
+            Ok("uatom".to_string())
+        );
+        assert_eq!(
+            from_str_with_options(r#""u\u0061tom""#, &options),
+            Ok("uatom".to_string())
+        );
+        assert_eq!(
+            from_str_with_options::<String>(r#""ujuno1""#, &options),
+            Err(Error::StringTooLong)
+        );
+        assert_eq!(
+            from_str_with_options::<String>(r#""u\u0061tom\n""#, &options),
+            Err(Error::StringTooLong)
+        );
+        assert_eq!(
+            from_str_with_options::<BTreeMap<String, u8>>(r#"{"denoms": 1}"#, &options),
+            Err(Error::StringTooLong)
+        );
+        assert_eq!(Error::StringTooLong.classify(), crate::Category::Data);
+    }
+
+    #[test]
+    fn count_limits() {
+        use super::{from_str_with_options, Error, Options};
+        use serde_json::Value;
+        use std::collections::BTreeMap;
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Temperature {
+            temperature: u8,
+        }
+
+        let options = Options::default().max_array_len(2).max_object_len(2);
+        assert_eq!(from_str_with_options("[1, 2]", &options), Ok(vec![1u8, 2]));
+        assert_eq!(
+            from_str_with_options::<Vec<u8>>("[1, 2, 3]", &options),
+            Err(Error::ArrayTooLong)
+        );
+        assert_eq!(
+            from_str_with_options::<Value>(r#"[[], [1, 2, 3]]"#, &options),
+            Err(Error::ArrayTooLong)
+        );
+        assert_eq!(
+            from_str_with_options::<BTreeMap<String, u8>>(r#"{"a": 1, "b": 2}"#, &options)
+                .map(|map| map.len()),
+            Ok(2)
+        );
+        assert_eq!(
+            from_str_with_options::<BTreeMap<String, u8>>(r#"{"a": 1, "b": 2, "c": 3}"#, &options),
+            Err(Error::ObjectTooLong)
+        );
+        // duplicate keys count as members
+        assert_eq!(
+            from_str_with_options::<BTreeMap<String, u8>>(r#"{"a": 1, "a": 2, "a": 3}"#, &options),
+            Err(Error::ObjectTooLong)
+        );
+        assert_eq!(
+            from_str_with_options::<Temperature>(
+                r#"{"temperature": 20, "ignored": {}, "other": 1}"#,
+                &options
+            ),
+            Err(Error::ObjectTooLong)
+        );
+        assert_eq!(Error::ArrayTooLong.classify(), crate::Category::Data);
+
+        // skipped values are limited as well
+        let with_ignored = |ignored: &str| {
+            from_str_with_options::<Temperature>(
+                &format!(r#"{{"temperature": 20, "ignored": {}}}"#, ignored),
+                &options,
+            )
+        };
+        assert_eq!(
+            with_ignored(r#"[[1, 2], {"a": [], "b": {}}]"#),
+            Ok(Temperature { temperature: 20 })
+        );
+        assert_eq!(with_ignored("[[1, 2, 3]]"), Err(Error::ArrayTooLong));
+        assert_eq!(with_ignored("[1, 2, []]"), Err(Error::ArrayTooLong));
+        assert_eq!(
+            with_ignored(r#"[{"a": 1, "b": 2, "c": 3}]"#),
+            Err(Error::ObjectTooLong)
+        );
+        let options = options.allow_trailing_commas(true);
+        assert_eq!(
+            from_str_with_options::<Temperature>(
+                r#"{"temperature": 20, "ignored": [1, 2,],}"#,
+                &options
+            ),
+            Ok(Temperature { temperature: 20 })
+        );
+    }
+
+    #[test]
+    fn unquoted_keys() {
+        use super::{from_str_with_options, Error, Options};
+        use std::collections::BTreeMap;
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        enum Msg {
+            Transfer { amount: String },
+        }
+
         #[derive(Debug, Deserialize, PartialEq)]
-        enum Ops {
-            Exit(),
-            Square(i32),
-            Add(i64, i64),
+        struct Coin<'a> {
+            denom: &'a str,
+            amount: &'a str,
         }
-        assert_eq!(from_str(r#"{"Exit":[]}"#), Ok(Ops::Exit()));
+
+        let options = Options::default().allow_unquoted_keys(true);
         assert_eq!(
-            serde_json::from_str::<Ops>(r#"{"Exit":[]}"#).unwrap(),
-            Ops::Exit()
+            from_str_with_options(
+                r#"{denom: "uatom", "amount": "1", _skip$1: {a: [1]}}"#,
+                &options
+            ),
+            Ok(Coin {
+                denom: "uatom",
+                amount: "1"
+            })
         );
-        assert_eq!(from_str(r#"{"Square":1}"#), Ok(Ops::Square(1)));
         assert_eq!(
-            serde_json::from_str::<Ops>(r#"{"Square":1}"#).unwrap(),
-            Ops::Square(1)
+            from_str_with_options(r#"{Transfer: {amount: "1"}}"#, &options),
+            Ok(Msg::Transfer {
+                amount: "1".to_string()
+            })
         );
-        assert_eq!(from_str(r#"{"Add":[2,3]}"#), Ok(Ops::Add(2, 3)));
+        let map: BTreeMap<&str, bool> =
+            from_str_with_options("{ true :true, $: false }", &options).unwrap();
         assert_eq!(
-            serde_json::from_str::<Ops>(r#"{"Add":[2,3]}"#).unwrap(),
-            Ops::Add(2, 3)
+            map.into_iter().collect::<Vec<_>>(),
+            vec![("$", false), ("true", true)]
         );
-    }
-
-    #[test]
-    fn bool() {
-        assert_eq!(from_str("true"), Ok(true));
-        assert_eq!(from_str(" true"), Ok(true));
-        assert_eq!(from_str("true "), Ok(true));
 
-        assert_eq!(from_str("false"), Ok(false));
-        assert_eq!(from_str(" false"), Ok(false));
-        assert_eq!(from_str("false "), Ok(false));
+        assert_eq!(
+            from_str_with_options::<BTreeMap<&str, u8>>("{1a: 1}", &options),
+            Err(Error::KeyMustBeAString)
+        );
+        assert_eq!(
+            from_str_with_options::<BTreeMap<&str, u8>>("{a-b: 1}", &options),
+            Err(Error::ExpectedColon)
+        );
+        assert_eq!(
+            from_str_with_options::<BTreeMap<&str, u8>>("{a: b}", &options),
+            Err(Error::ExpectedSomeValue(b'b'))
+        );
 
-        // errors
-        assert!(from_str::<bool>("true false").is_err());
-        assert!(from_str::<bool>("tru").is_err());
+        // rejected by default
+        assert_eq!(
+            from_str::<BTreeMap<&str, u8>>("{a: 1}"),
+            Err(Error::KeyMustBeAString)
+        );
     }
 
     #[test]
-    fn enum_clike() {
-        assert_eq!(from_str(r#" "boolean" "#), Ok(Type::Boolean));
-        assert_eq!(from_str(r#" "number" "#), Ok(Type::Number));
-        assert_eq!(from_str(r#" "thing" "#), Ok(Type::Thing));
-    }
+    fn single_quotes() {
+        use super::{from_str_with_options, Error, Options};
+        use std::collections::BTreeMap;
 
-    #[test]
-    fn string() {
-        assert_eq!(from_str(r#" "hello" "#), Ok("hello".to_string()));
-        assert_eq!(from_str(r#" "" "#), Ok("".to_string()));
-        assert_eq!(from_str(r#" " " "#), Ok(" ".to_string()));
-        assert_eq!(from_str(r#" "👏" "#), Ok("👏".to_string()));
+        #[derive(Debug, Deserialize, PartialEq)]
+        enum Kind {
+            Native,
+        }
 
-        // Unescapes things
-        assert_eq!(from_str(r#" "hel\tlo" "#), Ok("hel\tlo".to_string()));
-        assert_eq!(from_str(r#" "hel\\lo" "#), Ok("hel\\lo".to_string()));
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Device {
+            name: String,
+            kind: Kind,
+        }
 
-        // escaped " in the string content
-        assert_eq!(from_str(r#" "foo\"bar" "#), Ok(r#"foo"bar"#.to_string()));
-        assert_eq!(from_str(r#" "foo\\\"ba" "#), Ok(r#"foo\"ba"#.to_string()));
-        assert_eq!(from_str(r#" "foo\"\"ba" "#), Ok(r#"foo""ba"#.to_string()));
-        assert_eq!(from_str(r#" "\"bar" "#), Ok(r#""bar"#.to_string()));
-        assert_eq!(from_str(r#" "foo\"" "#), Ok(r#"foo""#.to_string()));
-        assert_eq!(from_str(r#" "\"" "#), Ok(r#"""#.to_string()));
+        let options = Options::default().allow_single_quotes(true);
+        assert_eq!(
+            from_str_with_options(
+                r#"{'name': 'it\'s "a\u0021"', 'kind': 'Native', 'x': ['\'']}"#,
+                &options
+            ),
+            Ok(Device {
+                name: "it's \"a!\"".to_string(),
+                kind: Kind::Native,
+            })
+        );
+        assert_eq!(
+            from_str_with_options(r#"'"'"#, &options),
+            Ok("\"".to_string())
+        );
+        assert_eq!(
+            from_str_with_options(r#""'""#, &options),
+            Ok("'".to_string())
+        );
+        let map: BTreeMap<u8, &str> = from_str_with_options("{'1': ''}", &options).unwrap();
+        assert_eq!(map.into_iter().collect::<Vec<_>>(), vec![(1, "")]);
 
-        // non-escaped " preceded by backslashes
-        assert_eq!(from_str(r#" "fooooo\\" "#), Ok(r#"fooooo\"#.to_string()));
-        assert_eq!(from_str(r#" "fooo\\\\" "#), Ok(r#"fooo\\"#.to_string()));
-        assert_eq!(from_str(r#" "fo\\\\\\" "#), Ok(r#"fo\\\"#.to_string()));
-        assert_eq!(from_str(r#" "\\\\\\\\" "#), Ok(r#"\\\\"#.to_string()));
+        assert_eq!(
+            from_str_with_options::<String>(r#"'a""#, &options),
+            Err(Error::EofWhileParsingString)
+        );
+        assert_eq!(
+            from_str_with_options::<String>(r#""a\'""#, &options),
+            Err(Error::InvalidEscape)
+        );
+
+        // rejected by default
+        assert_eq!(
+            from_str::<String>("'a'"),
+            Err(Error::ExpectedSomeValue(b'\''))
+        );
     }
 
+    #[cfg(feature = "json5")]
     #[test]
-    fn struct_bool() {
+    fn json5() {
+        use super::{from_str_with_options, Error, Options};
+        use std::collections::BTreeMap;
+
         #[derive(Debug, Deserialize, PartialEq)]
-        struct Led {
-            led: bool,
+        enum Action {
+            Send { to: String },
         }
 
-        assert_eq!(from_str(r#"{ "led": true }"#), Ok(Led { led: true }));
-        assert_eq!(from_str(r#"{ "led": false }"#), Ok(Led { led: false }));
-    }
-
-    #[test]
-    fn struct_i8() {
         #[derive(Debug, Deserialize, PartialEq)]
-        struct Temperature {
-            temperature: i8,
+        struct Config {
+            name: String,
+            mask: u32,
+            offset: i8,
+            ports: Vec<u16>,
+            action: Action,
         }
 
+        let options = Options::json5();
+        let input = r#"
+            // service configuration
+            {
+                name: 'it\'s "quoted"',
+                mask: 0xFF00,
+                offset: -0x80,
+                'ports': [80, 443,],
+                $ignored: {a_1: 'x', "b": [0x1F, 'y']},
+                action: {Send: {to: 'me'}},
+            }"#;
         assert_eq!(
-            from_str(r#"{ "temperature": -17 }"#),
-            Ok(Temperature { temperature: -17 })
+            from_str_with_options(input, &options),
+            Ok(Config {
+                name: "it's \"quoted\"".to_string(),
+                mask: 0xFF00,
+                offset: -0x80,
+                ports: vec![80, 443],
+                action: Action::Send {
+                    to: "me".to_string()
+                },
+            })
         );
 
+        let map: BTreeMap<u8, u64> =
+            from_str_with_options("{'1': 0x10, \"2\": 0XaB}", &options).unwrap();
         assert_eq!(
-            from_str(r#"{ "temperature": -0 }"#),
-            Ok(Temperature { temperature: -0 })
+            map.into_iter().collect::<Vec<_>>(),
+            vec![(1, 0x10), (2, 0xAB)]
+        );
+        assert_eq!(
+            from_str_with_options::<serde_json::Value>("[0x10, -0x10, 'a']", &options),
+            Ok(serde_json::json!([16, -16, "a"]))
         );
 
         assert_eq!(
-            from_str(r#"{ "temperature": 0 }"#),
-            Ok(Temperature { temperature: 0 })
+            from_str_with_options::<u8>("0x100", &options),
+            Err(Error::NumberOutOfRange)
+        );
+        assert_eq!(
+            from_str_with_options::<i8>("-0x81", &options),
+            Err(Error::NumberOutOfRange)
+        );
+        assert_eq!(
+            from_str_with_options::<u8>("0x", &options),
+            Err(Error::InvalidNumber)
+        );
+        assert_eq!(
+            from_str_with_options::<String>("'a\"", &options),
+            Err(Error::EofWhileParsingString)
+        );
+        assert_eq!(
+            from_str_with_options::<BTreeMap<u8, u8>>("{'1\": 1}", &options),
+            Err(Error::InvalidNumber)
+        );
+        assert_eq!(
+            from_str_with_options::<BTreeMap<String, u8>>("{1: 1}", &options),
+            Err(Error::KeyMustBeAString)
         );
 
-        // out of range
-        assert!(from_str::<Temperature>(r#"{ "temperature": 128 }"#).is_err());
-        assert!(from_str::<Temperature>(r#"{ "temperature": -129 }"#).is_err());
+        // all of it is rejected by default
+        assert_eq!(from_str::<u8>("0x10"), Err(Error::TrailingCharacters));
+        assert_eq!(
+            from_str::<&str>("'a'"),
+            Err(Error::ExpectedSomeValue(b'\''))
+        );
+        assert_eq!(
+            from_str::<BTreeMap<String, u8>>("{a: 1}"),
+            Err(Error::KeyMustBeAString)
+        );
     }
 
     #[test]
-    fn struct_option() {
+    fn skip_value() {
+        use super::{from_str_with_options, Error, Options};
+
         #[derive(Debug, Deserialize, PartialEq)]
-        struct Property {
-            description: Option<String>,
+        struct Temperature {
+            temperature: u8,
         }
+        let with_ignored = |ignored: &str| {
+            from_str::<Temperature>(&format!(
+                r#"{{ "temperature": 20, "ignored": {} }}"#,
+                ignored
+            ))
+        };
 
         assert_eq!(
-            from_str(r#"{ "description": "An ambient temperature sensor" }"#),
-            Ok(Property {
-                description: Some("An ambient temperature sensor".to_string()),
-            })
+            with_ignored(r#"[{ "a": [] }, {}, [[1], "]"], { "b": { "c": null } }]"#),
+            Ok(Temperature { temperature: 20 })
         );
-
+        assert_eq!(with_ignored(r#"[1, 2,]"#), Err(Error::TrailingComma));
+        assert_eq!(with_ignored(r#"{ "a": 1, }"#), Err(Error::TrailingComma));
         assert_eq!(
-            from_str(r#"{ "description": null }"#),
-            Ok(Property { description: None })
+            with_ignored(r#"{ "a": 1, "b": }"#),
+            Err(Error::ExpectedSomeValue(b'}'))
+        );
+        assert_eq!(with_ignored(r#"{ "a" 1 }"#), Err(Error::ExpectedColon));
+        assert_eq!(with_ignored(r#"{ 1: 1 }"#), Err(Error::KeyMustBeAString));
+        assert_eq!(
+            with_ignored(r#"[[], [}"#),
+            Err(Error::ExpectedSomeValue(b'}'))
+        );
+        assert_eq!(
+            with_ignored(r#"[{ "a": 1 ]"#),
+            Err(Error::ExpectedObjectCommaOrEnd)
+        );
+        assert_eq!(
+            from_str::<Temperature>(r#"{ "temperature": 20, "ignored": [[1], "#),
+            Err(Error::EofWhileParsingValue)
+        );
+        assert_eq!(
+            from_str::<Temperature>(r#"{ "temperature": 20, "ignored": [[1] "#),
+            Err(Error::EofWhileParsingList)
         );
 
-        assert_eq!(from_str(r#"{}"#), Ok(Property { description: None }));
+        // skipping doesn't recurse, so very deep nesting only needs a high enough limit
+        let depth = 1_000_000;
+        let input = format!(
+            r#"{{ "temperature": 20, "ignored": {}{} }}"#,
+            "[".repeat(depth),
+            "]".repeat(depth)
+        );
+        let options = Options::default().max_depth(depth + 1);
+        assert_eq!(
+            from_str_with_options(&input, &options),
+            Ok(Temperature { temperature: 20 })
+        );
     }
 
     #[test]
-    fn struct_u8() {
+    fn error_category() {
+        use crate::Category;
+
         #[derive(Debug, Deserialize, PartialEq)]
         struct Temperature {
             temperature: u8,
         }
 
+        let classify = |json: &str| from_str::<Temperature>(json).unwrap_err().classify();
+        assert_eq!(classify(r#"{ "temperature": 20 "#), Category::Eof);
+        assert_eq!(classify(r#"{ "temperature" "#), Category::Eof);
+        assert_eq!(classify(r#""#), Category::Eof);
+        assert_eq!(classify(r#"{ "temperature": 20, }"#), Category::Syntax);
+        assert_eq!(classify(r#"{ "temperature": 20 } 20"#), Category::Syntax);
+        assert_eq!(classify(r#"{ "temperature" 20 }"#), Category::Syntax);
+        assert_eq!(classify(r#"{ "temperature": true }"#), Category::Data);
+        assert_eq!(classify(r#"{ "temperature": 256 }"#), Category::Data);
+        assert_eq!(classify(r#"{ "temperature": -1 }"#), Category::Data);
+        assert_eq!(classify(r#"{ "temperature": - }"#), Category::Syntax);
+        assert_eq!(classify(r#"{}"#), Category::Data);
+
+        let err = from_str::<Temperature>(r#"{ "temperature": 2"#).unwrap_err();
+        assert!(err.is_eof() && !err.is_syntax() && !err.is_data());
+    }
+
+    #[test]
+    fn error_detail() {
+        use super::{Error, JsonType};
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Coin {
+            denom: String,
+            amount: u64,
+        }
+
         assert_eq!(
-            from_str(r#"{ "temperature": 20 }"#),
-            Ok(Temperature { temperature: 20 })
+            from_str::<Coin>(r#"{ "denom": ["uatom"], "amount": 1 }"#),
+            Err(Error::InvalidType(JsonType::Array))
         );
-
         assert_eq!(
-            from_str(r#"{ "temperature": 0 }"#),
-            Ok(Temperature { temperature: 0 })
+            from_str::<Coin>(r#"{ "denom": "uatom", "amount": null }"#),
+            Err(Error::InvalidType(JsonType::Null))
+        );
+        assert_eq!(
+            from_str::<Coin>(r#"{ "denom": "uatom", "amount": -1 }"#),
+            Err(Error::NumberOutOfRange)
+        );
+        assert_eq!(
+            from_str::<Coin>(r#"{ "denom": "uatom", "amount": :1 }"#),
+            Err(Error::ExpectedSomeValue(b':'))
+        );
+        assert_eq!(
+            from_str::<Coin>(r#"{ "amount": 1 }"#),
+            Err(Error::MissingField("denom"))
         );
 
-        // out of range
-        assert!(from_str::<Temperature>(r#"{ "temperature": 256 }"#).is_err());
-        assert!(from_str::<Temperature>(r#"{ "temperature": -1 }"#).is_err());
+        assert_eq!(
+            Error::InvalidType(JsonType::Bool).to_string(),
+            "Invalid type, found boolean"
+        );
+        assert_eq!(
+            Error::ExpectedSomeValue(b']').to_string(),
+            "Expected this character to start a JSON value, found `]`."
+        );
+        assert_eq!(
+            Error::ExpectedSomeValue(0xC3).to_string(),
+            "Expected this character to start a JSON value, found byte 0xC3."
+        );
+        assert_eq!(
+            Error::MissingField("amount").to_string(),
+            "missing field `amount`"
+        );
     }
 
     #[test]
-    fn struct_tuple() {
+    fn error_path() {
+        use super::{from_str_with_path, Error, JsonType};
+
         #[derive(Debug, Deserialize, PartialEq)]
-        struct Xy(i8, i8);
+        struct Coin {
+            denom: String,
+            amount: u64,
+        }
 
-        assert_eq!(from_str(r#"[10, 20]"#), Ok(Xy(10, 20)));
-        assert_eq!(from_str(r#"[10, -20]"#), Ok(Xy(10, -20)));
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Msg {
+            amount: Coin,
+        }
 
-        // wrong number of args
-        match from_str::<Xy>(r#"[10]"#) {
-            Err(super::Error::Custom(_)) => {}
-            _ => panic!("expect custom error"),
+        #[derive(Debug, Deserialize, PartialEq)]
+        #[serde(rename_all = "snake_case")]
+        enum ExecuteMsg {
+            Send { messages: Vec<Msg> },
         }
+
         assert_eq!(
-            from_str::<Xy>(r#"[10, 20, 30]"#),
-            Err(crate::de::Error::TrailingCharacters)
+            from_str_with_path(r#"{ "amount": { "denom": "uatom", "amount": 1 } }"#),
+            Ok(Msg {
+                amount: Coin {
+                    denom: "uatom".to_string(),
+                    amount: 1
+                }
+            })
+        );
+
+        let msg = |denom: &str| format!(r#"{{ "amount": {{ "denom": {}, "amount": 1 }} }}"#, denom);
+        let input = format!(
+            r#"{{ "send": {{ "messages": [{}, {}, {}, {}] }} }}"#,
+            msg(r#""a""#),
+            msg(r#""b""#),
+            msg(r#""c""#),
+            msg("4")
+        );
+        let err = from_str_with_path::<ExecuteMsg>(&input).unwrap_err();
+        assert_eq!(err.error, Error::InvalidType(JsonType::Number));
+        assert_eq!(err.path, "send.messages[3].amount.denom");
+        assert_eq!(
+            err.to_string(),
+            "Invalid type, found number at send.messages[3].amount.denom"
+        );
+
+        let err = from_str_with_path::<Vec<Vec<u8>>>(r#"[[1], [2, 300]]"#).unwrap_err();
+        assert_eq!(err.error, Error::NumberOutOfRange);
+        assert_eq!(err.path, "[1][1]");
+
+        // errors about the container itself don't point into it
+        let err = from_str_with_path::<Coin>(r#"{ "denom": "uatom" }"#).unwrap_err();
+        assert_eq!(err.path, "");
+        assert_eq!(err.to_string(), "missing field `amount`");
+        let err = from_str_with_path::<Coin>(r#"{ "denom": "uatom" "#).unwrap_err();
+        assert_eq!(
+            (err.error, err.path),
+            (Error::EofWhileParsingObject, "".to_string())
         );
     }
 
+    #[cfg(feature = "type-context")]
     #[test]
-    fn struct_empty() {
+    fn error_context() {
+        use super::{from_str_with_context, Error, JsonType};
+
         #[derive(Debug, Deserialize, PartialEq)]
-        struct Empty {};
+        struct Coin {
+            denom: String,
+            amount: u64,
+        }
 
-        assert_eq!(from_str(r#"{}"#), Ok(Empty {}));
-        assert_eq!(serde_json::from_str::<Empty>(r#"{}"#).unwrap(), Empty {});
-    }
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Denom(String);
 
-    #[test]
-    fn struct_nothing() {
         #[derive(Debug, Deserialize, PartialEq)]
-        struct Nothing;
+        #[serde(rename_all = "snake_case")]
+        enum ExecuteMsg {
+            Transfer { amount: Vec<Coin> },
+            Burn(Denom),
+        }
 
-        assert_eq!(from_str(r#"null"#), Ok(Nothing));
-        assert_eq!(serde_json::from_str::<Nothing>(r#"null"#).unwrap(), Nothing);
+        assert_eq!(
+            from_str_with_context(r#"{ "burn": "uatom" }"#),
+            Ok(ExecuteMsg::Burn(Denom("uatom".to_string())))
+        );
+
+        let err = from_str_with_context::<ExecuteMsg>(
+            r#"{ "transfer": { "amount": [{ "denom": "uatom", "amount": "1" }] } }"#,
+        )
+        .unwrap_err();
+        assert_eq!(err.error, Error::InvalidType(JsonType::String));
+        assert_eq!(err.context, "ExecuteMsg::transfer > Coin");
+        assert_eq!(
+            err.to_string(),
+            "while deserializing ExecuteMsg::transfer > Coin: Invalid type, found string"
+        );
+
+        let err = from_str_with_context::<ExecuteMsg>(r#"{ "burn": 1 }"#).unwrap_err();
+        assert_eq!(err.context, "ExecuteMsg::burn > Denom");
+
+        // errors outside of any struct or enum have no context
+        let err = from_str_with_context::<Vec<u8>>("[1, 300]").unwrap_err();
+        assert_eq!(
+            (err.error, err.context),
+            (Error::NumberOutOfRange, "".to_string())
+        );
     }
 
     #[test]
@@ -916,17 +5579,17 @@ mod tests {
 
         assert_eq!(
             from_str::<Temperature>(r#"{ "temperature": 20, "broken": }"#),
-            Err(crate::de::Error::ExpectedSomeValue)
+            Err(crate::de::Error::ExpectedSomeValue(b'}'))
         );
 
         assert_eq!(
             from_str::<Temperature>(r#"{ "temperature": 20, "broken": [ }"#),
-            Err(crate::de::Error::ExpectedSomeValue)
+            Err(crate::de::Error::ExpectedSomeValue(b'}'))
         );
 
         assert_eq!(
             from_str::<Temperature>(r#"{ "temperature": 20, "broken": ] }"#),
-            Err(crate::de::Error::ExpectedSomeValue)
+            Err(crate::de::Error::ExpectedSomeValue(b']'))
         );
     }
 
@@ -1188,4 +5851,89 @@ mod tests {
             })
         )
     }
+
+    #[test]
+    fn peek_type() {
+        use super::{peek_type, JsonType};
+
+        assert_eq!(peek_type(b"null"), Some(JsonType::Null));
+        assert_eq!(peek_type(b"false"), Some(JsonType::Bool));
+        assert_eq!(peek_type(b"\t-1"), Some(JsonType::Number));
+        assert_eq!(peek_type(b"\r\n\"a\""), Some(JsonType::String));
+        assert_eq!(peek_type(b"["), Some(JsonType::Array));
+        assert_eq!(peek_type(b" {}"), Some(JsonType::Object));
+        assert_eq!(peek_type(b""), None);
+        assert_eq!(peek_type(b"  "), None);
+        assert_eq!(peek_type(b"'a'"), None);
+    }
+
+    #[test]
+    fn validate() {
+        use super::{validate, validate_with_options, Error, Options};
+
+        assert_eq!(
+            validate(br#" {"a": [1, -2.5e3, "x", true, null, {}]} "#),
+            Ok(())
+        );
+        assert_eq!(validate(b"[1, 2,]"), Err(Error::TrailingComma));
+        assert_eq!(validate(b"[tru]"), Err(Error::ExpectedSomeIdent));
+        assert_eq!(validate(b"[01]"), Err(Error::ExpectedListCommaOrEnd));
+        assert_eq!(validate(b"[x]"), Err(Error::ExpectedSomeValue(b'x')));
+        assert_eq!(validate(b"{1: 2}"), Err(Error::KeyMustBeAString));
+        assert_eq!(validate(b"\"a\\x\""), Err(Error::InvalidEscape));
+        assert_eq!(validate(b"1 2"), Err(Error::TrailingCharacters));
+        assert_eq!(validate(b""), Err(Error::EofWhileParsingValue));
+
+        let options = Options::default()
+            .allow_trailing_commas(true)
+            .allow_unquoted_keys(true)
+            .allow_comments(true)
+            .max_depth(2)
+            .max_string_len(3);
+        assert_eq!(
+            validate_with_options(b"{a: [1, 2,], /* c */ b: 'x'}", &options),
+            Err(Error::ExpectedSomeValue(b'\''))
+        );
+        assert_eq!(
+            validate_with_options(b"{a: [1, 2,], /* c */ b: \"x\"}", &options),
+            Ok(())
+        );
+        assert_eq!(
+            validate_with_options(b"[[[]]]", &options),
+            Err(Error::RecursionLimitExceeded)
+        );
+        assert_eq!(
+            validate_with_options(b"[\"abcd\"]", &options),
+            Err(Error::StringTooLong)
+        );
+        assert_eq!(
+            validate_with_options(b"[1]", &options.clone().max_input_len(2)),
+            Err(Error::InputTooLong)
+        );
+        // duplicate keys are only detected while deserializing
+        let options = Options::default().duplicate_keys(super::DuplicateKeys::Reject);
+        assert_eq!(
+            validate_with_options(br#"{"a": 1, "a": 2}"#, &options),
+            Ok(())
+        );
+        let options = Options::default().allow_hex_integers(true);
+        assert_eq!(validate_with_options(b"[0x1F, -0xa]", &options), Ok(()));
+        assert_eq!(
+            validate_with_options(b"[0x]", &options),
+            Err(Error::InvalidNumber)
+        );
+    }
+
+    #[cfg(feature = "float")]
+    #[test]
+    fn validate_non_finite_floats() {
+        use super::{validate, validate_with_options, Error, Options};
+
+        let options = Options::default().allow_non_finite_floats(true);
+        assert_eq!(
+            validate_with_options(b"[NaN, Infinity, -Infinity, 1.5]", &options),
+            Ok(())
+        );
+        assert_eq!(validate(b"[NaN]"), Err(Error::ExpectedSomeValue(b'N')));
+    }
 }
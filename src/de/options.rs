@@ -0,0 +1,372 @@
+/// Maximum nesting depth of arrays and objects used by [`Options::default`]
+pub const DEFAULT_MAX_DEPTH: usize = 128;
+
+/// Settings that control how JSON is parsed
+///
+/// Use [`Options::default`] and the builder methods to create one, then pass it to
+/// [`from_slice_with_options`](crate::de::from_slice_with_options).
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Options {
+    /// Maximum nesting depth of arrays and objects. Deeper input fails with
+    /// [`Error::RecursionLimitExceeded`](crate::de::Error::RecursionLimitExceeded) instead of
+    /// exhausting the stack. Ignored values are skipped without recursion, but every level of a
+    /// value that is deserialized into a type takes a nested call.
+    pub max_depth: usize,
+
+    /// Maximum number of elements of an array. Longer arrays are rejected with
+    /// [`Error::ArrayTooLong`](crate::de::Error::ArrayTooLong). Unlimited by default.
+    pub max_array_len: Option<usize>,
+
+    /// Maximum number of members of an object, counting duplicate keys. Larger objects are
+    /// rejected with [`Error::ObjectTooLong`](crate::de::Error::ObjectTooLong). Unlimited by
+    /// default.
+    pub max_object_len: Option<usize>,
+
+    /// Maximum length of the input in bytes. Longer input is rejected with
+    /// [`Error::InputTooLong`](crate::de::Error::InputTooLong) before it is parsed. For a
+    /// [`PushDeserializer`](crate::de::PushDeserializer) and the functions reading from a byte
+    /// source, it limits the number of bytes buffered for one value, so reading stops once the
+    /// limit is exceeded. Unlimited by default.
+    pub max_input_len: Option<usize>,
+
+    /// Maximum length of a string in bytes after unescaping, which applies to object keys as
+    /// well. Longer strings are rejected with
+    /// [`Error::StringTooLong`](crate::de::Error::StringTooLong) while they are unescaped, so
+    /// no more than this is allocated for them. Unlimited by default.
+    pub max_string_len: Option<usize>,
+
+    /// Accept a comma after the last element of an array or the last member of an object, like
+    /// `[1, 2,]`. Off by default.
+    pub allow_trailing_commas: bool,
+
+    /// Treat `// line` and `/* block */` comments as whitespace. Off by default.
+    pub allow_comments: bool,
+
+    /// Accept object keys that are not in quotes, like `amount` in `{amount: "1"}`. Such keys are
+    /// limited to ASCII letters, digits, `_` and `$`, and must not start with a digit. Off by
+    /// default.
+    pub allow_unquoted_keys: bool,
+
+    /// Accept strings in single quotes, like `'text'`, for both values and object keys. They
+    /// follow the same escape rules as strings in double quotes, except that `\'` is a valid
+    /// escape sequence and `"` doesn't need to be escaped. Off by default.
+    pub allow_single_quotes: bool,
+
+    /// Accept `NaN`, `Infinity` and `-Infinity` as `f32` and `f64` values. Off by default.
+    #[cfg(feature = "float")]
+    pub allow_non_finite_floats: bool,
+
+    /// Accept the control characters U+0000 to U+001F, such as a raw line break, in strings.
+    /// They must be escaped in JSON, so they are rejected with
+    /// [`Error::ControlCharacterInString`](crate::de::Error::ControlCharacterInString) by default.
+    pub allow_control_characters: bool,
+
+    /// What to do with escaped UTF-16 surrogates that are not part of a pair, like `"\uD800"`.
+    /// They are rejected by default.
+    pub lone_surrogates: LoneSurrogates,
+
+    /// Replace invalid UTF-8 in strings by U+FFFD instead of rejecting it with
+    /// [`Error::InvalidUtf8`](crate::de::Error::InvalidUtf8). For
+    /// best-effort decoding of input with a few corrupt bytes. Strings that had to be repaired are
+    /// owned, so they cannot be deserialized into `&str`. Off by default.
+    pub replace_invalid_utf8: bool,
+
+    /// What to do with an object key that occurs more than once in the same object. By default
+    /// all members are passed on, so the last one wins for maps, while derived structs fail with
+    /// a duplicate field error. Objects in skipped values are not checked.
+    pub duplicate_keys: DuplicateKeys,
+
+    /// Reject object keys that are not fields of the struct being deserialized with
+    /// [`Error::UnknownField`](crate::de::Error::UnknownField), as if every struct had
+    /// `#[serde(deny_unknown_fields)]`. Only field names are known, not their aliases. Structs
+    /// with flattened fields and enums that serde buffers, like untagged enums, are not checked.
+    /// Off by default.
+    pub deny_unknown_fields: bool,
+
+    /// Reject numbers that don't follow the JSON grammar with
+    /// [`Error::InvalidNumber`](crate::de::Error::InvalidNumber), including numbers in skipped
+    /// values such as unknown struct fields. For example `01`, `1.`, `.5` and `+1` are rejected.
+    /// Off by default, in which case some of these are reported as other errors and skipped
+    /// values are not validated. Hexadecimal integers are still accepted if enabled.
+    pub strict_numbers: bool,
+
+    /// Reject numbers with a fraction or exponent, like `1.5` or `1e3`, with
+    /// [`Error::FloatNotAllowed`](crate::de::Error::FloatNotAllowed), whatever the type they are
+    /// deserialized to. This includes numbers in skipped values and numbers deserialized as
+    /// [`Number`](crate::Number) or [`RawValue`](crate::RawValue). Off by default.
+    pub deny_floats: bool,
+
+    /// Accept numbers with a fraction or exponent, like `1e3`, `2.5e2` or `7.0`, for integer
+    /// types if their value is an integer in the range of the type, as written by JavaScript
+    /// serializers that use exponents for large numbers. Other such numbers fail with
+    /// [`Error::InvalidNumber`](crate::de::Error::InvalidNumber), or with
+    /// [`Error::NumberOutOfRange`](crate::de::Error::NumberOutOfRange) if they are integers out of
+    /// the range of the type. The value is computed from the
+    /// decimal digits, without floating point arithmetic. Off by default, in which case the
+    /// fraction or exponent is rejected as trailing characters.
+    pub allow_exact_float_integers: bool,
+
+    /// Accept integers in strings, like `"12345"`, for integer types. CosmWasm and the Cosmos SDK
+    /// write 64 and 128 bit integers this way since JavaScript cannot represent all of them as
+    /// numbers. The string must contain nothing but the integer. Off by default.
+    pub allow_quoted_integers: bool,
+
+    /// Accept numbers in strings, like `"0.25"`, for `f32` and `f64`, as written by price feeds
+    /// that want to keep the decimal text intact. Off by default.
+    #[cfg(feature = "float")]
+    pub allow_quoted_floats: bool,
+
+    /// Decode bytes from strings of hexadecimal digits, like `"0xdeadbeef"` or `"DEADBEEF"`, as
+    /// used for hashes and addresses. The `0x` prefix is optional. This replaces base64 strings
+    /// with the `base64` feature. Off by default.
+    pub hex_bytes: bool,
+
+    /// Skip a UTF-8 byte order mark (the bytes `EF BB BF`) at the start of the input, as written
+    /// by some Windows tools. Off by default, in which case it is rejected with
+    /// [`Error::ExpectedSomeValue`](crate::de::Error::ExpectedSomeValue) like serde_json does.
+    /// For a stream of values, as read by a `PushDeserializer`, only the first one may start with
+    /// it.
+    pub allow_byte_order_mark: bool,
+
+    /// Accept hexadecimal integers like `0x1A2B` or `-0x80` for integer types, as written by
+    /// device configuration and firmware tooling for register values. Both `0x` and `0X` and
+    /// digits in either case are accepted. Off by default, in which case the `x` is rejected with
+    /// [`Error::TrailingCharacters`](crate::de::Error::TrailingCharacters) or another syntax
+    /// error.
+    pub allow_hex_integers: bool,
+
+    /// Visit strings with escape sequences with `visit_str` and keep the buffer they were
+    /// unescaped into for the next one, rather than handing it over with `visit_string`. This
+    /// saves an allocation per escaped string for types that copy short strings inline, like
+    /// `SmolStr` and `CompactString`, but costs a copy for `String`. Off by default.
+    pub reuse_string_buffer: bool,
+}
+
+impl Options {
+    /// Options that accept JSON5 (<https://json5.org>), which is convenient for hand-written
+    /// configuration files
+    ///
+    /// This allows comments, trailing commas, object keys that are ASCII identifiers, strings in
+    /// single quotes and hexadecimal integers. JSON5 numbers that are not integers, such as
+    /// `Infinity`, are not supported, and neither are line continuations in strings.
+    #[cfg(feature = "json5")]
+    pub fn json5() -> Self {
+        Options {
+            allow_trailing_commas: true,
+            allow_comments: true,
+            allow_unquoted_keys: true,
+            allow_single_quotes: true,
+            allow_hex_integers: true,
+            ..Options::default()
+        }
+    }
+
+    /// Sets the maximum nesting depth of arrays and objects
+    pub fn max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    /// Sets the maximum number of elements of an array
+    pub fn max_array_len(mut self, max_array_len: usize) -> Self {
+        self.max_array_len = Some(max_array_len);
+        self
+    }
+
+    /// Sets the maximum number of members of an object
+    pub fn max_object_len(mut self, max_object_len: usize) -> Self {
+        self.max_object_len = Some(max_object_len);
+        self
+    }
+
+    /// Sets the maximum length of the input in bytes
+    pub fn max_input_len(mut self, max_input_len: usize) -> Self {
+        self.max_input_len = Some(max_input_len);
+        self
+    }
+
+    /// Sets the maximum length of a string in bytes after unescaping
+    pub fn max_string_len(mut self, max_string_len: usize) -> Self {
+        self.max_string_len = Some(max_string_len);
+        self
+    }
+
+    /// Sets whether a trailing comma after the last element of an array or object is accepted
+    pub fn allow_trailing_commas(mut self, allow: bool) -> Self {
+        self.allow_trailing_commas = allow;
+        self
+    }
+
+    /// Sets whether `//` and `/* */` comments are accepted wherever whitespace is
+    pub fn allow_comments(mut self, allow: bool) -> Self {
+        self.allow_comments = allow;
+        self
+    }
+
+    /// Sets whether identifiers are accepted as object keys without quotes
+    pub fn allow_unquoted_keys(mut self, allow: bool) -> Self {
+        self.allow_unquoted_keys = allow;
+        self
+    }
+
+    /// Sets whether strings may be in single quotes
+    pub fn allow_single_quotes(mut self, allow: bool) -> Self {
+        self.allow_single_quotes = allow;
+        self
+    }
+
+    /// Sets whether unescaped control characters are accepted in strings
+    pub fn allow_control_characters(mut self, allow: bool) -> Self {
+        self.allow_control_characters = allow;
+        self
+    }
+
+    /// Sets what to do with escaped surrogates that are not part of a pair
+    pub fn lone_surrogates(mut self, policy: LoneSurrogates) -> Self {
+        self.lone_surrogates = policy;
+        self
+    }
+
+    /// Sets whether invalid UTF-8 in strings is replaced by U+FFFD
+    pub fn replace_invalid_utf8(mut self, replace: bool) -> Self {
+        self.replace_invalid_utf8 = replace;
+        self
+    }
+
+    /// Sets what to do with duplicate object keys
+    pub fn duplicate_keys(mut self, policy: DuplicateKeys) -> Self {
+        self.duplicate_keys = policy;
+        self
+    }
+
+    /// Sets whether object keys that are not fields of the struct are rejected
+    pub fn deny_unknown_fields(mut self, deny: bool) -> Self {
+        self.deny_unknown_fields = deny;
+        self
+    }
+
+    /// Sets whether numbers are validated strictly against the JSON grammar
+    pub fn strict_numbers(mut self, strict: bool) -> Self {
+        self.strict_numbers = strict;
+        self
+    }
+
+    /// Sets whether numbers with a fraction or exponent are rejected
+    pub fn deny_floats(mut self, deny: bool) -> Self {
+        self.deny_floats = deny;
+        self
+    }
+
+    /// Sets whether numbers with a fraction or exponent are accepted for integer types when
+    /// their value is an integer
+    pub fn allow_exact_float_integers(mut self, allow: bool) -> Self {
+        self.allow_exact_float_integers = allow;
+        self
+    }
+
+    /// Sets whether integers in strings are accepted for integer types
+    pub fn allow_quoted_integers(mut self, allow: bool) -> Self {
+        self.allow_quoted_integers = allow;
+        self
+    }
+
+    /// Sets whether numbers in strings are accepted for `f32` and `f64`
+    #[cfg(feature = "float")]
+    pub fn allow_quoted_floats(mut self, allow: bool) -> Self {
+        self.allow_quoted_floats = allow;
+        self
+    }
+
+    /// Sets whether bytes are decoded from hex strings
+    pub fn hex_bytes(mut self, hex: bool) -> Self {
+        self.hex_bytes = hex;
+        self
+    }
+
+    /// Sets whether a UTF-8 byte order mark at the start of the input is skipped
+    pub fn allow_byte_order_mark(mut self, allow: bool) -> Self {
+        self.allow_byte_order_mark = allow;
+        self
+    }
+
+    /// Sets whether hexadecimal integers like `0x1A2B` are accepted for integer types
+    pub fn allow_hex_integers(mut self, allow: bool) -> Self {
+        self.allow_hex_integers = allow;
+        self
+    }
+
+    /// Sets whether the buffer for strings with escape sequences is reused
+    pub fn reuse_string_buffer(mut self, reuse: bool) -> Self {
+        self.reuse_string_buffer = reuse;
+        self
+    }
+
+    /// Sets whether `NaN`, `Infinity` and `-Infinity` are accepted as floats
+    #[cfg(feature = "float")]
+    pub fn allow_non_finite_floats(mut self, allow: bool) -> Self {
+        self.allow_non_finite_floats = allow;
+        self
+    }
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            max_depth: DEFAULT_MAX_DEPTH,
+            max_array_len: None,
+            max_object_len: None,
+            max_input_len: None,
+            max_string_len: None,
+            allow_trailing_commas: false,
+            allow_comments: false,
+            allow_unquoted_keys: false,
+            allow_single_quotes: false,
+            #[cfg(feature = "float")]
+            allow_non_finite_floats: false,
+            allow_control_characters: false,
+            lone_surrogates: LoneSurrogates::Reject,
+            replace_invalid_utf8: false,
+            duplicate_keys: DuplicateKeys::LastWins,
+            deny_unknown_fields: false,
+            strict_numbers: false,
+            deny_floats: false,
+            allow_exact_float_integers: false,
+            allow_quoted_integers: false,
+            #[cfg(feature = "float")]
+            allow_quoted_floats: false,
+            hex_bytes: false,
+            allow_byte_order_mark: false,
+            allow_hex_integers: false,
+            reuse_string_buffer: false,
+        }
+    }
+}
+
+/// Handling of an escaped UTF-16 surrogate that is not part of a surrogate pair
+///
+/// Such escapes don't describe a character, so they cannot be represented in a Rust string.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum LoneSurrogates {
+    /// Fail with [`Error::LoneSurrogateFound`](crate::de::Error::LoneSurrogateFound),
+    /// [`Error::ExpectedLowSurrogate`](crate::de::Error::ExpectedLowSurrogate) or
+    /// [`Error::ExpectedHighSurrogate`](crate::de::Error::ExpectedHighSurrogate)
+    Reject,
+    /// Replace the surrogate by U+FFFD REPLACEMENT CHARACTER, like lossy UTF-16 decoders do
+    Replace,
+}
+
+/// Handling of an object key that occurs more than once in the same object
+///
+/// Keys are compared after unescaping, so `"a"` and `"\u0061"` are the same key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum DuplicateKeys {
+    /// Pass every member on. Maps keep the last value, like serde and serde_json do.
+    LastWins,
+    /// Keep the first member and skip later ones with the same key
+    FirstWins,
+    /// Fail with [`Error::DuplicateKey`](crate::de::Error::DuplicateKey)
+    Reject,
+}
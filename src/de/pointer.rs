@@ -0,0 +1,413 @@
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::str::from_utf8;
+
+use crate::de::{Deserializer, Error, Result};
+
+/// Returns the text of the value at the JSON Pointer (RFC 6901) `pointer` in `input`, like
+/// `/result/txs/0/hash`
+///
+/// Only the path to the value is parsed: the members and elements before it are skipped, and the
+/// rest of the document is not read at all. The skipped values and the returned value are
+/// checked to be valid JSON. If an object has several members with the same key, the first one is used.
+///
+/// Returns `None` if there is no value at `pointer` or `pointer` is not a valid JSON Pointer.
+/// The empty pointer `""` refers to the whole document.
+///
+/// ```
+/// use serde_json_wasm::de::pointer;
+///
+/// let response = br#"{"result": {"txs": [{"hash": "A1B2", "tx": "..."}]}}"#;
+/// assert_eq!(pointer(response, "/result/txs/0/hash"), Ok(Some(r#""A1B2""#)));
+/// assert_eq!(pointer(response, "/result/txs/1/hash"), Ok(None));
+/// ```
+pub fn pointer<'a>(input: &'a [u8], pointer: &str) -> Result<Option<&'a str>> {
+    if !pointer.is_empty() && !pointer.starts_with('/') {
+        return Ok(None);
+    }
+    let mut de = Deserializer::new(input);
+    for token in pointer.split('/').skip(1) {
+        let found = match de.parse_whitespace().ok_or(Error::EofWhileParsingValue)? {
+            b'{' => find_member(&mut de, &token.replace("~1", "/").replace("~0", "~"))?,
+            b'[' => match parse_index(token) {
+                Some(index) => find_element(&mut de, index)?,
+                None => false,
+            },
+            _ => false,
+        };
+        if !found {
+            return Ok(None);
+        }
+    }
+    de.parse_raw_slice().map(Some)
+}
+
+/// Returns the text of the value of the member `key` of the object in `input`, or `None` if
+/// there is no such member or the input is not an object
+///
+/// This is a shortcut for a [`pointer()`] with one key, for looking at a field like the
+/// discriminator of a message before choosing the type to deserialize it to. Errors in the
+/// input up to and including the value also return `None`.
+///
+/// ```
+/// use serde_json_wasm::de::get_field;
+///
+/// let msg = br#"{"type": "transfer", "amount": "100"}"#;
+/// assert_eq!(get_field(msg, "type"), Some(r#""transfer""#));
+/// assert_eq!(get_field(msg, "denom"), None);
+/// ```
+pub fn get_field<'a>(input: &'a [u8], key: &str) -> Option<&'a str> {
+    let mut de = Deserializer::new(input);
+    if de.parse_whitespace() != Some(b'{') || !find_member(&mut de, key).ok()? {
+        return None;
+    }
+    de.parse_raw_slice().ok()
+}
+
+/// Returns the texts of the values at several JSON Pointers in `input`, in the order of
+/// `pointers`, reading the document once
+///
+/// Only the members and elements on the way to the requested values are looked at. Everything
+/// else is skipped without being deserialized, and the document is read only until all values
+/// were found. This is for messages where only a few fields out of many are needed. Like in
+/// [`pointer()`], a pointer without a value, or one that is not a valid JSON Pointer, gets `None`,
+/// and of several members with the same key the first one is used.
+///
+/// ```
+/// use serde_json_wasm::de::select;
+///
+/// let msg = br#"{"transfer": {"recipient": "alice", "amount": "100", "memo": "..."}, "id": 7}"#;
+/// let fields = select(msg, &["/transfer/amount", "/transfer/recipient", "/fee"]).unwrap();
+/// assert_eq!(fields, [Some(r#""100""#), Some(r#""alice""#), None]);
+/// ```
+pub fn select<'a>(input: &'a [u8], pointers: &[&str]) -> Result<Vec<Option<&'a str>>> {
+    let paths: Vec<Option<Vec<String>>> = pointers
+        .iter()
+        .map(|pointer| {
+            if !pointer.is_empty() && !pointer.starts_with('/') {
+                return None;
+            }
+            let tokens = pointer.split('/').skip(1);
+            Some(
+                tokens
+                    .map(|token| token.replace("~1", "/").replace("~0", "~"))
+                    .collect(),
+            )
+        })
+        .collect();
+    let candidates: Vec<usize> = (0..paths.len()).filter(|&i| paths[i].is_some()).collect();
+    let mut selection = Selection {
+        paths: paths.into_iter().map(Option::unwrap_or_default).collect(),
+        found: vec![None; pointers.len()],
+        missing: candidates.len(),
+    };
+    if !candidates.is_empty() {
+        selection.visit(&mut Deserializer::new(input), &candidates, 0)?;
+    }
+    Ok(selection.found)
+}
+
+/// State of [`select`]
+struct Selection<'a> {
+    /// Reference tokens of the pointers
+    paths: Vec<Vec<String>>,
+    found: Vec<Option<&'a str>>,
+    /// Number of valid pointers whose value was not found yet
+    missing: usize,
+}
+
+impl<'a> Selection<'a> {
+    /// Visits the value at the current position, which is where the first `level` tokens of the
+    /// `candidates` lead
+    fn visit(
+        &mut self,
+        de: &mut Deserializer<'a>,
+        candidates: &[usize],
+        level: usize,
+    ) -> Result<()> {
+        let c = de.parse_whitespace().ok_or(Error::EofWhileParsingValue)?;
+        let start = de.read.index;
+        let (here, deeper): (Vec<usize>, Vec<usize>) = candidates
+            .iter()
+            .partition(|&&i| self.paths[i].len() == level);
+        match c {
+            b'{' if !deeper.is_empty() => self.visit_object(de, &deeper, level)?,
+            b'[' if !deeper.is_empty() => self.visit_array(de, &deeper, level)?,
+            _ => de.skip_value_checked(true)?,
+        }
+        if !here.is_empty() {
+            let text = from_utf8(&de.read.slice[start..de.read.index])
+                .map_err(|_| Error::InvalidUnicodeCodePoint)?;
+            for i in here {
+                if self.found[i].is_none() {
+                    self.found[i] = Some(text);
+                    self.missing -= 1;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn visit_object(
+        &mut self,
+        de: &mut Deserializer<'a>,
+        candidates: &[usize],
+        level: usize,
+    ) -> Result<()> {
+        de.enter_nested()?;
+        de.eat_char();
+        if de.parse_whitespace() != Some(b'}') {
+            loop {
+                match de.parse_whitespace().ok_or(Error::EofWhileParsingObject)? {
+                    b'"' => {}
+                    _ => return Err(Error::KeyMustBeAString),
+                }
+                let key = de.parse_string()?;
+                let matching: Vec<usize> = candidates
+                    .iter()
+                    .copied()
+                    .filter(|&i| self.paths[i][level] == key.as_str())
+                    .collect();
+                de.parse_object_colon()?;
+                if matching.is_empty() {
+                    de.skip_value_checked(true)?;
+                } else {
+                    self.visit(de, &matching, level + 1)?;
+                    if self.missing == 0 {
+                        return Ok(());
+                    }
+                }
+                match de.parse_whitespace() {
+                    Some(b',') => de.eat_char(),
+                    Some(b'}') => break,
+                    Some(_) => return Err(Error::ExpectedObjectCommaOrEnd),
+                    None => return Err(Error::EofWhileParsingObject),
+                }
+            }
+        }
+        de.eat_char();
+        de.leave_nested();
+        Ok(())
+    }
+
+    fn visit_array(
+        &mut self,
+        de: &mut Deserializer<'a>,
+        candidates: &[usize],
+        level: usize,
+    ) -> Result<()> {
+        de.enter_nested()?;
+        de.eat_char();
+        if de.parse_whitespace() != Some(b']') {
+            let mut index = 0;
+            loop {
+                let matching: Vec<usize> = candidates
+                    .iter()
+                    .copied()
+                    .filter(|&i| parse_index(&self.paths[i][level]) == Some(index))
+                    .collect();
+                if matching.is_empty() {
+                    de.skip_value_checked(true)?;
+                } else {
+                    self.visit(de, &matching, level + 1)?;
+                    if self.missing == 0 {
+                        return Ok(());
+                    }
+                }
+                match de.parse_whitespace() {
+                    Some(b',') => de.eat_char(),
+                    Some(b']') => break,
+                    Some(_) => return Err(Error::ExpectedListCommaOrEnd),
+                    None => return Err(Error::EofWhileParsingList),
+                }
+                index += 1;
+            }
+        }
+        de.eat_char();
+        de.leave_nested();
+        Ok(())
+    }
+}
+
+/// Parses an array index, which must not have leading zeros
+fn parse_index(token: &str) -> Option<usize> {
+    if token.is_empty()
+        || !token.bytes().all(|c| c.is_ascii_digit())
+        || (token.len() > 1 && token.starts_with('0'))
+    {
+        return None;
+    }
+    token.parse().ok()
+}
+
+/// Moves into the object at the current position to the value of the member with `key`.
+/// Returns `false` if there is none.
+fn find_member(de: &mut Deserializer<'_>, key: &str) -> Result<bool> {
+    de.enter_nested()?;
+    de.eat_char();
+    if de.parse_whitespace() == Some(b'}') {
+        return Ok(false);
+    }
+    loop {
+        match de.parse_whitespace().ok_or(Error::EofWhileParsingObject)? {
+            b'"' => {}
+            _ => return Err(Error::KeyMustBeAString),
+        }
+        let found = de.parse_string()?.as_str() == key;
+        de.parse_object_colon()?;
+        if found {
+            return Ok(true);
+        }
+        de.skip_value_checked(true)?;
+        match de.parse_whitespace() {
+            Some(b',') => de.eat_char(),
+            Some(b'}') => return Ok(false),
+            Some(_) => return Err(Error::ExpectedObjectCommaOrEnd),
+            None => return Err(Error::EofWhileParsingObject),
+        }
+    }
+}
+
+/// Moves into the array at the current position to the element at `index`. Returns `false` if
+/// the array is shorter.
+fn find_element(de: &mut Deserializer<'_>, index: usize) -> Result<bool> {
+    de.enter_nested()?;
+    de.eat_char();
+    if de.parse_whitespace() == Some(b']') {
+        return Ok(false);
+    }
+    for _ in 0..index {
+        de.skip_value_checked(true)?;
+        match de.parse_whitespace() {
+            Some(b',') => de.eat_char(),
+            Some(b']') => return Ok(false),
+            Some(_) => return Err(Error::ExpectedListCommaOrEnd),
+            None => return Err(Error::EofWhileParsingList),
+        }
+    }
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{get_field, pointer, select};
+    use crate::de::Error;
+
+    const RESPONSE: &[u8] = br#"{
+        "jsonrpc": "2.0",
+        "result": {
+            "txs": [
+                {"hash": "A1", "height": "5"},
+                {"hash": "B2", "height": "7", "events": [1, [2, 3]]}
+            ],
+            "a/b": {"m~n": true},
+            "total": 2
+        }
+    }"#;
+
+    #[test]
+    fn pointer_finds_values() {
+        assert_eq!(pointer(RESPONSE, "/jsonrpc"), Ok(Some(r#""2.0""#)));
+        assert_eq!(pointer(RESPONSE, "/result/txs/1/hash"), Ok(Some(r#""B2""#)));
+        assert_eq!(pointer(RESPONSE, "/result/txs/1/events/1/0"), Ok(Some("2")));
+        assert_eq!(
+            pointer(RESPONSE, "/result/txs/0"),
+            Ok(Some(r#"{"hash": "A1", "height": "5"}"#))
+        );
+        assert_eq!(pointer(RESPONSE, "/result/a~1b/m~0n"), Ok(Some("true")));
+        assert_eq!(pointer(b" [1, 2] ", ""), Ok(Some("[1, 2]")));
+        assert_eq!(pointer(br#"{"": 1}"#, "/"), Ok(Some("1")));
+        assert_eq!(pointer(br#"{"a": 1}"#, "/a"), Ok(Some("1")));
+    }
+
+    #[test]
+    fn pointer_missing_values() {
+        assert_eq!(pointer(RESPONSE, "/id"), Ok(None));
+        assert_eq!(pointer(RESPONSE, "/result/txs/2"), Ok(None));
+        assert_eq!(pointer(RESPONSE, "/result/txs/01"), Ok(None));
+        assert_eq!(pointer(RESPONSE, "/result/txs/-"), Ok(None));
+        assert_eq!(pointer(RESPONSE, "/result/total/0"), Ok(None));
+        assert_eq!(pointer(RESPONSE, "result"), Ok(None));
+        assert_eq!(pointer(b"{}", "/a"), Ok(None));
+        assert_eq!(pointer(b"[]", "/0"), Ok(None));
+    }
+
+    #[test]
+    fn pointer_errors() {
+        // the document is only read up to the value
+        assert_eq!(pointer(br#"{"a": 1, "b": tru"#, "/a"), Ok(Some("1")));
+        assert_eq!(
+            pointer(br#"{"a": 1 "b": 2}"#, "/b"),
+            Err(Error::ExpectedObjectCommaOrEnd)
+        );
+        assert_eq!(
+            pointer(br#"{"a": [1, tru]}"#, "/a"),
+            Err(Error::ExpectedSomeIdent)
+        );
+        assert_eq!(pointer(b"[1, 2", "/5"), Err(Error::EofWhileParsingList));
+        assert_eq!(pointer(b"", ""), Err(Error::EofWhileParsingValue));
+    }
+
+    #[test]
+    fn select_values() {
+        assert_eq!(
+            select(
+                RESPONSE,
+                &[
+                    "/result/txs/1/hash",
+                    "/jsonrpc",
+                    "/result/txs/0",
+                    "/result/txs/0/hash",
+                    "/result/a~1b/m~0n",
+                    "/result/txs/5",
+                    "result",
+                    "/result/total",
+                ]
+            ),
+            Ok(vec![
+                Some(r#""B2""#),
+                Some(r#""2.0""#),
+                Some(r#"{"hash": "A1", "height": "5"}"#),
+                Some(r#""A1""#),
+                Some("true"),
+                None,
+                None,
+                Some("2"),
+            ])
+        );
+        assert_eq!(
+            select(b" [1, [2]] ", &["", "/1/0"]),
+            Ok(vec![Some("[1, [2]]"), Some("2")])
+        );
+        assert_eq!(select(br#"{"a": 1, "a": 2}"#, &["/a"]), Ok(vec![Some("1")]));
+        assert_eq!(select(b"{}", &[]), Ok(vec![]));
+
+        // the document is only read until all values were found
+        assert_eq!(
+            select(br#"{"a": [1, 2], "b": tru"#, &["/a/1"]),
+            Ok(vec![Some("2")])
+        );
+        assert_eq!(
+            select(br#"{"a": [1, 2], "b": tru"#, &["/a/1", "/c"]),
+            Err(Error::ExpectedSomeIdent)
+        );
+        assert_eq!(
+            select(br#"{"a": 1 "b": 2}"#, &["/b"]),
+            Err(Error::ExpectedObjectCommaOrEnd)
+        );
+    }
+
+    #[test]
+    fn get_field_values() {
+        let msg = br#" {"amount": {"denom": "uatom"}, "type": "send", "type": "x"} "#;
+        assert_eq!(get_field(msg, "type"), Some(r#""send""#));
+        assert_eq!(get_field(msg, "amount"), Some(r#"{"denom": "uatom"}"#));
+        assert_eq!(get_field(msg, "denom"), None);
+        assert_eq!(get_field(br#"{"type\u0041": 1}"#, "typeA"), Some("1"));
+        assert_eq!(get_field(b"[1]", "0"), None);
+        assert_eq!(get_field(b"", "type"), None);
+        assert_eq!(get_field(br#"{"a": 1 "type": 2}"#, "type"), None);
+        assert_eq!(get_field(br#"{"type": tru}"#, "type"), None);
+    }
+}
@@ -0,0 +1,676 @@
+use alloc::collections::VecDeque;
+use alloc::vec::Vec;
+use core::marker::PhantomData;
+
+use serde::de::{Deserialize, DeserializeOwned};
+
+use crate::de::read::{ChunkRead, IoRead, ReadFn, Source};
+use crate::de::{
+    from_read, from_read_prefix, Deserializer, Error, Options, Result, BYTE_ORDER_MARK,
+};
+
+/// Outcome of feeding a chunk of input to a [`PushDeserializer`]
+#[derive(Debug, PartialEq)]
+pub enum Feed<T> {
+    /// The value is not complete yet. Push more data or call [`PushDeserializer::finish`] at the
+    /// end of the input.
+    NeedMoreData,
+    /// A complete value was deserialized
+    Value(T),
+}
+
+/// Deserializer that is fed the input in chunks as they arrive, e.g. from a websocket
+///
+/// Every chunk is scanned once to find the end of the current JSON value, so the work done per
+/// chunk only depends on its size. The chunks are kept as they were pushed, without joining them,
+/// until the value is complete, since a `Deserialize` impl can't be paused in the middle of a
+/// value to wait for the next chunk. The value is then parsed from the chunks, and every chunk is
+/// freed as soon as the parser is past it, so the value and its deserialized form are not both
+/// held in memory in full. Data after a value is kept for the next one, so a stream of
+/// whitespace-separated values can be read by pushing until each value is returned.
+///
+/// A top-level number, `true`, `false` or `null` is only known to be complete once it is
+/// followed by whitespace or the input is finished.
+pub struct PushDeserializer<T> {
+    options: Options,
+    chunks: VecDeque<Vec<u8>>,
+    /// Total length of `chunks`
+    len: usize,
+    /// Index of the first chunk and offset of the first byte in it that were not scanned yet
+    scanned: (usize, usize),
+    scanner: Scanner,
+    output: PhantomData<T>,
+}
+
+/// [`Source`] that hands over the chunks buffered by a [`PushDeserializer`] one by one, so that
+/// each one is dropped once it is parsed
+struct Chunks(VecDeque<Vec<u8>>);
+
+impl Source for Chunks {
+    fn fill(&mut self, buf: &mut Vec<u8>) -> Result<bool> {
+        match self.0.pop_front() {
+            // the chunk replaces the buffer if all of it was parsed, which frees the last chunk
+            Some(chunk) if buf.is_empty() => *buf = chunk,
+            Some(chunk) => buf.extend_from_slice(&chunk),
+            None => return Ok(false),
+        }
+        Ok(true)
+    }
+}
+
+/// Resumable state of the search for the end of a JSON value
+#[derive(Default)]
+pub(super) struct Scanner {
+    /// Whether comments are skipped, see [`Options::allow_comments`]
+    comments: bool,
+    /// Whether strings may be in single quotes
+    single_quotes: bool,
+    /// Number of bytes of a leading byte order mark that are still skipped, see
+    /// [`Options::allow_byte_order_mark`]
+    byte_order_mark: usize,
+    depth: usize,
+    in_string: bool,
+    /// Quote that ends the current string
+    quote: u8,
+    escaped: bool,
+    in_scalar: bool,
+    comment: Comment,
+}
+
+/// Position in a comment
+#[derive(Clone, Copy, PartialEq)]
+enum Comment {
+    None,
+    /// After a `/` that may start a comment
+    Slash,
+    Line,
+    Block,
+    /// After a `*` in a block comment
+    BlockStar,
+}
+
+impl Default for Comment {
+    fn default() -> Self {
+        Comment::None
+    }
+}
+
+impl Scanner {
+    pub(super) fn new(options: &Options) -> Self {
+        Scanner {
+            comments: options.allow_comments,
+            single_quotes: options.allow_single_quotes,
+            byte_order_mark: if options.allow_byte_order_mark {
+                BYTE_ORDER_MARK.len()
+            } else {
+                0
+            },
+            ..Scanner::default()
+        }
+    }
+
+    /// Consumes `bytes` until the value ends and returns the number of bytes of them that belong
+    /// to it, or `None` if it didn't end yet
+    pub(super) fn find_end(&mut self, bytes: &[u8]) -> Option<usize> {
+        for (i, &c) in bytes.iter().enumerate() {
+            match self.eat(c) {
+                Some(true) => return Some(i + 1),
+                Some(false) => return Some(i),
+                None => {}
+            }
+        }
+        None
+    }
+
+    /// Consumes `c`, returning whether the value ended. If `c` ends the value without being
+    /// part of it (like the whitespace after a number), `Some(false)` is returned.
+    fn eat(&mut self, c: u8) -> Option<bool> {
+        if self.byte_order_mark > 0 {
+            let next = BYTE_ORDER_MARK[BYTE_ORDER_MARK.len() - self.byte_order_mark];
+            if c == next {
+                self.byte_order_mark -= 1;
+                return None;
+            }
+            // not a byte order mark, which is left for the deserializer to report
+            self.byte_order_mark = 0;
+        }
+        if self.in_string {
+            if self.escaped {
+                self.escaped = false;
+            } else if c == b'\\' {
+                self.escaped = true;
+            } else if c == self.quote {
+                self.in_string = false;
+                if self.depth == 0 {
+                    return Some(true);
+                }
+            }
+            return None;
+        }
+        match self.comment {
+            Comment::None => {}
+            Comment::Slash => {
+                self.comment = match c {
+                    b'/' => Comment::Line,
+                    b'*' => Comment::Block,
+                    // not a comment, which is left for the deserializer to report
+                    _ if self.depth == 0 => return Some(true),
+                    _ => Comment::None,
+                };
+                return None;
+            }
+            Comment::Line => {
+                if c == b'\n' {
+                    self.comment = Comment::None;
+                }
+                return None;
+            }
+            Comment::Block | Comment::BlockStar => {
+                self.comment = match (self.comment, c) {
+                    (Comment::BlockStar, b'/') => Comment::None,
+                    (_, b'*') => Comment::BlockStar,
+                    _ => Comment::Block,
+                };
+                return None;
+            }
+        }
+        if self.in_scalar {
+            return match c {
+                b' ' | b'\n' | b'\t' | b'\r' | b'[' | b']' | b'{' | b'}' | b',' | b':' | b'"' => {
+                    Some(false)
+                }
+                b'/' if self.comments => Some(false),
+                b'\'' if self.single_quotes => Some(false),
+                _ => None,
+            };
+        }
+        match c {
+            b' ' | b'\n' | b'\t' | b'\r' => None,
+            b'/' if self.comments => {
+                self.comment = Comment::Slash;
+                None
+            }
+            b'"' | b'\'' if c == b'"' || self.single_quotes => {
+                self.in_string = true;
+                self.quote = c;
+                None
+            }
+            b'[' | b'{' => {
+                self.depth += 1;
+                None
+            }
+            b']' | b'}' => {
+                // an unmatched closing bracket is left for the deserializer to report
+                self.depth = self.depth.saturating_sub(1);
+                if self.depth == 0 {
+                    Some(true)
+                } else {
+                    None
+                }
+            }
+            _ if self.depth > 0 => None,
+            b',' | b':' => Some(true),
+            _ => {
+                self.in_scalar = true;
+                None
+            }
+        }
+    }
+}
+
+impl<T> PushDeserializer<T>
+where
+    T: DeserializeOwned,
+{
+    /// Creates a deserializer that doesn't have any input yet
+    pub fn new() -> Self {
+        Self::with_options(&Options::default())
+    }
+
+    /// Creates a deserializer that doesn't have any input yet and parses using `options`
+    pub fn with_options(options: &Options) -> Self {
+        PushDeserializer {
+            options: options.clone(),
+            chunks: VecDeque::new(),
+            len: 0,
+            scanned: (0, 0),
+            scanner: Scanner::new(options),
+            output: PhantomData,
+        }
+    }
+
+    /// Appends `chunk` to the input and deserializes the current value if it is complete
+    ///
+    /// Pushing an empty chunk returns the next value if one is already buffered.
+    pub fn push(&mut self, chunk: &[u8]) -> Result<Feed<T>> {
+        if !chunk.is_empty() {
+            self.chunks.push_back(chunk.to_vec());
+            self.len += chunk.len();
+        }
+        let (mut index, mut offset) = self.scanned;
+        while let Some(chunk) = self.chunks.get(index) {
+            if let Some(len) = self.scanner.find_end(&chunk[offset..]) {
+                return self.take(index, offset + len).map(Feed::Value);
+            }
+            index += 1;
+            offset = 0;
+        }
+        self.scanned = (index, offset);
+        if let Some(max_input_len) = self.options.max_input_len {
+            if self.len > max_input_len {
+                // the value can never be complete, so start over with the next push
+                self.chunks.clear();
+                self.len = 0;
+                self.reset();
+                return Err(Error::InputTooLong);
+            }
+        }
+        Ok(Feed::NeedMoreData)
+    }
+
+    /// Signals the end of the input and deserializes what is left of it, if anything
+    pub fn finish(self) -> Result<Option<T>> {
+        let read = IoRead::new(Chunks(self.chunks), self.options.max_input_len);
+        let mut de = Deserializer::with_read(read, &self.options);
+        let result = if de.parse_whitespace().is_none() {
+            de.end().map(|()| None)
+        } else {
+            Deserialize::deserialize(&mut de).and_then(|value| de.end().map(|()| Some(value)))
+        };
+        de.check_read(result)
+    }
+
+    /// Deserializes the value that ends at `end` in the chunk at `index` and removes it from the
+    /// buffered chunks
+    fn take(&mut self, index: usize, end: usize) -> Result<T> {
+        let mut rest = self.chunks.split_off(index + 1);
+        let tail = self.chunks[index].split_off(end);
+        if !tail.is_empty() {
+            rest.push_front(tail);
+        }
+        let value = core::mem::replace(&mut self.chunks, rest);
+        self.len = self.chunks.iter().map(Vec::len).sum();
+        let read = IoRead::new(Chunks(value), self.options.max_input_len);
+        let result = from_read(read, &self.options);
+        // a byte order mark can only start the input, not the values after the first one
+        self.options.allow_byte_order_mark = false;
+        self.reset();
+        result
+    }
+
+    /// Starts scanning for the next value at the start of the buffered chunks
+    fn reset(&mut self) {
+        self.scanned = (0, 0);
+        self.scanner = Scanner::new(&self.options);
+    }
+}
+
+impl<T> Default for PushDeserializer<T>
+where
+    T: DeserializeOwned,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Progress of reading a value into a buffer provided by the caller, for the readers that return
+/// the bytes after the value, like [`from_embedded_io`](crate::de::from_embedded_io)
+#[cfg(feature = "async")]
+pub(super) struct FillBuffer {
+    scanner: Scanner,
+    filled: usize,
+    limit: usize,
+}
+
+#[cfg(feature = "async")]
+impl FillBuffer {
+    pub(super) fn new(buf: &[u8], options: &Options) -> Self {
+        FillBuffer {
+            scanner: Scanner::new(options),
+            filled: 0,
+            limit: options
+                .max_input_len
+                .map_or(buf.len(), |max_input_len| max_input_len.min(buf.len())),
+        }
+    }
+
+    /// Returns the part of `buf` to read into next, failing if the value doesn't fit
+    pub(super) fn unfilled<'b>(&self, buf: &'b mut [u8]) -> Result<&'b mut [u8]> {
+        if self.filled == self.limit {
+            return Err(Error::InputTooLong);
+        }
+        Ok(&mut buf[self.filled..self.limit])
+    }
+
+    /// Accounts for `len` bytes that were read into the start of [`FillBuffer::unfilled`], with 0
+    /// meaning the end of the source, and returns the length of the value once it is complete
+    pub(super) fn fill(&mut self, buf: &[u8], len: usize) -> Option<usize> {
+        let start = self.filled;
+        self.filled += len;
+        if len == 0 {
+            return Some(start);
+        }
+        self.scanner
+            .find_end(&buf[start..self.filled])
+            .map(|value_len| start + value_len)
+    }
+
+    /// Deserializes the value in `buf[..end]` and returns it with the bytes that were read after
+    /// it
+    pub(super) fn deserialize<'a, T>(
+        &self,
+        buf: &'a [u8],
+        end: usize,
+        options: &Options,
+    ) -> Result<(T, &'a [u8])>
+    where
+        T: serde::Deserialize<'a>,
+    {
+        let value = crate::de::from_slice_with_options(&buf[..end], options)?;
+        Ok((value, &buf[end..self.filled]))
+    }
+}
+
+/// Size of the chunks read by [`from_read_fn`]
+const READ_CHUNK_SIZE: usize = 64;
+
+/// Deserializes an instance of type `T` from a byte source that is read in chunks until the value
+/// is complete
+///
+/// `read` has the shape of the `read` method of `embedded_io::Read` and `std::io::Read`: it fills
+/// the start of the given buffer and returns the number of bytes written, with 0 meaning the
+/// end of the input. This allows parsing from UARTs or flash without `std`, e.g. with
+/// `from_read_fn(|buf| uart.read(buf))`. Errors of `read` are reported as [`Error::Read`].
+///
+/// The value is parsed while it is read, and only the input that is still needed is kept in
+/// memory. Reading stops as soon as the value is complete, which doesn't require the source to
+/// end. Bytes after the value that were read as part of the last chunk are discarded. With the
+/// `embedded-io` feature, `from_embedded_io` reads from an
+/// `embedded_io::Read` into a caller-provided buffer instead and returns those bytes.
+pub fn from_read_fn<T, F, E>(read: F) -> Result<T>
+where
+    T: DeserializeOwned,
+    F: FnMut(&mut [u8]) -> core::result::Result<usize, E>,
+{
+    from_read_fn_with_options(read, &Options::default())
+}
+
+/// Deserializes an instance of type `T` from a byte source like [`from_read_fn`], using the given
+/// [`Options`]
+///
+/// With [`Options::max_input_len`], reading stops as soon as the value turns out to be too long.
+pub fn from_read_fn_with_options<T, F, E>(mut read: F, options: &Options) -> Result<T>
+where
+    T: DeserializeOwned,
+    F: FnMut(&mut [u8]) -> core::result::Result<usize, E>,
+{
+    let read = ReadFn::new(
+        move |buf: &mut [u8]| read(buf).map_err(|_| Error::Read),
+        READ_CHUNK_SIZE,
+    );
+    from_read_prefix(IoRead::new(read, options.max_input_len), options).map(|(value, _)| value)
+}
+
+/// Deserializes an instance of type `T` from JSON text that is split across several buffers,
+/// e.g. the two halves of a ring buffer
+///
+/// The value is parsed across the chunks without joining them. Strings are borrowed from the
+/// chunks, and only the parts of the input that cross the boundary between two chunks are
+/// copied, so a borrowed `&str` field can't hold a string that crosses a boundary, just like one
+/// with an escape sequence.
+pub fn from_chunks<'a, T>(chunks: &[&'a [u8]]) -> Result<T>
+where
+    T: Deserialize<'a>,
+{
+    from_read(ChunkRead::new(chunks), &Options::default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        from_chunks, from_read_fn, from_read_fn_with_options, Chunks, Feed, PushDeserializer,
+    };
+    use crate::de::read::Source;
+    use crate::de::{Error, Options};
+    use serde_derive::Deserialize;
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Coin {
+        denom: String,
+        amount: u64,
+    }
+
+    #[test]
+    fn push_chunks() {
+        let mut de = PushDeserializer::<Coin>::new();
+        assert_eq!(de.push(br#"{ "denom": "u"#), Ok(Feed::NeedMoreData));
+        assert_eq!(de.push(br#"atom\"}", "am"#), Ok(Feed::NeedMoreData));
+        assert_eq!(de.push(br#"ount": 1 "#), Ok(Feed::NeedMoreData));
+        assert_eq!(
+            de.push(br#"} { "denom": "ujuno", "amount": 2 }"#),
+            Ok(Feed::Value(Coin {
+                denom: "uatom\"}".to_string(),
+                amount: 1
+            }))
+        );
+        assert_eq!(
+            de.push(b""),
+            Ok(Feed::Value(Coin {
+                denom: "ujuno".to_string(),
+                amount: 2
+            }))
+        );
+        assert_eq!(de.push(b"\n"), Ok(Feed::NeedMoreData));
+        assert_eq!(de.finish(), Ok(None));
+
+        // scalars are complete at the next whitespace or at the end of the input
+        let mut de = PushDeserializer::<u64>::new();
+        assert_eq!(de.push(b"12"), Ok(Feed::NeedMoreData));
+        assert_eq!(de.push(b"34 5"), Ok(Feed::Value(1234)));
+        assert_eq!(de.push(b"6"), Ok(Feed::NeedMoreData));
+        assert_eq!(de.finish(), Ok(Some(56)));
+
+        let mut de = PushDeserializer::<Vec<Vec<u8>>>::new();
+        let results: Vec<_> = [&b"[[1"[..], b"], [", b"2]", b"]"]
+            .iter()
+            .map(|chunk| de.push(chunk))
+            .collect();
+        assert_eq!(
+            results,
+            vec![
+                Ok(Feed::NeedMoreData),
+                Ok(Feed::NeedMoreData),
+                Ok(Feed::NeedMoreData),
+                Ok(Feed::Value(vec![vec![1], vec![2]]))
+            ]
+        );
+        assert_eq!(de.finish(), Ok(None));
+    }
+
+    #[test]
+    fn push_chunks_moved() {
+        let chunk = b"[1,".to_vec();
+        let ptr = chunk.as_ptr();
+        let mut source = Chunks(vec![chunk, b" 2]".to_vec()].into_iter().collect());
+        let mut buf = Vec::new();
+        // a chunk is moved into an empty buffer instead of copied
+        assert_eq!(source.fill(&mut buf), Ok(true));
+        assert_eq!(buf.as_ptr(), ptr);
+        assert_eq!(source.fill(&mut buf), Ok(true));
+        assert_eq!(buf, b"[1, 2]");
+        assert_eq!(source.fill(&mut buf), Ok(false));
+    }
+
+    #[test]
+    fn push_errors() {
+        let mut de = PushDeserializer::<Coin>::new();
+        assert_eq!(
+            de.push(br#"{ "denom": "uatom" }"#),
+            Err(Error::MissingField("amount"))
+        );
+        // the next value can still be read
+        assert_eq!(
+            de.push(br#"{ "denom": "uatom", "amount": 1 }"#),
+            Ok(Feed::Value(Coin {
+                denom: "uatom".to_string(),
+                amount: 1
+            }))
+        );
+
+        let mut de = PushDeserializer::<Coin>::new();
+        assert_eq!(de.push(br#"{ "denom": "#), Ok(Feed::NeedMoreData));
+        assert_eq!(de.finish(), Err(Error::EofWhileParsingValue));
+
+        let mut de = PushDeserializer::<Vec<u8>>::new();
+        assert_eq!(de.push(b"]"), Err(Error::ExpectedSomeValue(b']')));
+
+        // an unfinished value that exceeds the limit is dropped
+        let mut de =
+            PushDeserializer::<Vec<u8>>::with_options(&Options::default().max_input_len(4));
+        assert_eq!(de.push(b"[1, 2"), Err(Error::InputTooLong));
+        assert_eq!(de.push(b"[3]"), Ok(Feed::Value(vec![3])));
+    }
+
+    #[test]
+    fn read_fn() {
+        // reads at most 5 bytes at a time and never ends
+        let source = |input: &'static [u8]| {
+            let mut rest = input;
+            move |buf: &mut [u8]| -> Result<usize, ()> {
+                let len = buf.len().min(5).min(rest.len());
+                buf[..len].copy_from_slice(&rest[..len]);
+                rest = &rest[len..];
+                if len == 0 {
+                    Err(())
+                } else {
+                    Ok(len)
+                }
+            }
+        };
+        assert_eq!(
+            from_read_fn(source(br#"{ "denom": "uatom", "amount": 1 }"#)),
+            Ok(Coin {
+                denom: "uatom".to_string(),
+                amount: 1
+            })
+        );
+        assert_eq!(
+            from_read_fn::<Coin, _, _>(source(br#"{ "denom": "uatom""#)),
+            Err(Error::Read)
+        );
+
+        let mut input: &[u8] = b" 42";
+        assert_eq!(
+            from_read_fn(|buf| std::io::Read::read(&mut input, buf)),
+            Ok(42u8)
+        );
+        let mut input: &[u8] = b"";
+        assert_eq!(
+            from_read_fn::<u8, _, _>(|buf| std::io::Read::read(&mut input, buf)),
+            Err(Error::EofWhileParsingValue)
+        );
+
+        let options = Options::default().max_input_len(8);
+        assert_eq!(
+            from_read_fn_with_options::<Vec<u8>, _, _>(source(b"[1, 2, 3, 4]"), &options),
+            Err(Error::InputTooLong)
+        );
+    }
+
+    #[test]
+    fn chunks() {
+        let coin = Coin {
+            denom: "uatom".to_string(),
+            amount: 1,
+        };
+        let input = br#"{ "denom": "uatom", "amount": 1 } "#;
+        assert_eq!(from_chunks(&[&input[..]]), Ok(coin));
+        for split in 0..input.len() {
+            let (head, tail) = input.split_at(split);
+            assert_eq!(
+                from_chunks(&[head, tail]),
+                Ok(Coin {
+                    denom: "uatom".to_string(),
+                    amount: 1
+                })
+            );
+        }
+        assert_eq!(from_chunks(&[b"1", b"2", b"", b"3"]), Ok(123u32));
+
+        // strings are borrowed unless they cross a chunk
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Denom<'a> {
+            denom: &'a str,
+        }
+        let input = br#"{ "denom": "uatom" }"#;
+        let (head, tail) = input.split_at(9);
+        assert_eq!(from_chunks(&[head, tail]), Ok(Denom { denom: "uatom" }));
+        let (head, tail) = input.split_at(14);
+        assert!(from_chunks::<Denom<'_>>(&[head, tail]).is_err());
+        assert_eq!(
+            from_chunks::<(String, String)>(&[b"[\"ua", b"tom\", \"a\\", b"u0062\"]"]),
+            Ok(("uatom".to_string(), "ab".to_string()))
+        );
+
+        assert_eq!(from_chunks::<u32>(&[]), Err(Error::EofWhileParsingValue));
+        assert_eq!(
+            from_chunks::<u32>(&[b" ", b" "]),
+            Err(Error::EofWhileParsingValue)
+        );
+        assert_eq!(
+            from_chunks::<Vec<u8>>(&[b"[1", b", 2"]),
+            Err(Error::EofWhileParsingList)
+        );
+        assert_eq!(
+            from_chunks::<Vec<u8>>(&[b"[1]", b" [2]"]),
+            Err(Error::TrailingCharacters)
+        );
+        assert_eq!(
+            from_chunks::<u8>(&[b"1 ", b"2"]),
+            Err(Error::TrailingCharacters)
+        );
+    }
+
+    #[test]
+    fn push_comments() {
+        use crate::de::Options;
+
+        let options = Options::default().allow_comments(true);
+        let mut de = PushDeserializer::<Vec<u8>>::with_options(&options);
+        assert_eq!(de.push(b"/* ] */ [1, /"), Ok(Feed::NeedMoreData));
+        assert_eq!(de.push(b"/ ]\n 2 /* ] *"), Ok(Feed::NeedMoreData));
+        assert_eq!(de.push(b"*/ ] 3"), Ok(Feed::Value(vec![1, 2])));
+        assert_eq!(
+            de.finish(),
+            Err(Error::InvalidType(crate::de::JsonType::Number))
+        );
+
+        let mut de = PushDeserializer::<u8>::with_options(&options);
+        assert_eq!(de.push(b"7// seven"), Ok(Feed::Value(7)));
+        assert_eq!(de.push(b"\n"), Ok(Feed::NeedMoreData));
+        assert_eq!(de.finish(), Ok(None));
+
+        let mut de = PushDeserializer::<u8>::with_options(&options);
+        assert_eq!(de.push(b"7 /* seven"), Ok(Feed::Value(7)));
+        assert_eq!(de.finish(), Err(Error::EofWhileParsingComment));
+    }
+
+    #[test]
+    fn push_single_quotes() {
+        use crate::de::Options;
+
+        let options = Options {
+            allow_single_quotes: true,
+            ..Options::default()
+        };
+        let mut de = PushDeserializer::<String>::with_options(&options);
+        assert_eq!(de.push(b"'a\"b"), Ok(Feed::NeedMoreData));
+        assert_eq!(
+            de.push(b"\\'c' \"d'\" "),
+            Ok(Feed::Value("a\"b'c".to_string()))
+        );
+        assert_eq!(de.push(b""), Ok(Feed::Value("d'".to_string())));
+        assert_eq!(de.finish(), Ok(None));
+    }
+}
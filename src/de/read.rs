@@ -0,0 +1,620 @@
+//! Sources of the JSON text that is deserialized
+//!
+//! The parser pulls its input byte by byte from a [`Read`]. Slices are read in place, so that
+//! strings and raw values can be borrowed from them. Streams go through an [`IoRead`], which
+//! reads them in chunks and drops the bytes the parser is done with whenever it reads the next
+//! one. Input in several slices is read by a [`ChunkRead`], which borrows from the slices too and
+//! only copies what crosses the boundary between two of them.
+//!
+//! Now and then the parser needs input again that it has already consumed, e.g. the content of a
+//! string once its end is found, or a number that is parsed once more as a float. It marks the
+//! start of such a part with [`Read::checkpoint`], which keeps the input from there in memory
+//! until the checkpoint is ended.
+
+use alloc::vec::Vec;
+use core::ops::Deref;
+
+use crate::de::{Error, Result};
+
+/// Source of the JSON text for the deserializer
+///
+/// An error of the source ends the input like the end of a stream does. It is returned by
+/// [`Read::take_error`] afterwards, since it explains the error the parser runs into then.
+pub trait Read<'de> {
+    /// Returns the next byte without consuming it, or `None` at the end of the input
+    fn peek(&mut self) -> Option<u8>;
+
+    /// Consumes the byte that was returned by the last [`Read::peek`]
+    fn discard(&mut self);
+
+    /// Consumes and returns the next byte
+    fn next(&mut self) -> Option<u8> {
+        let c = self.peek()?;
+        self.discard();
+        Some(c)
+    }
+
+    /// Returns the number of bytes consumed so far
+    fn position(&self) -> usize;
+
+    /// Keeps the input from the current position on, which is returned
+    ///
+    /// The parser can then go back to the position with [`Read::rewind`] or get the bytes read
+    /// since with [`Read::bytes`]. Checkpoints may be nested and are ended by `rewind` or
+    /// [`Read::release`], the innermost first. One that is not ended, e.g. because parsing
+    /// failed, only keeps more of the input in memory.
+    fn checkpoint(&mut self) -> usize;
+
+    /// Goes back to `checkpoint` and ends it
+    fn rewind(&mut self, checkpoint: usize);
+
+    /// Ends the innermost checkpoint without going back. Its bytes can still be retrieved until
+    /// the next [`Read::peek`].
+    fn release(&mut self);
+
+    /// Returns the input from `start` to `end`, which must lie between a checkpoint that was not
+    /// ended before the last [`Read::peek`] and the current position
+    fn bytes(&mut self, start: usize, end: usize) -> Reference<'de, '_>;
+
+    /// Returns the error that ended the input early, if any
+    fn take_error(&mut self) -> Option<Error>;
+}
+
+/// Bytes of the input, borrowed from it if the source allows it
+#[derive(Clone, Copy)]
+pub enum Reference<'b, 'c> {
+    /// Borrowed from the input, which outlives the deserializer
+    Borrowed(&'b [u8]),
+    /// Copied to a buffer of the source, which may be reused once it reads on
+    Copied(&'c [u8]),
+}
+
+impl Deref for Reference<'_, '_> {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        match *self {
+            Reference::Borrowed(bytes) => bytes,
+            Reference::Copied(bytes) => bytes,
+        }
+    }
+}
+
+/// Checkpoints that are not ended yet
+#[derive(Default)]
+struct Keep {
+    /// Number of them
+    depth: usize,
+    /// Position of the outermost one
+    from: usize,
+}
+
+impl Keep {
+    fn checkpoint(&mut self, pos: usize) -> usize {
+        if self.depth == 0 {
+            self.from = pos;
+        }
+        self.depth += 1;
+        pos
+    }
+
+    fn release(&mut self) {
+        self.depth = self.depth.saturating_sub(1);
+    }
+
+    /// Returns the position from which on the input is still needed, given the current position
+    fn start(&self, pos: usize) -> usize {
+        if self.depth > 0 {
+            self.from
+        } else {
+            pos
+        }
+    }
+}
+
+/// Fails if `len` bytes of input are more than `max_input_len`
+pub(crate) fn check_len(len: usize, max_input_len: Option<usize>) -> Result<()> {
+    match max_input_len {
+        Some(max_input_len) if len > max_input_len => Err(Error::InputTooLong),
+        _ => Ok(()),
+    }
+}
+
+/// Input in a byte slice
+pub struct SliceRead<'a> {
+    pub(crate) slice: &'a [u8],
+    pub(crate) index: usize,
+}
+
+impl<'a> SliceRead<'a> {
+    pub fn new(slice: &'a [u8]) -> Self {
+        SliceRead { slice, index: 0 }
+    }
+}
+
+impl<'a> Read<'a> for SliceRead<'a> {
+    #[inline]
+    fn peek(&mut self) -> Option<u8> {
+        self.slice.get(self.index).cloned()
+    }
+
+    #[inline]
+    fn discard(&mut self) {
+        self.index += 1;
+    }
+
+    fn position(&self) -> usize {
+        self.index
+    }
+
+    fn checkpoint(&mut self) -> usize {
+        self.index
+    }
+
+    fn rewind(&mut self, checkpoint: usize) {
+        self.index = checkpoint;
+    }
+
+    fn release(&mut self) {}
+
+    fn bytes(&mut self, start: usize, end: usize) -> Reference<'a, '_> {
+        Reference::Borrowed(&self.slice[start..end])
+    }
+
+    fn take_error(&mut self) -> Option<Error> {
+        None
+    }
+}
+
+/// Produces the input of an [`IoRead`] in chunks
+pub trait Source {
+    /// Appends the next chunk of the input to `buf`, or returns `Ok(false)` at its end
+    fn fill(&mut self, buf: &mut Vec<u8>) -> Result<bool>;
+}
+
+/// [`Source`] that reads chunks of up to `chunk_len` bytes with a function like
+/// `std::io::Read::read`, which returns 0 at the end of the input
+pub struct ReadFn<F> {
+    read: F,
+    chunk_len: usize,
+}
+
+impl<F> ReadFn<F>
+where
+    F: FnMut(&mut [u8]) -> Result<usize>,
+{
+    pub fn new(read: F, chunk_len: usize) -> Self {
+        ReadFn { read, chunk_len }
+    }
+}
+
+impl<F> Source for ReadFn<F>
+where
+    F: FnMut(&mut [u8]) -> Result<usize>,
+{
+    fn fill(&mut self, buf: &mut Vec<u8>) -> Result<bool> {
+        let len = buf.len();
+        buf.resize(len + self.chunk_len, 0);
+        let result = (self.read)(&mut buf[len..]);
+        let read = match result {
+            Ok(read) => read.min(self.chunk_len),
+            Err(_) => 0,
+        };
+        buf.truncate(len + read);
+        result.map(|_| read > 0)
+    }
+}
+
+/// Input from a [`Source`] that is read in chunks into a buffer
+///
+/// Whenever a chunk is read, the bytes before the outermost checkpoint, or before the current
+/// position if there is none, are dropped from the buffer, so it only grows with the parts of the
+/// input that the parser needs in one piece, like a long string. Nothing is borrowed from it.
+pub struct IoRead<S> {
+    source: S,
+    buf: Vec<u8>,
+    /// Position of `buf[0]` in the input
+    base: usize,
+    pos: usize,
+    keep: Keep,
+    max_input_len: Option<usize>,
+    /// Set once the source reached its end or failed
+    eof: bool,
+    /// Set when the source produced more than `max_input_len` bytes. The extra bytes are cut off,
+    /// and reading past the limit fails with [`Error::InputTooLong`].
+    too_long: bool,
+    error: Option<Error>,
+}
+
+impl<S: Source> IoRead<S> {
+    pub fn new(source: S, max_input_len: Option<usize>) -> Self {
+        IoRead {
+            source,
+            buf: Vec::new(),
+            base: 0,
+            pos: 0,
+            keep: Keep::default(),
+            max_input_len,
+            eof: false,
+            too_long: false,
+            error: None,
+        }
+    }
+
+    /// Reads chunks, after dropping the bytes that are no longer needed, until there is a byte at
+    /// the current position. Returns `false` at the end of the input.
+    #[cold]
+    fn fill(&mut self) -> bool {
+        while !self.eof {
+            if self.too_long {
+                self.error = Some(Error::InputTooLong);
+                self.eof = true;
+                break;
+            }
+            let start = self.keep.start(self.pos);
+            if start > self.base {
+                self.buf.drain(..start - self.base);
+                self.base = start;
+            }
+            match self.source.fill(&mut self.buf) {
+                Ok(true) => {}
+                Ok(false) => self.eof = true,
+                Err(error) => {
+                    self.error = Some(error);
+                    self.eof = true;
+                }
+            }
+            if let Some(max_input_len) = self.max_input_len {
+                if self.base + self.buf.len() > max_input_len {
+                    self.buf.truncate(max_input_len - self.base);
+                    self.too_long = true;
+                }
+            }
+            if self.pos < self.base + self.buf.len() {
+                return true;
+            }
+        }
+        false
+    }
+}
+
+impl<'de, S: Source> Read<'de> for IoRead<S> {
+    #[inline]
+    fn peek(&mut self) -> Option<u8> {
+        if self.pos - self.base == self.buf.len() && !self.fill() {
+            return None;
+        }
+        Some(self.buf[self.pos - self.base])
+    }
+
+    #[inline]
+    fn discard(&mut self) {
+        self.pos += 1;
+    }
+
+    fn position(&self) -> usize {
+        self.pos
+    }
+
+    fn checkpoint(&mut self) -> usize {
+        self.keep.checkpoint(self.pos)
+    }
+
+    fn rewind(&mut self, checkpoint: usize) {
+        self.pos = checkpoint;
+        self.keep.release();
+    }
+
+    fn release(&mut self) {
+        self.keep.release();
+    }
+
+    fn bytes(&mut self, start: usize, end: usize) -> Reference<'de, '_> {
+        Reference::Copied(&self.buf[start - self.base..end - self.base])
+    }
+
+    fn take_error(&mut self) -> Option<Error> {
+        self.error.take()
+    }
+}
+
+/// Input in several slices, e.g. the two halves of a ring buffer
+///
+/// Strings are borrowed from the slices. What the parser needs in one piece, like a string, is
+/// only copied if it crosses the boundary between two slices.
+pub struct ChunkRead<'a, 'c> {
+    chunk: &'a [u8],
+    /// Slices after `chunk`
+    rest: &'c [&'a [u8]],
+    /// Position of `chunk[0]` in the input
+    chunk_base: usize,
+    pos: usize,
+    keep: Keep,
+    /// Copy of the kept input from before `chunk`, which may extend into it
+    carry: Vec<u8>,
+    /// Position of `carry[0]` in the input
+    carry_base: usize,
+}
+
+impl<'a, 'c> ChunkRead<'a, 'c> {
+    pub fn new(chunks: &'c [&'a [u8]]) -> Self {
+        ChunkRead {
+            chunk: &[],
+            rest: chunks,
+            chunk_base: 0,
+            pos: 0,
+            keep: Keep::default(),
+            carry: Vec::new(),
+            carry_base: 0,
+        }
+    }
+
+    /// Before moving on from `chunk`, copies the part of the input that is kept to `carry`
+    fn carry_kept(&mut self) {
+        if self.keep.depth == 0 {
+            self.carry.clear();
+            return;
+        }
+        let from = self.keep.from;
+        if from >= self.chunk_base || self.carry.is_empty() {
+            self.carry.clear();
+            self.carry_base = from;
+        } else if from > self.carry_base {
+            self.carry.drain(..from - self.carry_base);
+            self.carry_base = from;
+        }
+        let copied = self.carry_base + self.carry.len();
+        self.carry
+            .extend_from_slice(&self.chunk[copied - self.chunk_base..]);
+    }
+}
+
+impl<'a> Read<'a> for ChunkRead<'a, '_> {
+    fn peek(&mut self) -> Option<u8> {
+        if self.pos < self.chunk_base {
+            // after rewinding to a checkpoint in an earlier slice
+            return Some(self.carry[self.pos - self.carry_base]);
+        }
+        while self.pos - self.chunk_base == self.chunk.len() {
+            let (&next, rest) = self.rest.split_first()?;
+            self.carry_kept();
+            self.chunk_base += self.chunk.len();
+            self.chunk = next;
+            self.rest = rest;
+        }
+        Some(self.chunk[self.pos - self.chunk_base])
+    }
+
+    fn discard(&mut self) {
+        self.pos += 1;
+    }
+
+    fn position(&self) -> usize {
+        self.pos
+    }
+
+    fn checkpoint(&mut self) -> usize {
+        self.keep.checkpoint(self.pos)
+    }
+
+    fn rewind(&mut self, checkpoint: usize) {
+        self.pos = checkpoint;
+        self.keep.release();
+    }
+
+    fn release(&mut self) {
+        self.keep.release();
+    }
+
+    fn bytes(&mut self, start: usize, end: usize) -> Reference<'a, '_> {
+        if start >= self.chunk_base {
+            return Reference::Borrowed(
+                &self.chunk[start - self.chunk_base..end - self.chunk_base],
+            );
+        }
+        let copied = self.carry_base + self.carry.len();
+        if end > copied {
+            self.carry
+                .extend_from_slice(&self.chunk[copied - self.chunk_base..end - self.chunk_base]);
+        }
+        Reference::Copied(&self.carry[start - self.carry_base..end - self.carry_base])
+    }
+
+    fn take_error(&mut self) -> Option<Error> {
+        None
+    }
+}
+
+/// Input that is read into a buffer provided by the caller, which strings are borrowed from
+///
+/// Nothing is dropped from the buffer, so the value has to fit into it.
+#[cfg(feature = "embedded-io")]
+pub struct BufferRead<'a, F> {
+    read: F,
+    /// Start of the buffer, which is borrowed mutably for `'a`. Its first `filled` bytes are only
+    /// read from, through shared references that live for `'a`, and the rest is only written to.
+    buf: *mut u8,
+    /// Number of bytes of the buffer that may be filled
+    limit: usize,
+    filled: usize,
+    pos: usize,
+    eof: bool,
+    error: Option<Error>,
+    marker: core::marker::PhantomData<&'a mut [u8]>,
+}
+
+#[cfg(feature = "embedded-io")]
+impl<'a, F> BufferRead<'a, F>
+where
+    F: FnMut(&mut [u8]) -> Result<usize>,
+{
+    /// Creates a reader that fills up to `limit` bytes of `buf` with `read`
+    pub fn new(buf: &'a mut [u8], limit: usize, read: F) -> Self {
+        BufferRead {
+            read,
+            buf: buf.as_mut_ptr(),
+            limit: limit.min(buf.len()),
+            filled: 0,
+            pos: 0,
+            eof: false,
+            error: None,
+            marker: core::marker::PhantomData,
+        }
+    }
+
+    fn filled(&self) -> &'a [u8] {
+        // SAFETY: the first `filled` bytes of the buffer were written and are never written again
+        unsafe { core::slice::from_raw_parts(self.buf, self.filled) }
+    }
+
+    /// Returns the bytes that were read after the current position
+    pub fn rest(&self) -> &'a [u8] {
+        &self.filled()[self.pos..]
+    }
+
+    #[cold]
+    fn fill(&mut self) -> bool {
+        while !self.eof {
+            if self.filled == self.limit {
+                self.error = Some(Error::InputTooLong);
+                self.eof = true;
+                break;
+            }
+            // SAFETY: `filled..limit` is within the buffer, and no reference to it was handed out
+            let unfilled = unsafe {
+                core::slice::from_raw_parts_mut(self.buf.add(self.filled), self.limit - self.filled)
+            };
+            let len = unfilled.len();
+            match (self.read)(unfilled) {
+                Ok(0) => self.eof = true,
+                Ok(read) => {
+                    self.filled += read.min(len);
+                    return true;
+                }
+                Err(error) => {
+                    self.error = Some(error);
+                    self.eof = true;
+                }
+            }
+        }
+        false
+    }
+}
+
+#[cfg(feature = "embedded-io")]
+impl<'a, F> Read<'a> for BufferRead<'a, F>
+where
+    F: FnMut(&mut [u8]) -> Result<usize>,
+{
+    fn peek(&mut self) -> Option<u8> {
+        if self.pos == self.filled && !self.fill() {
+            return None;
+        }
+        Some(self.filled()[self.pos])
+    }
+
+    fn discard(&mut self) {
+        self.pos += 1;
+    }
+
+    fn position(&self) -> usize {
+        self.pos
+    }
+
+    fn checkpoint(&mut self) -> usize {
+        self.pos
+    }
+
+    fn rewind(&mut self, checkpoint: usize) {
+        self.pos = checkpoint;
+    }
+
+    fn release(&mut self) {}
+
+    fn bytes(&mut self, start: usize, end: usize) -> Reference<'a, '_> {
+        Reference::Borrowed(&self.filled()[start..end])
+    }
+
+    fn take_error(&mut self) -> Option<Error> {
+        self.error.take()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ChunkRead, IoRead, Read, ReadFn, Reference};
+    use crate::de::Error;
+
+    /// Reads all of `read`, taking the bytes of every string from a checkpoint at its quote
+    fn strings<'a>(read: &mut impl Read<'a>) -> Vec<(bool, Vec<u8>)> {
+        let mut strings = Vec::new();
+        while let Some(c) = read.next() {
+            if c == b'"' {
+                let start = read.checkpoint();
+                while read.next().map_or(false, |c| c != b'"') {}
+                let end = read.position() - 1;
+                read.release();
+                strings.push(match read.bytes(start, end) {
+                    Reference::Borrowed(bytes) => (true, bytes.to_vec()),
+                    Reference::Copied(bytes) => (false, bytes.to_vec()),
+                });
+            }
+        }
+        strings
+    }
+
+    #[test]
+    fn chunk_read() {
+        let chunks: [&[u8]; 4] = [b"[\"ab\", \"c", b"", b"d\", \"e", b"\"]"];
+        assert_eq!(
+            strings(&mut ChunkRead::new(&chunks)),
+            [
+                (true, b"ab".to_vec()),
+                (false, b"cd".to_vec()),
+                (false, b"e".to_vec())
+            ]
+        );
+
+        // going back into an earlier chunk
+        let chunks: [&[u8]; 3] = [b"12", b"34", b"5"];
+        let mut read = ChunkRead::new(&chunks);
+        read.next();
+        let start = read.checkpoint();
+        while read.next().is_some() {}
+        read.rewind(start);
+        assert_eq!(read.next(), Some(b'2'));
+        assert_eq!(read.next(), Some(b'3'));
+        let start = read.checkpoint();
+        read.next();
+        assert_eq!(&*read.bytes(start, read.position()), b"4");
+    }
+
+    #[test]
+    fn io_read() {
+        let source = |input: &'static [u8]| {
+            let mut rest = input;
+            ReadFn::new(
+                move |buf: &mut [u8]| {
+                    let len = buf.len().min(rest.len());
+                    buf[..len].copy_from_slice(&rest[..len]);
+                    rest = &rest[len..];
+                    Ok(len)
+                },
+                3,
+            )
+        };
+        let mut read = IoRead::new(source(br#"["ab", "cdefg"]"#), None);
+        assert_eq!(
+            strings(&mut read),
+            [(false, b"ab".to_vec()), (false, b"cdefg".to_vec())]
+        );
+        // only the last string, which was kept, and the chunk after it are left
+        assert!(read.buf.len() <= 5 + 3);
+        assert_eq!(read.take_error(), None);
+
+        let mut read = IoRead::new(source(b"[1, 2]"), Some(4));
+        while read.next().is_some() {}
+        assert_eq!(read.position(), 4);
+        assert_eq!(read.take_error(), Some(Error::InputTooLong));
+    }
+}
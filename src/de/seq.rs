@@ -1,19 +1,25 @@
 use serde::de;
 
-use crate::de::{Deserializer, Error, Result};
+use crate::de::read::Read;
+use crate::de::{Deserializer, Error, PathSegment, Result};
 
-pub(crate) struct SeqAccess<'a, 'b> {
+pub(crate) struct SeqAccess<'a, 'b, R> {
     first: bool,
-    de: &'a mut Deserializer<'b>,
+    index: usize,
+    de: &'a mut Deserializer<'b, R>,
 }
 
-impl<'a, 'b> SeqAccess<'a, 'b> {
-    pub fn new(de: &'a mut Deserializer<'b>) -> Self {
-        SeqAccess { de, first: true }
+impl<'a, 'b, R> SeqAccess<'a, 'b, R> {
+    pub fn new(de: &'a mut Deserializer<'b, R>) -> Self {
+        SeqAccess {
+            de,
+            first: true,
+            index: 0,
+        }
     }
 }
 
-impl<'a, 'de> de::SeqAccess<'de> for SeqAccess<'a, 'de> {
+impl<'a, 'de, R: Read<'de>> de::SeqAccess<'de> for SeqAccess<'a, 'de, R> {
     type Error = Error;
 
     fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>>
@@ -26,7 +32,7 @@ impl<'a, 'de> de::SeqAccess<'de> for SeqAccess<'a, 'de> {
             .ok_or(Error::EofWhileParsingList)?
         {
             b']' => return Ok(None),
-            b',' => {
+            b',' if !self.first => {
                 self.de.eat_char();
                 self.de
                     .parse_whitespace()
@@ -43,9 +49,26 @@ impl<'a, 'de> de::SeqAccess<'de> for SeqAccess<'a, 'de> {
         };
 
         if peek == b']' {
-            Err(Error::TrailingComma)
+            if self.de.options.allow_trailing_commas {
+                Ok(None)
+            } else {
+                Err(Error::TrailingComma)
+            }
         } else {
-            Ok(Some(seed.deserialize(&mut *self.de)?))
+            let index = self.index;
+            if let Some(max_array_len) = self.de.options.max_array_len {
+                if index >= max_array_len {
+                    return Err(Error::ArrayTooLong);
+                }
+            }
+            self.index += 1;
+            match seed.deserialize(&mut *self.de) {
+                Ok(value) => Ok(Some(value)),
+                Err(e) => {
+                    self.de.record_path(PathSegment::Index(index));
+                    Err(e)
+                }
+            }
         }
     }
 }
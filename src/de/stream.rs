@@ -0,0 +1,210 @@
+use core::marker::PhantomData;
+
+use serde::de;
+
+use crate::de::{Deserializer, Error, Options, Result};
+use crate::RawValue;
+
+/// Iterator that deserializes a sequence of JSON values from one input, such as
+/// `{"a":1} {"a":2}\n{"a":3}`
+///
+/// Values must be separated by whitespace unless they are arrays, objects or strings. The
+/// iterator ends at the end of the input or after the first error.
+pub struct StreamDeserializer<'a, T> {
+    de: Deserializer<'a>,
+    failed: bool,
+    output: PhantomData<T>,
+}
+
+impl<'a, T> StreamDeserializer<'a, T>
+where
+    T: de::Deserialize<'a>,
+{
+    /// Creates an iterator over the JSON values in `v`
+    pub fn new(v: &'a [u8]) -> Self {
+        Self::with_options(v, &Options::default())
+    }
+
+    /// Creates an iterator over the JSON values in `v`, which are parsed using `options`
+    pub fn with_options(v: &'a [u8], options: &Options) -> Self {
+        StreamDeserializer {
+            de: Deserializer::with_options(v, options),
+            failed: false,
+            output: PhantomData,
+        }
+    }
+
+    /// Returns the number of bytes consumed so far
+    ///
+    /// After an error this is the position at which parsing failed.
+    pub fn byte_offset(&self) -> usize {
+        self.de.read.index
+    }
+
+    /// Checks that the value just parsed is not directly followed by something other than
+    /// whitespace, unless either side delimits itself (e.g. `[1][2]` or `"a""b"`)
+    fn check_separated(&mut self) -> Result<()> {
+        let delimited = match self.de.read.slice[..self.de.read.index].last() {
+            Some(b']') | Some(b'}') => true,
+            Some(&c) => self.de.is_quote(c),
+            None => false,
+        };
+        match self.de.peek() {
+            Some(b' ') | Some(b'\n') | Some(b'\t') | Some(b'\r') | None => Ok(()),
+            Some(b'[') | Some(b'{') => Ok(()),
+            Some(c) if self.de.is_quote(c) => Ok(()),
+            Some(b'/') if self.de.options.allow_comments => Ok(()),
+            Some(_) if delimited => Ok(()),
+            Some(_) => Err(Error::TrailingCharacters),
+        }
+    }
+}
+
+impl<'a, T> Iterator for StreamDeserializer<'a, T>
+where
+    T: de::Deserialize<'a>,
+{
+    type Item = Result<T>;
+
+    fn next(&mut self) -> Option<Result<T>> {
+        if self.failed {
+            return None;
+        }
+        if self.de.parse_whitespace().is_none() {
+            self.failed = true;
+            return self.de.end().err().map(Err);
+        }
+        let result = de::Deserialize::deserialize(&mut self.de)
+            .and_then(|value| self.check_separated().map(|()| value));
+        self.failed = result.is_err();
+        Some(result)
+    }
+}
+
+/// Returns an iterator over the text of the whitespace-separated JSON values in `input`, without
+/// deserializing them
+///
+/// The values are checked to be valid JSON and separated like for [`StreamDeserializer`]. This
+/// allows to store or forward the values of a stream before deciding how to deserialize each of
+/// them.
+///
+/// ```
+/// use serde_json_wasm::de::raw_values;
+///
+/// let frames: Vec<&str> = raw_values(b"{\"a\": 1} [2]\n\"3\"").map(Result::unwrap).collect();
+/// assert_eq!(frames, [r#"{"a": 1}"#, "[2]", r#""3""#]);
+/// ```
+pub fn raw_values(input: &[u8]) -> RawValues<'_> {
+    RawValues::with_options(input, &Options::default())
+}
+
+/// Iterator over the text of the JSON values in one input, created by [`raw_values`]
+pub struct RawValues<'a> {
+    stream: StreamDeserializer<'a, &'a RawValue>,
+}
+
+impl<'a> RawValues<'a> {
+    /// Creates an iterator over the JSON values in `input`, which are parsed using `options`
+    pub fn with_options(input: &'a [u8], options: &Options) -> Self {
+        RawValues {
+            stream: StreamDeserializer::with_options(input, options),
+        }
+    }
+
+    /// Returns the number of bytes consumed so far
+    ///
+    /// After an error this is the position at which parsing failed.
+    pub fn byte_offset(&self) -> usize {
+        self.stream.byte_offset()
+    }
+}
+
+impl<'a> Iterator for RawValues<'a> {
+    type Item = Result<&'a str>;
+
+    fn next(&mut self) -> Option<Result<&'a str>> {
+        self.stream
+            .next()
+            .map(|result| result.map(|value| value.get()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{raw_values, StreamDeserializer};
+    use crate::de::{Error, Options};
+    use serde_derive::Deserialize;
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Event {
+        height: u64,
+    }
+
+    #[test]
+    fn stream() {
+        let input = br#"{ "height": 1 }{ "height": 2 }
+            { "height": 3 }
+        "#;
+        let mut stream = StreamDeserializer::<Event>::new(input);
+        assert_eq!(stream.next(), Some(Ok(Event { height: 1 })));
+        assert_eq!(stream.byte_offset(), 15);
+        assert_eq!(stream.next(), Some(Ok(Event { height: 2 })));
+        assert_eq!(stream.next(), Some(Ok(Event { height: 3 })));
+        assert_eq!(stream.next(), None);
+        assert_eq!(stream.byte_offset(), input.len());
+
+        let values: Result<Vec<u8>, _> = StreamDeserializer::new(b"1 2\n3\t4").collect();
+        assert_eq!(values, Ok(vec![1, 2, 3, 4]));
+        let values: Result<Vec<Vec<u8>>, _> = StreamDeserializer::new(b"[1][2]").collect();
+        assert_eq!(values, Ok(vec![vec![1], vec![2]]));
+        assert_eq!(StreamDeserializer::<u8>::new(b"").next(), None);
+        assert_eq!(StreamDeserializer::<u8>::new(b" \n ").next(), None);
+    }
+
+    #[test]
+    fn stream_errors() {
+        let mut stream = StreamDeserializer::<Event>::new(
+            br#"{ "height": 1 } { "height": true } { "height": 3 }"#,
+        );
+        assert_eq!(stream.next(), Some(Ok(Event { height: 1 })));
+        assert!(stream.next().unwrap().is_err());
+        assert_eq!(stream.next(), None);
+
+        let mut stream = StreamDeserializer::<u8>::new(b"1 2x 3");
+        assert_eq!(stream.next(), Some(Ok(1)));
+        assert_eq!(stream.next(), Some(Err(Error::TrailingCharacters)));
+        assert_eq!(stream.byte_offset(), 3);
+        assert_eq!(stream.next(), None);
+
+        let mut stream = StreamDeserializer::<Event>::new(br#"{ "height": 1 } { "height": "#);
+        assert_eq!(stream.next(), Some(Ok(Event { height: 1 })));
+        assert_eq!(stream.next(), Some(Err(Error::EofWhileParsingValue)));
+
+        let options = Options::default().allow_comments(true);
+        let mut stream = StreamDeserializer::<u8>::with_options(b"1 /* 2", &options);
+        assert_eq!(stream.next(), Some(Ok(1)));
+        assert_eq!(stream.next(), Some(Err(Error::EofWhileParsingComment)));
+        assert_eq!(stream.next(), None);
+    }
+
+    #[test]
+    fn raw_values_of_stream() {
+        let mut values = raw_values(b" 1 {\"a\": [true]}[2]\"x\" null ");
+        assert_eq!(values.next(), Some(Ok("1")));
+        assert_eq!(values.next(), Some(Ok(r#"{"a": [true]}"#)));
+        assert_eq!(values.next(), Some(Ok("[2]")));
+        assert_eq!(values.next(), Some(Ok(r#""x""#)));
+        assert_eq!(values.next(), Some(Ok("null")));
+        assert_eq!(values.next(), None);
+
+        let mut values = raw_values(b"[1] [tru] [3]");
+        assert_eq!(values.next(), Some(Ok("[1]")));
+        assert_eq!(values.next(), Some(Err(Error::ExpectedSomeIdent)));
+        assert_eq!(values.byte_offset(), 9);
+        assert_eq!(values.next(), None);
+
+        let mut values = raw_values(b"1 2x");
+        assert_eq!(values.next(), Some(Ok("1")));
+        assert_eq!(values.next(), Some(Err(Error::TrailingCharacters)));
+    }
+}
@@ -1,4 +1,7 @@
-use std::convert::TryFrom;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+use core::str::from_utf8;
 
 use super::errors::{Error, Result};
 
@@ -14,9 +17,137 @@ static SURROGARES_HIGH_LAST: u16 = 0xDBFF;
 static SURROGARES_LOW_FIRST: u16 = 0xDC00;
 static SURROGARES_LAST: u16 = 0xDFFF;
 
-pub(crate) fn unescape(source: &[u8]) -> Result<String> {
-    let mut out: Vec<u8> = Vec::with_capacity(source.len());
+/// Delimits strings that were unescaped by `unescape_strings_in_place`. This byte never occurs
+/// in valid UTF-8, so it cannot be confused with string content.
+pub(crate) static UNESCAPED_MARKER: u8 = 0xFF;
+
+/// What is accepted in the content of a string
+#[derive(Clone, Copy)]
+pub(crate) struct StringRules {
+    /// Quote that delimits the string. In a string in single quotes, `\'` is a valid escape
+    /// sequence.
+    pub quote: u8,
+    /// Whether the control characters U+0000 to U+001F may occur unescaped
+    pub allow_control_characters: bool,
+    /// Whether escaped surrogates that are not part of a pair are replaced by U+FFFD
+    pub replace_lone_surrogates: bool,
+    /// Whether invalid UTF-8 is replaced by U+FFFD
+    pub replace_invalid_utf8: bool,
+    /// Maximum length of the unescaped content in bytes, see `unescape_into`
+    pub max_len: Option<usize>,
+}
+
+impl Default for StringRules {
+    fn default() -> Self {
+        StringRules {
+            quote: b'"',
+            allow_control_characters: false,
+            replace_lone_surrogates: false,
+            replace_invalid_utf8: false,
+            max_len: None,
+        }
+    }
+}
+
+/// Unescapes the content of a string that follows `rules`. The allocation of `out` is reused for
+/// the returned string.
+pub(crate) fn unescape(source: &[u8], rules: StringRules, mut out: Vec<u8>) -> Result<String> {
+    unescape_into(source, rules, &mut out)?;
+    match String::from_utf8(out) {
+        Ok(string) => Ok(string),
+        Err(e) if rules.replace_invalid_utf8 => {
+            Ok(String::from_utf8_lossy(e.as_bytes()).into_owned())
+        }
+        Err(_) => Err(Error::InvalidUnicodeCodePoint),
+    }
+}
+
+/// Unescapes the content of a string that follows `rules` into `out`, replacing what it held.
+/// The bytes written are not checked to be UTF-8.
+///
+/// Fails with [`Error::StringTooLong`] as soon as the unescaped content would exceed
+/// `rules.max_len`, so `out` never grows past the limit.
+pub(crate) fn unescape_into(source: &[u8], rules: StringRules, out: &mut Vec<u8>) -> Result<()> {
+    out.clear();
+    out.reserve(
+        rules
+            .max_len
+            .map_or(source.len(), |max_len| max_len.min(source.len())),
+    );
+    let mut append = Append {
+        source,
+        out,
+        max_len: rules.max_len,
+    };
+    unescape_with(&mut append, source.len(), rules)
+}
+
+/// Unescapes the string content in `buf` and writes the result to the start of `buf`.
+///
+/// Returns the length of the unescaped content. Since every escape sequence is longer than the
+/// bytes it decodes to, the write position never overtakes the read position. The result is not
+/// validated to be UTF-8.
+pub(crate) fn unescape_in_place(buf: &mut [u8], rules: StringRules) -> Result<usize> {
+    let len = buf.len();
+    let mut in_place = InPlace { buf, write: 0 };
+    unescape_with(&mut in_place, len, rules)?;
+    Ok(in_place.write)
+}
+
+/// Source of the escaped content and destination of the unescaped bytes for `unescape_with`
+trait Output {
+    /// Returns the byte of the escaped content at `read`
+    fn byte(&self, read: usize) -> u8;
+
+    /// Writes `bytes` after what was unescaped so far
+    fn put(&mut self, bytes: &[u8]) -> Result<()>;
+}
+
+/// Writes the unescaped bytes over the escaped content in `buf`
+struct InPlace<'b> {
+    buf: &'b mut [u8],
+    /// Position where the next unescaped byte is written
+    write: usize,
+}
+
+impl Output for InPlace<'_> {
+    fn byte(&self, read: usize) -> u8 {
+        self.buf[read]
+    }
 
+    fn put(&mut self, bytes: &[u8]) -> Result<()> {
+        let end = self.write + bytes.len();
+        self.buf[self.write..end].copy_from_slice(bytes);
+        self.write = end;
+        Ok(())
+    }
+}
+
+/// Appends the unescaped bytes of `source` to `out`, which may not grow past `max_len`
+struct Append<'b> {
+    source: &'b [u8],
+    out: &'b mut Vec<u8>,
+    max_len: Option<usize>,
+}
+
+impl Output for Append<'_> {
+    fn byte(&self, read: usize) -> u8 {
+        self.source[read]
+    }
+
+    fn put(&mut self, bytes: &[u8]) -> Result<()> {
+        if let Some(max_len) = self.max_len {
+            if self.out.len() + bytes.len() > max_len {
+                return Err(Error::StringTooLong);
+            }
+        }
+        self.out.extend_from_slice(bytes);
+        Ok(())
+    }
+}
+
+/// Unescapes the `len` bytes of escaped content read from `out`
+fn unescape_with(out: &mut impl Output, len: usize, rules: StringRules) -> Result<()> {
     let mut encoding_tmp = [0u8; 4];
     let mut in_escape = false;
     let mut in_unicode = false;
@@ -26,15 +157,16 @@ pub(crate) fn unescape(source: &[u8]) -> Result<String> {
     let mut unicode_tmp_pos: usize = 0;
     let mut high_surrogate: Option<u16> = None;
 
-    for byte in source {
-        if *byte <= 0x1F {
+    for read in 0..len {
+        let byte = out.byte(read);
+        if byte <= 0x1F && !rules.allow_control_characters {
             return Err(Error::ControlCharacterInString);
         }
 
         if in_unicode {
             match byte {
                 b'0'..=b'9' | b'a'..=b'f' | b'A'..=b'F' => {
-                    unicode_tmp[unicode_tmp_pos] = *byte;
+                    unicode_tmp[unicode_tmp_pos] = byte;
                     unicode_tmp_pos += 1;
                     if unicode_tmp_pos == 4 {
                         let codepoint = hex_decode(unicode_tmp);
@@ -42,32 +174,36 @@ pub(crate) fn unescape(source: &[u8]) -> Result<String> {
                         if codepoint >= SURROGARES_FIRST && codepoint <= SURROGARES_LAST {
                             if let Some(high) = high_surrogate {
                                 if codepoint < SURROGARES_LOW_FIRST {
-                                    return Err(Error::ExpectedLowSurrogate);
+                                    lone_surrogate(out, rules, Error::ExpectedLowSurrogate)?;
+                                    high_surrogate = Some(codepoint);
+                                } else {
+                                    let low = codepoint;
+
+                                    // https://en.wikipedia.org/wiki/Universal_Character_Set_characters#Surrogates
+                                    let combined = 0x1_0000
+                                        + (((high - 0xD800) as u32) << 10 | (low - 0xDC00) as u32);
+                                    let encoded = match char::try_from(combined) {
+                                        Ok(c) => c.encode_utf8(&mut encoding_tmp as &mut [u8]),
+                                        Err(_) => return Err(Error::InvalidUnicodeCodePoint),
+                                    };
+                                    out.put(encoded.as_bytes())?;
+
+                                    high_surrogate = None;
                                 }
-                                let low = codepoint;
-
-                                // https://en.wikipedia.org/wiki/Universal_Character_Set_characters#Surrogates
-                                let combined = 0x1_0000
-                                    + (((high - 0xD800) as u32) << 10 | (low - 0xDC00) as u32);
-                                let encoded = match char::try_from(combined) {
-                                    Ok(c) => c.encode_utf8(&mut encoding_tmp as &mut [u8]),
-                                    Err(_) => return Err(Error::InvalidUnicodeCodePoint),
-                                };
-                                out.extend_from_slice(encoded.as_bytes());
-
-                                high_surrogate = None;
+                            } else if codepoint > SURROGARES_HIGH_LAST {
+                                lone_surrogate(out, rules, Error::ExpectedHighSurrogate)?;
                             } else {
-                                if codepoint > SURROGARES_HIGH_LAST {
-                                    return Err(Error::ExpectedHighSurrogate);
-                                }
                                 high_surrogate = Some(codepoint);
                             }
                         } else {
+                            if high_surrogate.take().is_some() {
+                                lone_surrogate(out, rules, Error::LoneSurrogateFound)?;
+                            }
                             let encoded = match char::try_from(codepoint as u32) {
                                 Ok(c) => c.encode_utf8(&mut encoding_tmp as &mut [u8]),
                                 Err(_) => return Err(Error::InvalidEscape),
                             };
-                            out.extend_from_slice(encoded.as_bytes());
+                            out.put(encoded.as_bytes())?;
                         }
 
                         unicode_tmp_pos = 0;
@@ -78,47 +214,36 @@ pub(crate) fn unescape(source: &[u8]) -> Result<String> {
                 _ => return Err(Error::InvalidEscape),
             }
         } else if in_escape {
-            match byte {
-                b'"' | b'/' | b'\\' => {
-                    out.push(*byte);
-                    in_escape = false;
-                }
-                b'b' => {
-                    out.push(BACKSPACE);
-                    in_escape = false;
-                }
-                b'f' => {
-                    out.push(FORMFEED);
-                    in_escape = false;
-                }
-                b'n' => {
-                    out.push(LINEFEED);
-                    in_escape = false;
-                }
-                b'r' => {
-                    out.push(CARRIAGE_RETURN);
-                    in_escape = false;
-                }
-                b't' => {
-                    out.push(HORIZONTAL_TAB);
-                    in_escape = false;
-                }
+            if byte != b'u' && high_surrogate.take().is_some() {
+                lone_surrogate(out, rules, Error::LoneSurrogateFound)?;
+            }
+            let unescaped = match byte {
+                b'"' | b'/' | b'\\' => byte,
+                b'\'' if rules.quote == b'\'' => byte,
+                b'b' => BACKSPACE,
+                b'f' => FORMFEED,
+                b'n' => LINEFEED,
+                b'r' => CARRIAGE_RETURN,
+                b't' => HORIZONTAL_TAB,
                 b'u' => {
                     in_unicode = true;
+                    continue;
                 }
                 _ => return Err(Error::InvalidEscape),
-            }
+            };
+            out.put(&[unescaped])?;
+            in_escape = false;
         } else {
             // Default case, not in escape sequence
 
-            if *byte == b'\\' {
+            if byte == b'\\' {
                 in_escape = true;
             } else {
-                if high_surrogate.is_some() {
-                    return Err(Error::LoneSurrogateFound);
+                if high_surrogate.take().is_some() {
+                    lone_surrogate(out, rules, Error::LoneSurrogateFound)?;
                 }
 
-                out.push(*byte);
+                out.put(&[byte])?;
             }
         }
     }
@@ -128,10 +253,74 @@ pub(crate) fn unescape(source: &[u8]) -> Result<String> {
     }
 
     if high_surrogate.is_some() {
-        return Err(Error::LoneSurrogateFound);
+        lone_surrogate(out, rules, Error::LoneSurrogateFound)?;
+    }
+
+    Ok(())
+}
+
+/// Handles a surrogate that is not part of a pair, which is either an error or written as
+/// U+FFFD.
+fn lone_surrogate(out: &mut impl Output, rules: StringRules, error: Error) -> Result<()> {
+    if rules.replace_lone_surrogates {
+        out.put("\u{FFFD}".as_bytes())
+    } else {
+        Err(error)
     }
+}
+
+/// Unescapes all JSON strings containing escape sequences in `buf`, in place.
+///
+/// A string `"<escaped>"` is rewritten to `"<marker><unescaped><marker>` followed by space
+/// padding, which takes at most as many bytes as the original. The padding is insignificant
+/// whitespace between JSON tokens.
+pub(crate) fn unescape_strings_in_place(buf: &mut [u8]) -> Result<()> {
+    let mut index = 0;
+    while index < buf.len() {
+        let byte = buf[index];
+        if byte == UNESCAPED_MARKER {
+            return Err(Error::InvalidUtf8(index));
+        }
+        if byte != b'"' {
+            index += 1;
+            continue;
+        }
+
+        let start = index + 1;
+        let mut end = start;
+        let mut contains_backslash = false;
+        let mut escaped = false;
+        loop {
+            match buf.get(end) {
+                // let the parser report the unterminated string
+                None => return Ok(()),
+                Some(b'"') if !escaped => break,
+                Some(b'\\') => {
+                    contains_backslash = true;
+                    escaped = !escaped;
+                }
+                Some(&b) if b == UNESCAPED_MARKER => return Err(Error::InvalidUtf8(end)),
+                Some(_) => escaped = false,
+            }
+            end += 1;
+        }
 
-    String::from_utf8(out).map_err(|_| Error::InvalidUnicodeCodePoint)
+        if contains_backslash {
+            from_utf8(&buf[start..end])
+                .map_err(|error| Error::InvalidUtf8(start + error.valid_up_to()))?;
+            let len = unescape_in_place(&mut buf[start..end], StringRules::default())?;
+            from_utf8(&buf[start..start + len]).map_err(|_| Error::InvalidUnicodeCodePoint)?;
+            buf.copy_within(start..start + len, start + 1);
+            buf[start] = UNESCAPED_MARKER;
+            buf[start + 1 + len] = UNESCAPED_MARKER;
+            for b in &mut buf[start + len + 2..=end] {
+                *b = b' ';
+            }
+        }
+
+        index = end + 1;
+    }
+    Ok(())
 }
 
 /// Returns a 16 bit value between 0x0000 and 0xFFFF, i.e. a codepoint
@@ -158,6 +347,11 @@ fn hex_decode_4bit(x: u8) -> u8 {
 mod tests {
     use super::*;
 
+    /// Unescapes a string in double quotes
+    fn unescape(source: &[u8]) -> Result<String> {
+        super::unescape(source, StringRules::default(), Vec::new())
+    }
+
     /// A testing wrapper around unescape
     fn ue(source: &[u8]) -> String {
         unescape(source).unwrap()
@@ -302,6 +496,23 @@ mod tests {
         assert_eq!(unescape(br#" \"#), Err(Error::InvalidEscape));
     }
 
+    #[test]
+    fn unescape_works_for_single_quotes() {
+        let rules = StringRules {
+            quote: b'\'',
+            ..StringRules::default()
+        };
+        assert_eq!(
+            super::unescape(br#"it\'s"#, rules, Vec::new()),
+            Ok("it's".to_string())
+        );
+        assert_eq!(
+            super::unescape(br#"\"\'"#, rules, Vec::new()),
+            Ok("\"'".to_string())
+        );
+        assert_eq!(unescape(br#"it\'s"#), Err(Error::InvalidEscape));
+    }
+
     #[test]
     fn unescape_works_for_surrogate_pairs() {
         assert_eq!(ue(br#" \uD83D\uDC4F "#), " \u{1F44F} ".to_string());
@@ -328,6 +539,104 @@ mod tests {
         assert_eq!(uee(br#" \uD800\u0001 "#), Error::LoneSurrogateFound);
         assert_eq!(uee(br#" \uD800\uD799 "#), Error::LoneSurrogateFound);
         assert_eq!(uee(br#" \uD800\uE000 "#), Error::LoneSurrogateFound);
+
+        // high surrogate followed by a different escape, even if a low surrogate comes later
+        assert_eq!(uee(br#" \uD83D\n\uDC4F "#), Error::LoneSurrogateFound);
+        assert_eq!(uee(br#" \uD83D\u0041\uDC4F "#), Error::LoneSurrogateFound);
+    }
+
+    #[test]
+    fn unescape_replaces_lone_surrogates() {
+        let rules = StringRules {
+            replace_lone_surrogates: true,
+            ..StringRules::default()
+        };
+        let replaced = |source: &[u8]| super::unescape(source, rules, Vec::new()).unwrap();
+
+        assert_eq!(replaced(br#"\uDEAD"#), "\u{FFFD}");
+        assert_eq!(replaced(br#"\uDC4F\uD83D"#), "\u{FFFD}\u{FFFD}");
+        assert_eq!(replaced(br#"\uD800\uD800"#), "\u{FFFD}\u{FFFD}");
+        assert_eq!(replaced(br#"\uD800\uD83D\uDC4F"#), "\u{FFFD}\u{1F44F}");
+        assert_eq!(replaced(br#"\uD83Dabc"#), "\u{FFFD}abc");
+        assert_eq!(replaced(br#"\uD800\u0041"#), "\u{FFFD}A");
+        assert_eq!(replaced(br#"\uD83D\n\uDC4F"#), "\u{FFFD}\n\u{FFFD}");
+        assert_eq!(replaced(br#"a\uD83D"#), "a\u{FFFD}");
+
+        // pairs and other errors are unaffected
+        assert_eq!(replaced(br#"\uD83D\uDC4F"#), "\u{1F44F}");
+        assert_eq!(
+            super::unescape(br#"\uD800\x"#, rules, Vec::new()),
+            Err(Error::InvalidEscape)
+        );
+    }
+
+    #[test]
+    fn unescape_replaces_invalid_utf8() {
+        let rules = StringRules {
+            replace_invalid_utf8: true,
+            ..StringRules::default()
+        };
+        assert_eq!(
+            super::unescape(b"a\\n\xF0\x9Fb\xFF", rules, Vec::new()),
+            Ok("a\n\u{FFFD}b\u{FFFD}".to_string())
+        );
+        assert_eq!(
+            super::unescape(b"a\\n\xFF", StringRules::default(), Vec::new()),
+            Err(Error::InvalidUnicodeCodePoint)
+        );
+    }
+
+    /// A testing wrapper around unescape_strings_in_place
+    fn usip(source: &[u8]) -> Vec<u8> {
+        let mut buf = source.to_vec();
+        unescape_strings_in_place(&mut buf).unwrap();
+        buf
+    }
+
+    #[test]
+    fn unescape_strings_in_place_works() {
+        // Unchanged because no unescaping happens
+        assert_eq!(usip(b""), b"");
+        assert_eq!(usip(br#"["a","b"]"#), br#"["a","b"]"#);
+
+        assert_eq!(usip(br#""\n""#), b"\"\xFF\n\xFF");
+        assert_eq!(usip(br#"["a\"b", "c"]"#), b"[\"\xFFa\"b\xFF, \"c\"]");
+        assert_eq!(
+            usip(br#"{"k\\":"\u00e4"}"#),
+            b"{\"\xFFk\\\xFF:\"\xFF\xC3\xA4\xFF   }"
+        );
+
+        // the marker byte must not appear in the input
+        let mut buf = b"\"\xFF\"".to_vec();
+        assert_eq!(
+            unescape_strings_in_place(&mut buf),
+            Err(Error::InvalidUtf8(1))
+        );
+        let mut buf = br#""\q""#.to_vec();
+        assert_eq!(
+            unescape_strings_in_place(&mut buf),
+            Err(Error::InvalidEscape)
+        );
+    }
+
+    #[test]
+    fn unescape_into_stops_at_max_len() {
+        let rules = StringRules {
+            max_len: Some(3),
+            ..StringRules::default()
+        };
+        let mut out = Vec::new();
+        assert_eq!(unescape_into(br"a\u0062c", rules, &mut out), Ok(()));
+        assert_eq!(out, b"abc");
+        assert_eq!(
+            unescape_into(br"a\u0062c\nd efgh", rules, &mut out),
+            Err(Error::StringTooLong)
+        );
+        assert!(out.len() <= 3);
+        assert_eq!(
+            unescape_into(br"\u00e4\u00e4", rules, &mut out),
+            Err(Error::StringTooLong)
+        );
     }
 
     #[test]
@@ -0,0 +1,164 @@
+use alloc::vec::Vec;
+use core::char::{decode_utf16, DecodeUtf16};
+
+use serde::de::DeserializeOwned;
+
+use crate::de::read::{IoRead, Source};
+use crate::de::{from_read, Error, Options, Result};
+
+/// Number of code units transcoded at a time
+const CHUNK_LEN: usize = 64;
+
+/// Deserializes an instance of type `T` from JSON text in UTF-16
+///
+/// The input is transcoded to UTF-8 in small chunks while it is parsed, so only the parts the
+/// parser needs in one piece, like a string, are held in UTF-8. Since they are not kept for the
+/// lifetime of the input, borrowed fields are not supported. A leading byte order mark is
+/// skipped.
+///
+/// Input that is not valid UTF-16, like a lone surrogate, fails with [`Error::InvalidUtf16`].
+///
+/// ```
+/// use serde_json_wasm::de::from_utf16_slice;
+///
+/// let text: Vec<u16> = r#"{"denom": "uatom"}"#.encode_utf16().collect();
+/// let coin: std::collections::BTreeMap<String, String> = from_utf16_slice(&text).unwrap();
+/// assert_eq!(coin["denom"], "uatom");
+/// ```
+pub fn from_utf16_slice<T>(v: &[u16]) -> Result<T>
+where
+    T: DeserializeOwned,
+{
+    transcode(v.iter().copied())
+}
+
+/// Deserializes an instance of type `T` from JSON text in UTF-16 little endian, like
+/// [`from_utf16_slice`]
+///
+/// The input must have an even number of bytes.
+pub fn from_utf16le<T>(v: &[u8]) -> Result<T>
+where
+    T: DeserializeOwned,
+{
+    let units = v.chunks(2).map(|unit| {
+        if unit.len() == 2 {
+            u16::from_le_bytes([unit[0], unit[1]])
+        } else {
+            // an odd byte at the end, which is reported like a lone surrogate
+            0xDC00
+        }
+    });
+    transcode(units)
+}
+
+/// Deserializes an instance of type `T` from the UTF-16 code units `units`
+fn transcode<T, I>(units: I) -> Result<T>
+where
+    T: DeserializeOwned,
+    I: Iterator<Item = u16>,
+{
+    let source = Utf16Source {
+        units: decode_utf16(units),
+        index: 0,
+        error: None,
+    };
+    from_read(IoRead::new(source, None), &Options::default())
+}
+
+/// [`Source`] that transcodes UTF-16 code units to UTF-8
+struct Utf16Source<I: Iterator<Item = u16>> {
+    units: DecodeUtf16<I>,
+    /// Index of the next code unit
+    index: usize,
+    /// Error at the end of the part of the input that was already transcoded
+    error: Option<Error>,
+}
+
+impl<I: Iterator<Item = u16>> Source for Utf16Source<I> {
+    fn fill(&mut self, buf: &mut Vec<u8>) -> Result<bool> {
+        if let Some(error) = self.error.take() {
+            return Err(error);
+        }
+        let start = self.index;
+        while self.index - start < CHUNK_LEN {
+            let c = match self.units.next() {
+                Some(Ok(c)) => c,
+                Some(Err(_)) => {
+                    self.error = Some(Error::InvalidUtf16(self.index));
+                    break;
+                }
+                None => break,
+            };
+            if !(self.index == 0 && c == '\u{FEFF}') {
+                buf.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes());
+            }
+            self.index += c.len_utf16();
+        }
+        if self.index > start {
+            return Ok(true);
+        }
+        match self.error.take() {
+            Some(error) => Err(error),
+            None => Ok(false),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{from_utf16_slice, from_utf16le};
+    use crate::de::Error;
+    use serde_derive::Deserialize;
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Memo {
+        text: String,
+        count: u8,
+    }
+
+    fn utf16le(text: &str) -> Vec<u8> {
+        text.encode_utf16().flat_map(u16::to_le_bytes).collect()
+    }
+
+    #[test]
+    fn utf16_input() {
+        let expected = || Memo {
+            text: "é😀\n".to_string(),
+            count: 2,
+        };
+        let input = "{\"text\": \"é😀\\n\", \"count\": 2}";
+        let units: Vec<u16> = input.encode_utf16().collect();
+        assert_eq!(from_utf16_slice(&units), Ok(expected()));
+        assert_eq!(from_utf16le(&utf16le(input)), Ok(expected()));
+        assert_eq!(from_utf16le(&utf16le("\u{FEFF}[1]")), Ok(vec![1u8]));
+
+        // strings across the chunks that are transcoded at a time
+        let text = "é😀".repeat(50);
+        let input = format!("{{\"text\": \"{}\", \"count\": 2}}", text);
+        assert_eq!(from_utf16le(&utf16le(&input)), Ok(Memo { text, count: 2 }));
+    }
+
+    #[test]
+    fn utf16_errors() {
+        assert_eq!(
+            from_utf16_slice::<String>(&[0x22, 0x61, 0xD800, 0x22]),
+            Err(Error::InvalidUtf16(2))
+        );
+        assert_eq!(
+            from_utf16_slice::<String>(&[0x22, 0xD83D, 0xDE00, 0xDC00, 0x22]),
+            Err(Error::InvalidUtf16(3))
+        );
+        assert_eq!(from_utf16le::<u8>(b"1\0 "), Err(Error::InvalidUtf16(1)));
+        let input = format!("[{}2]", "1, ".repeat(60));
+        let mut units: Vec<u16> = input.encode_utf16().collect();
+        units[130] = 0xDC00;
+        assert_eq!(
+            from_utf16_slice::<Vec<u8>>(&units),
+            Err(Error::InvalidUtf16(130))
+        );
+        assert_eq!(
+            from_utf16le::<u8>(&utf16le("1 2")),
+            Err(Error::TrailingCharacters)
+        );
+    }
+}
@@ -0,0 +1,193 @@
+//! Reformatting of JSON text without deserializing it
+
+use alloc::vec::Vec;
+
+use crate::de::{Event, Events, Options, Result};
+
+/// The layout of the JSON text written by [`reformat`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format<'a> {
+    /// Without any whitespace, like `{"a":[1,2]}`
+    Compact,
+    /// Every element and member on its own line, indented by the given string (usually spaces)
+    /// per level of nesting. Empty arrays and objects stay on one line.
+    Pretty(&'a str),
+}
+
+/// Appends `input` to `out` with all insignificant whitespace removed
+///
+/// This is [`reformat`] with [`Format::Compact`].
+///
+/// ```
+/// let mut out = Vec::new();
+/// serde_json_wasm::minify(b"{ \"amount\": [ 1, 2 ],\n  \"denom\": \"uatom\" }", &mut out).unwrap();
+/// assert_eq!(out, br#"{"amount":[1,2],"denom":"uatom"}"#);
+/// ```
+pub fn minify(input: &[u8], out: &mut Vec<u8>) -> Result<()> {
+    reformat(input, out, Format::Compact)
+}
+
+/// Appends `input` to `out` laid out according to `format`
+///
+/// The input is checked to be one well-formed JSON value, like [`de::validate`] does. Strings and
+/// numbers are copied as they are, including their escape sequences. If the input is invalid,
+/// the error is returned and `out` is left unchanged.
+///
+/// ```
+/// use serde_json_wasm::{reformat, Format};
+///
+/// let mut out = Vec::new();
+/// reformat(br#"{"amount":[1,2],"memo":{}}"#, &mut out, Format::Pretty("  ")).unwrap();
+/// assert_eq!(
+///     String::from_utf8(out).unwrap(),
+///     "{\n  \"amount\": [\n    1,\n    2\n  ],\n  \"memo\": {}\n}"
+/// );
+/// ```
+///
+/// [`de::validate`]: crate::de::validate
+pub fn reformat(input: &[u8], out: &mut Vec<u8>, format: Format<'_>) -> Result<()> {
+    let len = out.len();
+    let result = write_formatted(input, out, format);
+    if result.is_err() {
+        out.truncate(len);
+    }
+    result
+}
+
+fn write_formatted(input: &[u8], out: &mut Vec<u8>, format: Format<'_>) -> Result<()> {
+    let mut events = Events::with_options(input, &Options::default());
+    let mut depth = 0;
+    // whether the last event opened an array or object
+    let mut opened = false;
+    // whether a value was completed in the current array or object, so that a comma comes next
+    let mut need_comma = false;
+    while let Some(event) = events.next_event()? {
+        match event {
+            Event::ObjectEnd | Event::ArrayEnd => {
+                depth -= 1;
+                if !opened {
+                    write_newline(out, format, depth);
+                }
+                out.extend_from_slice(events.token());
+                opened = false;
+                need_comma = true;
+            }
+            event => {
+                if need_comma {
+                    out.push(b',');
+                }
+                if need_comma || opened {
+                    write_newline(out, format, depth);
+                }
+                out.extend_from_slice(events.token());
+                opened = false;
+                need_comma = false;
+                match event {
+                    Event::Key(_) => match format {
+                        Format::Compact => out.push(b':'),
+                        Format::Pretty(_) => out.extend_from_slice(b": "),
+                    },
+                    Event::ObjectStart | Event::ArrayStart => {
+                        depth += 1;
+                        opened = true;
+                    }
+                    _ => need_comma = true,
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Starts a new line at nesting level `depth`, unless the format is compact
+fn write_newline(out: &mut Vec<u8>, format: Format<'_>, depth: usize) {
+    if let Format::Pretty(indent) = format {
+        out.push(b'\n');
+        for _ in 0..depth {
+            out.extend_from_slice(indent.as_bytes());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{minify, reformat, Format};
+    use crate::de::Error;
+
+    fn minified(input: &str) -> Result<String, Error> {
+        let mut out = Vec::new();
+        minify(input.as_bytes(), &mut out)?;
+        Ok(String::from_utf8(out).unwrap())
+    }
+
+    #[test]
+    fn minify_documents() {
+        assert_eq!(
+            minified(
+                r#" {
+                    "a b" : [ 1 , -2.5e3, "x \n y" , true ],
+                    "c\"" : { } ,
+                    "d" : [ ] ,
+                    "e" : [ [ null ] , { "f" : false } ]
+                }
+                "#
+            ),
+            Ok(
+                r#"{"a b":[1,-2.5e3,"x \n y",true],"c\"":{},"d":[],"e":[[null],{"f":false}]}"#
+                    .to_string()
+            )
+        );
+        assert_eq!(minified(" 1 "), Ok("1".to_string()));
+        assert_eq!(minified(r#""é""#), Ok(r#""é""#.to_string()));
+    }
+
+    #[test]
+    fn minify_errors() {
+        assert_eq!(minified("[1 2]"), Err(Error::ExpectedListCommaOrEnd));
+        assert_eq!(minified("[1,]"), Err(Error::TrailingComma));
+        assert_eq!(minified("{} {}"), Err(Error::TrailingCharacters));
+
+        let mut out = b"[".to_vec();
+        assert_eq!(minify(b"[1, 2", &mut out), Err(Error::EofWhileParsingList));
+        assert_eq!(out, b"[");
+    }
+
+    fn pretty(input: &str, indent: &str) -> Result<String, Error> {
+        let mut out = Vec::new();
+        reformat(input.as_bytes(), &mut out, Format::Pretty(indent))?;
+        Ok(String::from_utf8(out).unwrap())
+    }
+
+    #[test]
+    fn reformat_pretty() {
+        assert_eq!(
+            pretty(r#"{"a":[1,{"b":null,"c":[]}],"d":{},"e":[[true]]}"#, "  "),
+            Ok(r#"{
+  "a": [
+    1,
+    {
+      "b": null,
+      "c": []
+    }
+  ],
+  "d": {},
+  "e": [
+    [
+      true
+    ]
+  ]
+}"#
+            .to_string())
+        );
+        assert_eq!(pretty("[1, 2]", "\t"), Ok("[\n\t1,\n\t2\n]".to_string()));
+        assert_eq!(pretty(" \"a\" ", "  "), Ok("\"a\"".to_string()));
+        assert_eq!(pretty("[[]]", ""), Ok("[\n[]\n]".to_string()));
+        assert_eq!(pretty("[1,]", "  "), Err(Error::TrailingComma));
+
+        // pretty output minifies back to the same text
+        let input = r#"{"a":[1,{"b":"x\"y"}],"c":-0.5}"#;
+        let mut out = Vec::new();
+        minify(pretty(input, "    ").unwrap().as_bytes(), &mut out).unwrap();
+        assert_eq!(out, input.as_bytes());
+    }
+}
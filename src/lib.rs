@@ -8,60 +8,103 @@
 //! # Current features
 //!
 //! - The error type is a simple C like enum (less overhead, smaller memory footprint)
-//! - (De)serialization doesn't require memory allocations
+//! - `no_std` with `alloc` when the default `std` feature is disabled. Only [`de::from_reader`],
+//!   [`de::from_cstr`] and the `std::error::Error` impls of the error types need `std`.
+//! - Few memory allocations: strings without escape sequences are borrowed from the input, and
+//!   serialization writes into a single output buffer
 //! - Deserialization of integers doesn't go through `u64`; instead the string is directly parsed
 //!   into the requested integer type. This avoids pulling in KBs of compiler intrinsics when
 //!   targeting a non 64-bit architecture.
 //! - Supports deserialization of:
 //!   - `bool`
-//!   - Integers
-//!   - `str` (This is a zero copy operation.) (\*)
+//!   - Integers (including `i128` and `u128`)
+//!   - `f32` and `f64` (with the `float` feature; without it no float code is compiled in and
+//!     they fail with [`de::Error::FloatNotSupported`])
+//!   - Numbers of any size and precision as their text, with [`Number`]
+//!   - The unparsed text of any value, with [`RawValue`]
+//!   - `char`
+//!   - `str` (This is a zero copy operation when the string contains no escape sequences.) (\*)
+//!   - Bytes from arrays of numbers, base64 strings (with the `base64` feature) or hex strings
 //!   - `Option`
 //!   - Arrays
 //!   - Tuples
 //!   - Structs
-//!   - C like enums
-//! - Supports serialization (compact format only) of:
+//!   - Maps (with string, integer, `char` or C like enum keys)
+//!   - C like enums (from their variant name or variant index)
+//!   - Self-describing types via `deserialize_any` (floats only with the `float` feature)
+//!   - Untagged, internally tagged and adjacently tagged enums
+//!   - Structs with `#[serde(flatten)]` fields
+//! - Supports serialization (compact or pretty printed) of:
 //!   - `bool`
-//!   - Integers
+//!   - Integers (including `i128` and `u128`)
+//!   - `char`
 //!   - `str`
 //!   - `Option`
 //!   - Arrays
-//!   - Tuples
-//!   - Structs
+//!   - Tuples and tuple structs
+//!   - Structs, including ones with `#[serde(flatten)]` fields
+//!   - Maps (with string, integer, `bool`, `char`, newtype struct or C like enum keys)
 //!   - C like enums
+//!   - Bytes, as arrays of numbers, base64 strings (with the `base64` feature) or hex strings
+//!
+//! Bytes are (de)serialized for types that use `serialize_bytes`/`deserialize_byte_buf`, like
+//! `serde_bytes::ByteBuf`. `serde_bytes::Bytes` can be serialized, but not deserialized since
+//! the decoded bytes cannot be borrowed from the input.
 //!
-//! (\*) Deserialization of strings ignores escaped sequences. Escaped sequences might be supported
-//! in the future using a different Serializer as this operation is not zero copy.
+//! (\*) Strings containing escape sequences are unescaped into an owned `String`. Deserialize such
+//! fields into `String` or `Cow<str>` (with `#[serde(borrow)]`) rather than `&str`, since the
+//! unescaped content cannot be borrowed from the input. A `Cow<str>` borrows whenever the string
+//! contains no escapes. Alternatively, [`from_slice_mut`] unescapes strings in place within a
+//! mutable input buffer, which allows `&str` fields for any string.
+//!
+//! Untagged enums, internally tagged enums and flattened fields are buffered by serde before they
+//! are deserialized. serde's buffer does not support `i128`/`u128`, so such values cannot be used
+//! there.
 //!
 //! # Planned features
 //!
-//! - (De)serialization from / into IO objects once `core::io::{Read,Write}` becomes a thing.
+//! - Serialization into IO objects. Deserialization already reads from `std::io::Read` with
+//!   [`de::from_reader`], from `embedded_io::Read` with the `embedded-io` feature and from async
+//!   readers with the `async` feature.
 //!
 //! # Non-features
 //!
 //! This is explicitly out of scope
 //!
-//! - Anything that involves dynamic memory allocation
-//!   - Like the dynamic [`Value`](https://docs.rs/serde_json/1.0.11/serde_json/enum.Value.html)
-//!     type
+//! - A dynamic [`Value`](https://docs.rs/serde_json/1.0.11/serde_json/enum.Value.html) type.
+//!   [`RawValue`] and [`Number`] keep the text of values whose type is not known up front instead.
 //!
 //! # MSRV
 //!
-//! This crate is guaranteed to compile on stable Rust 1.31.0 and up. It *might* compile with older
-//! versions but that may change in any new patch release.
+//! This crate is guaranteed to compile on stable Rust 1.40.0 and up with the default features.
+//! Optional dependencies like `heapless` and `arrayvec` need newer versions. It *might* compile
+//! with older versions but that may change in any new patch release.
 
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
 #![deny(missing_docs)]
 #![deny(rust_2018_compatibility)]
 #![deny(rust_2018_idioms)]
 
+extern crate alloc;
+
+#[cfg(feature = "base64")]
+mod base64;
+mod category;
 pub mod de;
+mod format;
+mod number;
+mod raw;
 pub mod ser;
 
+pub use self::category::Category;
+pub use self::format::{minify, reformat, Format};
+pub use self::number::Number;
+pub use self::raw::RawValue;
+
 #[doc(inline)]
-pub use self::de::{from_slice, from_str};
+pub use self::de::{from_slice, from_slice_mut, from_str};
 #[doc(inline)]
-pub use self::ser::{to_string, to_vec};
+pub use self::ser::{to_string, to_string_pretty, to_vec, to_vec_pretty};
 
 #[cfg(test)]
 mod test {
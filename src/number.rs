@@ -0,0 +1,129 @@
+use alloc::string::{String, ToString};
+use core::fmt;
+use core::str::FromStr;
+
+use serde::de::{self, Visitor};
+use serde::ser;
+
+/// Name of the newtype struct through which [`Number`] is passed to the (de)serializer, which
+/// then exchanges the raw text of the number instead of a string
+pub(crate) const TOKEN: &str = "$serde_json_wasm::private::Number";
+
+/// A JSON number kept as the exact text it was written with, like `123.4500` or `1e80`
+///
+/// This allows types with more precision than the built-in integers, such as big decimals, to
+/// parse the digits themselves. A `Number` is serialized as the same text, without quotes.
+///
+/// ```
+/// use serde_json_wasm::Number;
+///
+/// let number: Number = serde_json_wasm::from_str("123.4500").unwrap();
+/// assert_eq!(number.as_str(), "123.4500");
+/// assert_eq!(serde_json_wasm::to_string(&number).unwrap(), "123.4500");
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Number(String);
+
+impl Number {
+    /// Returns the text of the number
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for Number {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// Parses a number that follows the JSON grammar, like `-0.5e3`
+impl FromStr for Number {
+    type Err = crate::de::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        crate::from_str(s)
+    }
+}
+
+impl ser::Serialize for Number {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: ser::Serializer,
+    {
+        serializer.serialize_newtype_struct(TOKEN, &self.0)
+    }
+}
+
+impl<'de> de::Deserialize<'de> for Number {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        struct NumberVisitor;
+
+        impl<'de> Visitor<'de> for NumberVisitor {
+            type Value = Number;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+                formatter.write_str("a JSON number")
+            }
+
+            fn visit_str<E>(self, v: &str) -> Result<Number, E>
+            where
+                E: de::Error,
+            {
+                Ok(Number(v.to_string()))
+            }
+        }
+
+        deserializer.deserialize_newtype_struct(TOKEN, NumberVisitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Number;
+    use crate::de::Error;
+    use crate::{from_str, to_string};
+    use serde_derive::{Deserialize, Serialize};
+
+    #[derive(Debug, Deserialize, Serialize, PartialEq)]
+    struct Price {
+        amount: Number,
+        denom: String,
+    }
+
+    #[test]
+    fn keeps_the_text() {
+        let json = r#"{"amount":115792089237316195423570985008687907853269984665640564039457.584007913129639935,"denom":"uatom"}"#;
+        let price: Price = from_str(json).unwrap();
+        assert_eq!(
+            price.amount.as_str(),
+            "115792089237316195423570985008687907853269984665640564039457.584007913129639935"
+        );
+        assert_eq!(to_string(&price).unwrap(), json);
+
+        for text in &["0", "-0", "1.50", "-2E+10", "1e-7"] {
+            assert_eq!(
+                from_str::<Number>(text).map(|n| n.to_string()),
+                Ok(text.to_string())
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_other_values() {
+        assert_eq!(from_str::<Number>("1."), Err(Error::InvalidNumber));
+        assert_eq!(from_str::<Number>("-"), Err(Error::InvalidNumber));
+        assert_eq!(
+            from_str::<Number>(r#""1""#),
+            Err(Error::InvalidType(crate::de::JsonType::String))
+        );
+        assert_eq!("01".parse::<Number>(), Err(Error::TrailingCharacters));
+        assert_eq!(
+            " 1 ".parse::<Number>().map(|n| n.to_string()),
+            Ok("1".to_string())
+        );
+    }
+}
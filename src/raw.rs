@@ -0,0 +1,250 @@
+use alloc::borrow::ToOwned;
+use alloc::boxed::Box;
+use alloc::string::String;
+use core::fmt;
+
+use serde::de::{self, Visitor};
+use serde::ser;
+
+/// Name of the newtype struct through which [`RawValue`] is passed to the (de)serializer, which
+/// then exchanges the raw JSON text instead of a string
+pub(crate) const TOKEN: &str = "$serde_json_wasm::private::RawValue";
+
+/// The unparsed JSON text of a value, borrowed from the input
+///
+/// A `&RawValue` field captures the text of a JSON value, such as a nested message, without
+/// interpreting it. The text is checked to be valid JSON, but kept exactly as it was written,
+/// including whitespace inside of it. It can then be forwarded or deserialized later, and is
+/// serialized as the same text.
+///
+/// ```
+/// use serde_derive::Deserialize;
+/// use serde_json_wasm::RawValue;
+///
+/// #[derive(Deserialize)]
+/// struct Envelope<'a> {
+///     action: &'a str,
+///     #[serde(borrow)]
+///     payload: &'a RawValue,
+/// }
+///
+/// let envelope: Envelope = serde_json_wasm::from_str(r#"{"action": "send", "payload": { "amount": 1 }}"#).unwrap();
+/// assert_eq!(envelope.payload.get(), r#"{ "amount": 1 }"#);
+/// ```
+///
+/// A `Box<RawValue>` owns a copy of the text, so it can be used in structs that implement
+/// `DeserializeOwned` and be stored, e.g. to execute a sub-message later.
+///
+/// The text is borrowed from the input, so this cannot be used with
+/// [`from_slice_mut`](crate::from_slice_mut), which changes the strings in the input.
+#[repr(transparent)]
+pub struct RawValue {
+    json: str,
+}
+
+impl RawValue {
+    fn from_borrowed(json: &str) -> &RawValue {
+        // SAFETY: `RawValue` is a transparent wrapper around `str`
+        unsafe { &*(json as *const str as *const RawValue) }
+    }
+
+    fn from_owned(json: Box<str>) -> Box<RawValue> {
+        // SAFETY: `RawValue` is a transparent wrapper around `str`
+        unsafe { Box::from_raw(Box::into_raw(json) as *mut RawValue) }
+    }
+
+    /// Checks that `json` is a valid JSON value and wraps it. Whitespace around the value is
+    /// removed.
+    pub fn from_string(json: String) -> Result<Box<RawValue>, crate::de::Error> {
+        if crate::from_str::<&RawValue>(&json)?.get().len() == json.len() {
+            return Ok(RawValue::from_owned(json.into_boxed_str()));
+        }
+        let trimmed = json.trim_matches(&[' ', '\t', '\n', '\r'][..]);
+        Ok(RawValue::from_owned(trimmed.into()))
+    }
+
+    /// Returns the JSON text
+    pub fn get(&self) -> &str {
+        &self.json
+    }
+}
+
+impl ToOwned for RawValue {
+    type Owned = Box<RawValue>;
+
+    fn to_owned(&self) -> Box<RawValue> {
+        RawValue::from_owned(self.json.into())
+    }
+}
+
+impl Clone for Box<RawValue> {
+    fn clone(&self) -> Self {
+        (**self).to_owned()
+    }
+}
+
+impl fmt::Debug for RawValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("RawValue").field(&&self.json).finish()
+    }
+}
+
+impl fmt::Display for RawValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.json)
+    }
+}
+
+impl PartialEq for RawValue {
+    fn eq(&self, other: &Self) -> bool {
+        self.json == other.json
+    }
+}
+
+impl ser::Serialize for RawValue {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: ser::Serializer,
+    {
+        serializer.serialize_newtype_struct(TOKEN, &self.json)
+    }
+}
+
+impl<'de: 'a, 'a> de::Deserialize<'de> for &'a RawValue {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        struct RawValueVisitor;
+
+        impl<'de> Visitor<'de> for RawValueVisitor {
+            type Value = &'de RawValue;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+                formatter.write_str("any valid JSON value")
+            }
+
+            fn visit_borrowed_str<E>(self, v: &'de str) -> Result<&'de RawValue, E>
+            where
+                E: de::Error,
+            {
+                Ok(RawValue::from_borrowed(v))
+            }
+        }
+
+        deserializer.deserialize_newtype_struct(TOKEN, RawValueVisitor)
+    }
+}
+
+impl<'de> de::Deserialize<'de> for Box<RawValue> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        struct BoxedRawValueVisitor;
+
+        impl<'de> Visitor<'de> for BoxedRawValueVisitor {
+            type Value = Box<RawValue>;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+                formatter.write_str("any valid JSON value")
+            }
+
+            fn visit_str<E>(self, v: &str) -> Result<Box<RawValue>, E>
+            where
+                E: de::Error,
+            {
+                Ok(RawValue::from_owned(v.into()))
+            }
+        }
+
+        deserializer.deserialize_newtype_struct(TOKEN, BoxedRawValueVisitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RawValue;
+    use crate::de::Error;
+    use crate::{from_slice_mut, from_str, to_string};
+    use serde_derive::{Deserialize, Serialize};
+
+    #[derive(Debug, Deserialize, Serialize, PartialEq)]
+    struct Envelope<'a> {
+        action: &'a str,
+        #[serde(borrow)]
+        payload: &'a RawValue,
+    }
+
+    #[test]
+    fn captures_the_text() {
+        let json = r#"{"action": "send", "payload": {"to": "cosmos1", "amount": [1.5, "\"2\""], "memo": null}}"#;
+        let envelope: Envelope<'_> = from_str(json).unwrap();
+        assert_eq!(envelope.action, "send");
+        assert_eq!(
+            envelope.payload.get(),
+            r#"{"to": "cosmos1", "amount": [1.5, "\"2\""], "memo": null}"#
+        );
+        assert_eq!(
+            to_string(&envelope).unwrap(),
+            r#"{"action":"send","payload":{"to": "cosmos1", "amount": [1.5, "\"2\""], "memo": null}}"#
+        );
+
+        for text in &["true", "-1e5", r#""a\nb""#, "[]", r#"{"a":[{}]}"#] {
+            let json = format!(" {} ", text);
+            let value: &RawValue = from_str(&json).unwrap();
+            assert_eq!(value.get(), *text);
+        }
+    }
+
+    #[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+    struct Pending {
+        id: u64,
+        msg: Box<RawValue>,
+    }
+
+    #[test]
+    fn owned() {
+        let json = br#"{"id": 7, "msg": {"bank": {"send": {"amount": [ ]}}}}"#.to_vec();
+        let pending: Pending = crate::from_slice(&json).unwrap();
+        drop(json);
+        assert_eq!(pending.msg.get(), r#"{"bank": {"send": {"amount": [ ]}}}"#);
+        assert_eq!(
+            to_string(&pending.clone()).unwrap(),
+            r#"{"id":7,"msg":{"bank": {"send": {"amount": [ ]}}}}"#
+        );
+
+        assert_eq!(
+            RawValue::from_string("\n [1, 2]\t".to_string()).map(|raw| raw.to_string()),
+            Ok("[1, 2]".to_string())
+        );
+        assert_eq!(
+            RawValue::from_string("[1, 2]".to_string()).map(|raw| raw.to_string()),
+            Ok("[1, 2]".to_string())
+        );
+        assert_eq!(
+            RawValue::from_string("[1, 2".to_string()),
+            Err(Error::EofWhileParsingList)
+        );
+        let borrowed: &RawValue = from_str("{}").unwrap();
+        assert_eq!(*borrowed.to_owned(), *borrowed);
+    }
+
+    #[test]
+    fn rejects_invalid_json() {
+        for json in &["tru", "1.", "[1, x]", r#"{"a" 1}"#, "nul", "[01]"] {
+            assert!(from_str::<&RawValue>(json).is_err(), "{}", json);
+        }
+        assert_eq!(
+            from_str::<&RawValue>(r#"{"a": 1"#),
+            Err(Error::EofWhileParsingObject)
+        );
+        assert_eq!(
+            from_str::<&RawValue>("[1] 2"),
+            Err(Error::TrailingCharacters)
+        );
+
+        let mut json = br#"{"action": "send", "payload": {}}"#.to_vec();
+        assert!(from_slice_mut::<Envelope<'_>>(&mut json).is_err());
+    }
+}
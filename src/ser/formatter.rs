@@ -0,0 +1,252 @@
+use alloc::vec::Vec;
+
+/// An escape sequence in a JSON string, passed to [`Formatter::write_char_escape`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CharEscape {
+    /// `"` as `\"`
+    Quote,
+    /// `\` as `\\`
+    ReverseSolidus,
+    /// U+0008 as `\b`
+    Backspace,
+    /// U+0009 as `\t`
+    Tab,
+    /// U+000A as `\n`
+    LineFeed,
+    /// U+000C as `\f`
+    FormFeed,
+    /// U+000D as `\r`
+    CarriageReturn,
+    /// Any other control character, as `\u00XX`
+    AsciiControl(u8),
+}
+
+/// Writes the tokens of the JSON text that the [`Serializer`](super::Serializer) produces
+///
+/// The serializer calls into a `Formatter` for every piece of output, so the layout can be
+/// changed without touching the serializer, e.g. to add whitespace or escape more characters.
+/// All methods have default implementations that write compact JSON, like [`CompactFormatter`].
+pub trait Formatter {
+    /// Writes `null`
+    fn write_null(&mut self, out: &mut Vec<u8>) {
+        out.extend_from_slice(b"null");
+    }
+
+    /// Writes `true` or `false`
+    fn write_bool(&mut self, out: &mut Vec<u8>, value: bool) {
+        out.extend_from_slice(if value { b"true" } else { b"false" });
+    }
+
+    /// Writes the decimal digits of an integer, with a leading `-` if it is negative
+    fn write_number_str(&mut self, out: &mut Vec<u8>, value: &str) {
+        out.extend_from_slice(value.as_bytes());
+    }
+
+    /// Writes the opening quote of a string
+    fn begin_string(&mut self, out: &mut Vec<u8>) {
+        out.push(b'"');
+    }
+
+    /// Writes the closing quote of a string
+    fn end_string(&mut self, out: &mut Vec<u8>) {
+        out.push(b'"');
+    }
+
+    /// Writes a part of a string that needs no escaping
+    fn write_string_fragment(&mut self, out: &mut Vec<u8>, fragment: &str) {
+        out.extend_from_slice(fragment.as_bytes());
+    }
+
+    /// Writes a character of a string that must be escaped
+    fn write_char_escape(&mut self, out: &mut Vec<u8>, escape: CharEscape) {
+        let c = match escape {
+            CharEscape::Quote => b'"',
+            CharEscape::ReverseSolidus => b'\\',
+            CharEscape::Backspace => b'b',
+            CharEscape::Tab => b't',
+            CharEscape::LineFeed => b'n',
+            CharEscape::FormFeed => b'f',
+            CharEscape::CarriageReturn => b'r',
+            CharEscape::AsciiControl(c) => {
+                let (hex1, hex2) = hex(c);
+                out.extend_from_slice(&[b'\\', b'u', b'0', b'0', hex1, hex2]);
+                return;
+            }
+        };
+        out.extend_from_slice(&[b'\\', c]);
+    }
+
+    /// Writes the opening bracket of an array
+    fn begin_array(&mut self, out: &mut Vec<u8>) {
+        out.push(b'[');
+    }
+
+    /// Writes the closing bracket of an array
+    fn end_array(&mut self, out: &mut Vec<u8>) {
+        out.push(b']');
+    }
+
+    /// Writes what comes before an element of an array, like a comma unless it is the `first`
+    fn begin_array_value(&mut self, out: &mut Vec<u8>, first: bool) {
+        if !first {
+            out.push(b',');
+        }
+    }
+
+    /// Called after an element of an array was written
+    fn end_array_value(&mut self, _out: &mut Vec<u8>) {}
+
+    /// Writes the opening brace of an object
+    fn begin_object(&mut self, out: &mut Vec<u8>) {
+        out.push(b'{');
+    }
+
+    /// Writes the closing brace of an object
+    fn end_object(&mut self, out: &mut Vec<u8>) {
+        out.push(b'}');
+    }
+
+    /// Writes what comes before a member of an object, like a comma unless it is the `first`
+    fn begin_object_key(&mut self, out: &mut Vec<u8>, first: bool) {
+        if !first {
+            out.push(b',');
+        }
+    }
+
+    /// Called after the key of a member was written
+    fn end_object_key(&mut self, _out: &mut Vec<u8>) {}
+
+    /// Writes the separator between the key and the value of a member
+    fn begin_object_value(&mut self, out: &mut Vec<u8>) {
+        out.push(b':');
+    }
+
+    /// Called after the value of a member was written
+    fn end_object_value(&mut self, _out: &mut Vec<u8>) {}
+
+    /// Writes JSON text as it is, like the content of a [`RawValue`](crate::RawValue)
+    fn write_raw_fragment(&mut self, out: &mut Vec<u8>, fragment: &str) {
+        out.extend_from_slice(fragment.as_bytes());
+    }
+}
+
+/// Upper-case hex for value in 0..16, encoded as ASCII bytes
+fn hex_4bit(c: u8) -> u8 {
+    if c <= 9 {
+        0x30 + c
+    } else {
+        0x41 + (c - 10)
+    }
+}
+
+/// Upper-case hex for value in 0..256, encoded as ASCII bytes
+fn hex(c: u8) -> (u8, u8) {
+    (hex_4bit(c >> 4), hex_4bit(c & 0x0F))
+}
+
+/// Writes JSON text without any whitespace, like `{"a":[1,2]}`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CompactFormatter;
+
+impl Formatter for CompactFormatter {}
+
+/// Writes every element and member on its own line, indented per level of nesting
+///
+/// Empty arrays and objects stay on one line.
+#[derive(Debug, Clone)]
+pub struct PrettyFormatter<'a> {
+    indent: &'a [u8],
+    /// Number of arrays and objects the formatter is in
+    depth: usize,
+    /// Whether the current array or object has elements or members
+    has_value: bool,
+}
+
+impl<'a> PrettyFormatter<'a> {
+    /// Creates a formatter that indents by two spaces
+    pub fn new() -> Self {
+        PrettyFormatter::with_indent(b"  ")
+    }
+
+    /// Creates a formatter that indents by `indent` per level of nesting
+    ///
+    /// `indent` is copied as it is, so it should only contain whitespace for the output to be
+    /// valid JSON.
+    pub fn with_indent(indent: &'a [u8]) -> Self {
+        PrettyFormatter {
+            indent,
+            depth: 0,
+            has_value: false,
+        }
+    }
+
+    /// Starts a new line at the current nesting level
+    fn write_newline(&self, out: &mut Vec<u8>) {
+        out.push(b'\n');
+        for _ in 0..self.depth {
+            out.extend_from_slice(self.indent);
+        }
+    }
+}
+
+impl Default for PrettyFormatter<'_> {
+    fn default() -> Self {
+        PrettyFormatter::new()
+    }
+}
+
+impl Formatter for PrettyFormatter<'_> {
+    fn begin_array(&mut self, out: &mut Vec<u8>) {
+        self.depth += 1;
+        self.has_value = false;
+        out.push(b'[');
+    }
+
+    fn end_array(&mut self, out: &mut Vec<u8>) {
+        self.depth -= 1;
+        if self.has_value {
+            self.write_newline(out);
+        }
+        out.push(b']');
+    }
+
+    fn begin_array_value(&mut self, out: &mut Vec<u8>, first: bool) {
+        if !first {
+            out.push(b',');
+        }
+        self.write_newline(out);
+    }
+
+    fn end_array_value(&mut self, _out: &mut Vec<u8>) {
+        self.has_value = true;
+    }
+
+    fn begin_object(&mut self, out: &mut Vec<u8>) {
+        self.depth += 1;
+        self.has_value = false;
+        out.push(b'{');
+    }
+
+    fn end_object(&mut self, out: &mut Vec<u8>) {
+        self.depth -= 1;
+        if self.has_value {
+            self.write_newline(out);
+        }
+        out.push(b'}');
+    }
+
+    fn begin_object_key(&mut self, out: &mut Vec<u8>, first: bool) {
+        if !first {
+            out.push(b',');
+        }
+        self.write_newline(out);
+    }
+
+    fn begin_object_value(&mut self, out: &mut Vec<u8>) {
+        out.extend_from_slice(b": ");
+    }
+
+    fn end_object_value(&mut self, _out: &mut Vec<u8>) {
+        self.has_value = true;
+    }
+}
@@ -0,0 +1,251 @@
+use alloc::string::ToString;
+use core::fmt;
+
+use serde::ser::{self, Impossible};
+
+use crate::ser::{Error, Formatter, Members, Result, Serializer};
+
+pub struct SerializeMap<'a, F> {
+    ser: &'a mut Serializer<F>,
+    first: bool,
+    members: Option<Members>,
+}
+
+impl<'a, F: Formatter> SerializeMap<'a, F> {
+    pub(crate) fn new(ser: &'a mut Serializer<F>) -> Self {
+        let members = ser.members();
+        SerializeMap {
+            ser,
+            first: true,
+            members,
+        }
+    }
+}
+
+impl<'a, F: Formatter> ser::SerializeMap for SerializeMap<'a, F> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_key<T>(&mut self, key: &T) -> Result<()>
+    where
+        T: ser::Serialize + ?Sized,
+    {
+        self.ser.begin_key(&mut self.members, self.first);
+        self.first = false;
+
+        key.serialize(MapKeySerializer {
+            ser: &mut *self.ser,
+        })?;
+        self.ser.end_key(&mut self.members);
+        Ok(())
+    }
+
+    fn serialize_value<T>(&mut self, value: &T) -> Result<()>
+    where
+        T: ser::Serialize + ?Sized,
+    {
+        self.ser.formatter.begin_object_value(&mut self.ser.buf);
+        value.serialize(&mut *self.ser)?;
+        self.ser.end_value(&mut self.members);
+        Ok(())
+    }
+
+    fn end(self) -> Result<Self::Ok> {
+        self.ser.end_object(self.members);
+        Ok(())
+    }
+}
+
+/// Serializes a map key, which must be a string in JSON
+///
+/// Integers, `bool`s and `char`s are quoted like in serde_json, e.g. `{"42":true}` for a
+/// `BTreeMap<u64, bool>`. Newtype structs are written as their content and C like enums as the
+/// name of their variant.
+struct MapKeySerializer<'a, F> {
+    ser: &'a mut Serializer<F>,
+}
+
+impl<'a, F: Formatter> MapKeySerializer<'a, F> {
+    /// Writes the value that `serialize` writes in quotes
+    fn quoted(self, serialize: impl FnOnce(&mut Serializer<F>) -> Result<()>) -> Result<()> {
+        self.ser.formatter.begin_string(&mut self.ser.buf);
+        serialize(&mut *self.ser)?;
+        self.ser.formatter.end_string(&mut self.ser.buf);
+        Ok(())
+    }
+}
+
+impl<'a, F: Formatter> ser::Serializer for MapKeySerializer<'a, F> {
+    type Ok = ();
+    type Error = Error;
+    type SerializeSeq = Impossible<(), Error>;
+    type SerializeTuple = Impossible<(), Error>;
+    type SerializeTupleStruct = Impossible<(), Error>;
+    type SerializeTupleVariant = Impossible<(), Error>;
+    type SerializeMap = Impossible<(), Error>;
+    type SerializeStruct = Impossible<(), Error>;
+    type SerializeStructVariant = Impossible<(), Error>;
+
+    fn serialize_str(self, v: &str) -> Result<()> {
+        ser::Serializer::serialize_str(&mut *self.ser, v)
+    }
+
+    fn serialize_bool(self, v: bool) -> Result<()> {
+        self.quoted(|ser| ser::Serializer::serialize_bool(ser, v))
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<()> {
+        self.quoted(|ser| ser::Serializer::serialize_i8(ser, v))
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<()> {
+        self.quoted(|ser| ser::Serializer::serialize_i16(ser, v))
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<()> {
+        self.quoted(|ser| ser::Serializer::serialize_i32(ser, v))
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<()> {
+        self.quoted(|ser| ser::Serializer::serialize_i64(ser, v))
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<()> {
+        self.quoted(|ser| ser::Serializer::serialize_u8(ser, v))
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<()> {
+        self.quoted(|ser| ser::Serializer::serialize_u16(ser, v))
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<()> {
+        self.quoted(|ser| ser::Serializer::serialize_u32(ser, v))
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<()> {
+        self.quoted(|ser| ser::Serializer::serialize_u64(ser, v))
+    }
+
+    fn serialize_i128(self, v: i128) -> Result<()> {
+        self.quoted(|ser| ser.write_i128(v))
+    }
+
+    fn serialize_u128(self, v: u128) -> Result<()> {
+        self.quoted(|ser| ser.write_u128(v))
+    }
+
+    fn serialize_f32(self, _v: f32) -> Result<()> {
+        Err(Error::KeyMustBeAString)
+    }
+
+    fn serialize_f64(self, _v: f64) -> Result<()> {
+        Err(Error::KeyMustBeAString)
+    }
+
+    fn serialize_char(self, v: char) -> Result<()> {
+        self.serialize_str(v.encode_utf8(&mut [0; 4]))
+    }
+
+    fn serialize_bytes(self, _v: &[u8]) -> Result<()> {
+        Err(Error::KeyMustBeAString)
+    }
+
+    fn serialize_none(self) -> Result<()> {
+        Err(Error::KeyMustBeAString)
+    }
+
+    fn serialize_some<T>(self, _value: &T) -> Result<()>
+    where
+        T: ser::Serialize + ?Sized,
+    {
+        Err(Error::KeyMustBeAString)
+    }
+
+    fn serialize_unit(self) -> Result<()> {
+        Err(Error::KeyMustBeAString)
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<()> {
+        Err(Error::KeyMustBeAString)
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<()> {
+        self.serialize_str(variant)
+    }
+
+    fn serialize_newtype_struct<T>(self, _name: &'static str, value: &T) -> Result<()>
+    where
+        T: ser::Serialize + ?Sized,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<()>
+    where
+        T: ser::Serialize + ?Sized,
+    {
+        Err(Error::KeyMustBeAString)
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq> {
+        Err(Error::KeyMustBeAString)
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple> {
+        Err(Error::KeyMustBeAString)
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleStruct> {
+        Err(Error::KeyMustBeAString)
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant> {
+        Err(Error::KeyMustBeAString)
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap> {
+        Err(Error::KeyMustBeAString)
+    }
+
+    fn serialize_struct(self, _name: &'static str, _len: usize) -> Result<Self::SerializeStruct> {
+        Err(Error::KeyMustBeAString)
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant> {
+        Err(Error::KeyMustBeAString)
+    }
+
+    fn collect_str<T>(self, value: &T) -> Result<()>
+    where
+        T: fmt::Display + ?Sized,
+    {
+        self.serialize_str(&value.to_string())
+    }
+}
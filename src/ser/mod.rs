@@ -1,15 +1,29 @@
 //! Serialize a Rust data structure into JSON data
 
-use std::{error, fmt};
+use alloc::string::{String, ToString};
+use core::{fmt, str};
+#[cfg(feature = "std")]
+use std::error;
 
 use serde::ser;
 
-use std::vec::Vec;
+use alloc::vec::Vec;
 
+use crate::Category;
+
+use self::map::SerializeMap;
 use self::seq::SerializeSeq;
+use self::sort::Members;
 use self::struct_::SerializeStruct;
 
+pub use self::formatter::{CharEscape, CompactFormatter, Formatter, PrettyFormatter};
+pub use self::options::Options;
+
+mod formatter;
+mod map;
+mod options;
 mod seq;
+mod sort;
 mod struct_;
 
 /// Serialization result
@@ -24,6 +38,18 @@ pub enum Error {
 
     /// Custom error message from serde
     Custom(String),
+
+    /// A map key that cannot be written as a string, like an array
+    KeyMustBeAString,
+}
+
+impl Error {
+    /// Categorizes the cause of this error. Serialization errors are always [`Category::Data`].
+    pub fn classify(&self) -> Category {
+        match self {
+            Error::BufferFull | Error::Custom(_) | Error::KeyMustBeAString => Category::Data,
+        }
+    }
 }
 
 impl From<()> for Error {
@@ -38,6 +64,7 @@ impl From<u8> for Error {
     }
 }
 
+#[cfg(feature = "std")]
 impl error::Error for Error {
     fn source(&self) -> Option<&(dyn error::Error + 'static)> {
         None
@@ -48,19 +75,31 @@ impl error::Error for Error {
     }
 }
 
+/// Without `std`, serde requires its own stand-in for `std::error::Error`
+#[cfg(not(feature = "std"))]
+impl ser::StdError for Error {}
+
 impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             Error::BufferFull => write!(f, "Buffer is full"),
             Error::Custom(msg) => write!(f, "{}", &msg),
+            Error::KeyMustBeAString => write!(f, "Map key must be a string"),
         }
     }
 }
 
 /// Serializer implements serde::ser::Serializer and allows us to serialize a
 /// serde struct into JSON
-pub struct Serializer {
+///
+/// The tokens are written by a [`Formatter`], which decides about the layout of the output.
+pub struct Serializer<F = CompactFormatter> {
     buf: Vec<u8>,
+    /// Set while the text of a [`Number`](crate::Number) or [`RawValue`](crate::RawValue) is
+    /// serialized, which is written as it is instead of as a string
+    raw: bool,
+    formatter: F,
+    options: Options,
 }
 
 /// Number of bytes reserved by default for the output JSON
@@ -68,10 +107,103 @@ static INITIAL_CAPACITY: usize = 1024;
 
 impl Serializer {
     fn new() -> Self {
+        Serializer::with_formatter(CompactFormatter)
+    }
+}
+
+impl<F: Formatter> Serializer<F> {
+    fn with_formatter(formatter: F) -> Self {
+        Serializer::with_formatter_and_options(formatter, &Options::default())
+    }
+
+    fn with_formatter_and_options(formatter: F, options: &Options) -> Self {
         Serializer {
             buf: Vec::with_capacity(INITIAL_CAPACITY),
+            raw: false,
+            formatter,
+            options: options.clone(),
+        }
+    }
+
+    /// Writes the text of an integer
+    fn write_digits(&mut self, digits: &[u8]) {
+        // integers are made of ASCII digits and maybe a `-`
+        let digits = unsafe { str::from_utf8_unchecked(digits) };
+        self.formatter.write_number_str(&mut self.buf, digits);
+    }
+
+    /// Returns where to record the members of a new object, if they are sorted
+    pub(crate) fn members(&self) -> Option<Members> {
+        if self.options.sort_keys {
+            Some(Members::default())
+        } else {
+            None
+        }
+    }
+
+    /// Starts the key of a member of an object
+    pub(crate) fn begin_key(&mut self, members: &mut Option<Members>, first: bool) {
+        let start = self.buf.len();
+        self.formatter.begin_object_key(&mut self.buf, first);
+        if let Some(members) = members {
+            members.begin_key(start, self.buf.len());
         }
     }
+
+    /// Ends the key of a member of an object
+    pub(crate) fn end_key(&mut self, members: &mut Option<Members>) {
+        if let Some(members) = members {
+            members.end_key(self.buf.len());
+        }
+        self.formatter.end_object_key(&mut self.buf);
+    }
+
+    /// Ends the value of a member of an object
+    pub(crate) fn end_value(&mut self, members: &mut Option<Members>) {
+        self.formatter.end_object_value(&mut self.buf);
+        if let Some(members) = members {
+            members.end_value(self.buf.len());
+        }
+    }
+
+    /// Ends an object, sorting its `members` if they were recorded
+    pub(crate) fn end_object(&mut self, members: Option<Members>) {
+        if let Some(members) = members {
+            members.sort(&mut self.buf);
+        }
+        self.formatter.end_object(&mut self.buf);
+    }
+
+    /// Writes a struct field, which needs no escaping, as an object key
+    pub(crate) fn write_field_key(
+        &mut self,
+        members: &mut Option<Members>,
+        first: bool,
+        key: &str,
+    ) {
+        self.begin_key(members, first);
+        self.formatter.begin_string(&mut self.buf);
+        self.formatter.write_string_fragment(&mut self.buf, key);
+        self.formatter.end_string(&mut self.buf);
+        self.end_key(members);
+        self.formatter.begin_object_value(&mut self.buf);
+    }
+
+    /// Starts the object around the content of an enum variant
+    fn begin_variant(&mut self, variant: &str) -> Result<()> {
+        self.formatter.begin_object(&mut self.buf);
+        self.formatter.begin_object_key(&mut self.buf, true);
+        ser::Serializer::serialize_str(&mut *self, variant)?;
+        self.formatter.end_object_key(&mut self.buf);
+        self.formatter.begin_object_value(&mut self.buf);
+        Ok(())
+    }
+
+    /// Ends the object around the content of an enum variant
+    pub(crate) fn end_variant(&mut self) {
+        self.formatter.end_object_value(&mut self.buf);
+        self.formatter.end_object(&mut self.buf);
+    }
 }
 
 // NOTE(serialize_*signed) This is basically the numtoa implementation minus the lookup tables,
@@ -93,7 +225,7 @@ macro_rules! serialize_unsigned {
             }
         }
 
-        $self.buf.extend_from_slice(&buf[i..]);
+        $self.write_digits(&buf[i..]);
         Ok(())
     }};
 }
@@ -127,42 +259,50 @@ macro_rules! serialize_signed {
         } else {
             i += 1;
         }
-        $self.buf.extend_from_slice(&buf[i..]);
+        $self.write_digits(&buf[i..]);
         Ok(())
     }};
 }
 
-/// Upper-case hex for value in 0..16, encoded as ASCII bytes
-fn hex_4bit(c: u8) -> u8 {
-    if c <= 9 {
-        0x30 + c
-    } else {
-        0x41 + (c - 10)
+impl<F: Formatter> Serializer<F> {
+    /// Writes the digits of an `i128`, unquoted even with [`Options::quote_large_integers`]
+    pub(crate) fn write_i128(&mut self, v: i128) -> Result<()> {
+        // -170141183460469231731687303715884105728
+        serialize_signed!(self, 40, v, i128, u128)
+    }
+
+    /// Writes the digits of a `u128`, unquoted even with [`Options::quote_large_integers`]
+    pub(crate) fn write_u128(&mut self, v: u128) -> Result<()> {
+        // 340282366920938463463374607431768211455
+        serialize_unsigned!(self, 39, v)
     }
-}
 
-/// Upper-case hex for value in 0..256, encoded as ASCII bytes
-fn hex(c: u8) -> (u8, u8) {
-    (hex_4bit(c >> 4), hex_4bit(c & 0x0F))
+    /// Writes a 128-bit integer, in a string with [`Options::quote_large_integers`]
+    fn write_large_integer(&mut self, write: impl FnOnce(&mut Self) -> Result<()>) -> Result<()> {
+        if self.options.quote_large_integers {
+            self.formatter.begin_string(&mut self.buf);
+            write(self)?;
+            self.formatter.end_string(&mut self.buf);
+            Ok(())
+        } else {
+            write(self)
+        }
+    }
 }
 
-impl<'a> ser::Serializer for &'a mut Serializer {
+impl<'a, F: Formatter> ser::Serializer for &'a mut Serializer<F> {
     type Ok = ();
     type Error = Error;
-    type SerializeSeq = SerializeSeq<'a>;
-    type SerializeTuple = SerializeSeq<'a>;
-    type SerializeTupleStruct = Unreachable;
-    type SerializeTupleVariant = SerializeSeq<'a>;
-    type SerializeMap = Unreachable;
-    type SerializeStruct = SerializeStruct<'a>;
-    type SerializeStructVariant = SerializeStruct<'a>;
+    type SerializeSeq = SerializeSeq<'a, F>;
+    type SerializeTuple = SerializeSeq<'a, F>;
+    type SerializeTupleStruct = SerializeSeq<'a, F>;
+    type SerializeTupleVariant = SerializeSeq<'a, F>;
+    type SerializeMap = SerializeMap<'a, F>;
+    type SerializeStruct = SerializeStruct<'a, F>;
+    type SerializeStructVariant = SerializeStruct<'a, F>;
 
     fn serialize_bool(self, v: bool) -> Result<Self::Ok> {
-        if v {
-            self.buf.extend_from_slice(b"true");
-        } else {
-            self.buf.extend_from_slice(b"false");
-        }
+        self.formatter.write_bool(&mut self.buf, v);
         Ok(())
     }
 
@@ -206,6 +346,14 @@ impl<'a> ser::Serializer for &'a mut Serializer {
         serialize_unsigned!(self, 20, v)
     }
 
+    fn serialize_i128(self, v: i128) -> Result<Self::Ok> {
+        self.write_large_integer(|ser| ser.write_i128(v))
+    }
+
+    fn serialize_u128(self, v: u128) -> Result<Self::Ok> {
+        self.write_large_integer(|ser| ser.write_u128(v))
+    }
+
     fn serialize_f32(self, _v: f32) -> Result<Self::Ok> {
         unreachable!()
     }
@@ -214,12 +362,18 @@ impl<'a> ser::Serializer for &'a mut Serializer {
         unreachable!()
     }
 
-    fn serialize_char(self, _v: char) -> Result<Self::Ok> {
-        unreachable!()
+    fn serialize_char(self, v: char) -> Result<Self::Ok> {
+        self.serialize_str(v.encode_utf8(&mut [0; 4]))
     }
 
     fn serialize_str(self, v: &str) -> Result<Self::Ok> {
-        self.buf.push(b'"');
+        if self.raw {
+            self.raw = false;
+            self.formatter.write_raw_fragment(&mut self.buf, v);
+            return Ok(());
+        }
+
+        self.formatter.begin_string(&mut self.buf);
 
         // Do escaping according to "6. MUST represent all strings (including object member names) in
         // their minimal-length UTF-8 encoding": https://gibson042.github.io/canonicaljson-spec/
@@ -229,76 +383,82 @@ impl<'a> ser::Serializer for &'a mut Serializer {
         // cannot exist in a Rust String. If they do, the bug is in the String constructor.
         // An excellent explanation is available at https://www.youtube.com/watch?v=HhIEDWmQS3w
 
-        // Temporary storage for encoded a single char.
-        // A char is up to 4 bytes long when encoded to UTF-8.
-        let mut encoding_tmp = [0u8; 4];
-
-        for c in v.chars() {
-            match c {
-                '\\' => {
-                    self.buf.push(b'\\');
-                    self.buf.push(b'\\');
-                }
-                '"' => {
-                    self.buf.push(b'\\');
-                    self.buf.push(b'"');
-                }
-                '\u{0008}' => {
-                    self.buf.push(b'\\');
-                    self.buf.push(b'b');
-                }
-                '\u{0009}' => {
-                    self.buf.push(b'\\');
-                    self.buf.push(b't');
-                }
-                '\u{000A}' => {
-                    self.buf.push(b'\\');
-                    self.buf.push(b'n');
-                }
-                '\u{000C}' => {
-                    self.buf.push(b'\\');
-                    self.buf.push(b'f');
-                }
-                '\u{000D}' => {
-                    self.buf.push(b'\\');
-                    self.buf.push(b'r');
-                }
-                '\u{0000}'..='\u{001F}' => {
-                    self.buf.push(b'\\');
-                    self.buf.push(b'u');
-                    self.buf.push(b'0');
-                    self.buf.push(b'0');
-                    let (hex1, hex2) = hex(c as u8);
-                    self.buf.push(hex1);
-                    self.buf.push(hex2);
-                }
-                _ => {
-                    if c.len_utf8() == 1 {
-                        self.buf.push(c as u8);
-                    } else {
-                        let encoded = c.encode_utf8(&mut encoding_tmp as &mut [u8]);
-                        self.buf.extend_from_slice(encoded.as_bytes());
-                    }
-                }
+        // Start of the part of `v` that was not written yet. All escaped characters are ASCII, so
+        // the parts in between are whole characters.
+        let mut start = 0;
+        for (i, &byte) in v.as_bytes().iter().enumerate() {
+            let escape = match byte {
+                b'"' => CharEscape::Quote,
+                b'\\' => CharEscape::ReverseSolidus,
+                0x08 => CharEscape::Backspace,
+                0x09 => CharEscape::Tab,
+                0x0A => CharEscape::LineFeed,
+                0x0C => CharEscape::FormFeed,
+                0x0D => CharEscape::CarriageReturn,
+                0x00..=0x1F => CharEscape::AsciiControl(byte),
+                _ => continue,
+            };
+            if start < i {
+                self.formatter
+                    .write_string_fragment(&mut self.buf, &v[start..i]);
             }
+            self.formatter.write_char_escape(&mut self.buf, escape);
+            start = i + 1;
+        }
+        if start < v.len() {
+            self.formatter
+                .write_string_fragment(&mut self.buf, &v[start..]);
         }
 
-        self.buf.push(b'"');
+        self.formatter.end_string(&mut self.buf);
         Ok(())
     }
 
-    fn serialize_bytes(self, _v: &[u8]) -> Result<Self::Ok> {
-        unreachable!()
+    /// Bytes are serialized as an array of numbers like in serde_json, or as a base64 string with
+    /// the `base64` feature, or as configured with [`Options::hex_bytes`] and
+    /// [`Options::byte_arrays`]
+    fn serialize_bytes(self, v: &[u8]) -> Result<Self::Ok> {
+        if self.options.hex_bytes {
+            const DIGITS: &[u8; 16] = b"0123456789abcdef";
+
+            self.formatter.begin_string(&mut self.buf);
+            // hex digits need no escaping
+            self.buf.reserve(v.len() * 2);
+            for byte in v {
+                self.buf.push(DIGITS[(byte >> 4) as usize]);
+                self.buf.push(DIGITS[(byte & 0x0F) as usize]);
+            }
+            self.formatter.end_string(&mut self.buf);
+            return Ok(());
+        }
+        #[cfg(feature = "base64")]
+        {
+            if !self.options.byte_arrays {
+                self.formatter.begin_string(&mut self.buf);
+                // the base64 alphabet needs no escaping
+                crate::base64::encode_into(v, &mut self.buf);
+                self.formatter.end_string(&mut self.buf);
+                return Ok(());
+            }
+        }
+
+        use serde::ser::SerializeSeq;
+
+        let mut seq = self.serialize_seq(Some(v.len()))?;
+        for byte in v {
+            seq.serialize_element(byte)?;
+        }
+        seq.end()
     }
 
     fn serialize_none(self) -> Result<Self::Ok> {
-        self.buf.extend_from_slice(b"null");
+        self.formatter.write_null(&mut self.buf);
         Ok(())
     }
 
-    fn serialize_some<T: ?Sized>(self, value: &T) -> Result<Self::Ok>
+    fn serialize_some<T>(self, value: &T) -> Result<Self::Ok>
     where
-        T: ser::Serialize,
+        T: ser::Serialize + ?Sized,
     {
         value.serialize(self)
     }
@@ -306,13 +466,13 @@ impl<'a> ser::Serializer for &'a mut Serializer {
     fn serialize_unit(self) -> Result<Self::Ok> {
         // The unit type is a zero element tuple, so the consistent way to serialize this would be "[]".
         // However, for compatibility with serde_json we serialize to "null".
-        self.buf.extend_from_slice(b"null");
+        self.formatter.write_null(&mut self.buf);
         Ok(())
     }
 
     fn serialize_unit_struct(self, _name: &'static str) -> Result<Self::Ok> {
         // Unit struct is serialized to (serde_json compatible) "null"
-        self.buf.extend_from_slice(b"null");
+        self.formatter.write_null(&mut self.buf);
         Ok(())
     }
 
@@ -325,14 +485,21 @@ impl<'a> ser::Serializer for &'a mut Serializer {
         self.serialize_str(variant)
     }
 
-    fn serialize_newtype_struct<T: ?Sized>(self, _name: &'static str, value: &T) -> Result<Self::Ok>
+    /// A [`Number`](crate::Number) or [`RawValue`](crate::RawValue) is serialized as its text
+    fn serialize_newtype_struct<T>(self, name: &'static str, value: &T) -> Result<Self::Ok>
     where
-        T: ser::Serialize,
+        T: ser::Serialize + ?Sized,
     {
+        if name == crate::number::TOKEN || name == crate::raw::TOKEN {
+            self.raw = true;
+            let result = value.serialize(&mut *self);
+            self.raw = false;
+            return result;
+        }
         value.serialize(&mut *self)
     }
 
-    fn serialize_newtype_variant<T: ?Sized>(
+    fn serialize_newtype_variant<T>(
         self,
         _name: &'static str,
         _variant_index: u32,
@@ -340,18 +507,16 @@ impl<'a> ser::Serializer for &'a mut Serializer {
         value: &T,
     ) -> Result<Self::Ok>
     where
-        T: ser::Serialize,
+        T: ser::Serialize + ?Sized,
     {
-        self.buf.push(b'{');
-        self.serialize_str(variant)?;
-        self.buf.push(b':');
+        self.begin_variant(variant)?;
         value.serialize(&mut *self)?;
-        self.buf.push(b'}');
+        self.end_variant();
         Ok(())
     }
 
     fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq> {
-        self.buf.push(b'[');
+        self.formatter.begin_array(&mut self.buf);
 
         Ok(SerializeSeq::new(self))
     }
@@ -363,9 +528,9 @@ impl<'a> ser::Serializer for &'a mut Serializer {
     fn serialize_tuple_struct(
         self,
         _name: &'static str,
-        _len: usize,
+        len: usize,
     ) -> Result<Self::SerializeTupleStruct> {
-        unreachable!()
+        self.serialize_seq(Some(len))
     }
 
     fn serialize_tuple_variant(
@@ -375,18 +540,18 @@ impl<'a> ser::Serializer for &'a mut Serializer {
         variant: &'static str,
         len: usize,
     ) -> Result<Self::SerializeTupleVariant> {
-        self.buf.push(b'{');
-        self.serialize_str(variant)?;
-        self.buf.push(b':');
+        self.begin_variant(variant)?;
         self.serialize_tuple(len)
     }
 
     fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap> {
-        unreachable!()
+        self.formatter.begin_object(&mut self.buf);
+
+        Ok(SerializeMap::new(self))
     }
 
     fn serialize_struct(self, _name: &'static str, _len: usize) -> Result<Self::SerializeStruct> {
-        self.buf.push(b'{');
+        self.formatter.begin_object(&mut self.buf);
 
         Ok(SerializeStruct::new(self))
     }
@@ -398,15 +563,13 @@ impl<'a> ser::Serializer for &'a mut Serializer {
         variant: &'static str,
         len: usize,
     ) -> Result<Self::SerializeStructVariant> {
-        self.buf.push(b'{');
-        self.serialize_str(variant)?;
-        self.buf.push(b':');
+        self.begin_variant(variant)?;
         self.serialize_struct(name, len)
     }
 
-    fn collect_str<T: ?Sized>(self, _value: &T) -> Result<Self::Ok>
+    fn collect_str<T>(self, _value: &T) -> Result<Self::Ok>
     where
-        T: fmt::Display,
+        T: fmt::Display + ?Sized,
     {
         unreachable!()
     }
@@ -432,6 +595,183 @@ where
     Ok(ser.buf)
 }
 
+/// Serializes the given data structure as a string of JSON text, using the given [`Options`]
+pub fn to_string_with_options<T>(value: &T, options: &Options) -> Result<String>
+where
+    T: ser::Serialize + ?Sized,
+{
+    to_vec_with_options(value, options).map(|buf| unsafe { String::from_utf8_unchecked(buf) })
+}
+
+/// Serializes the given data structure as a JSON byte vector, using the given [`Options`]
+///
+/// ```
+/// use serde_bytes::Bytes;
+/// use serde_json_wasm::ser::{to_vec_with_options, Options};
+///
+/// let options = Options::default().hex_bytes(true);
+/// assert_eq!(
+///     to_vec_with_options(Bytes::new(&[0xde, 0xad, 0xbe, 0xef]), &options).unwrap(),
+///     br#""deadbeef""#
+/// );
+/// ```
+pub fn to_vec_with_options<T>(value: &T, options: &Options) -> Result<Vec<u8>>
+where
+    T: ser::Serialize + ?Sized,
+{
+    let mut ser = Serializer::with_formatter_and_options(CompactFormatter, options);
+    value.serialize(&mut ser)?;
+    Ok(ser.buf)
+}
+
+/// Serializes the given data structure as a pretty printed string of JSON text
+///
+/// Every element and member goes on its own line, indented by two spaces per level of nesting.
+/// Empty arrays and objects stay on one line.
+///
+/// ```
+/// use serde_derive::Serialize;
+///
+/// #[derive(Serialize)]
+/// struct Coin {
+///     denom: &'static str,
+///     amount: u64,
+/// }
+///
+/// let coins = [Coin { denom: "uatom", amount: 1 }];
+/// assert_eq!(
+///     serde_json_wasm::to_string_pretty(&coins).unwrap(),
+///     "[\n  {\n    \"denom\": \"uatom\",\n    \"amount\": 1\n  }\n]"
+/// );
+/// ```
+pub fn to_string_pretty<T>(value: &T) -> Result<String>
+where
+    T: ser::Serialize + ?Sized,
+{
+    let mut ser = Serializer::with_formatter(PrettyFormatter::new());
+    value.serialize(&mut ser)?;
+    Ok(unsafe { String::from_utf8_unchecked(ser.buf) })
+}
+
+/// Serializes the given data structure as a pretty printed JSON byte vector, see
+/// [`to_string_pretty`]
+pub fn to_vec_pretty<T>(value: &T) -> Result<Vec<u8>>
+where
+    T: ser::Serialize + ?Sized,
+{
+    to_vec_with_formatter(value, PrettyFormatter::new())
+}
+
+/// Serializes the given data structure as a pretty printed string of JSON text, indented by
+/// `indent` per level of nesting
+///
+/// ```
+/// assert_eq!(
+///     serde_json_wasm::ser::to_string_with_indent(&[1, 2], "\t").unwrap(),
+///     "[\n\t1,\n\t2\n]"
+/// );
+/// ```
+pub fn to_string_with_indent<T>(value: &T, indent: &str) -> Result<String>
+where
+    T: ser::Serialize + ?Sized,
+{
+    let mut ser = Serializer::with_formatter(PrettyFormatter::with_indent(indent.as_bytes()));
+    value.serialize(&mut ser)?;
+    Ok(unsafe { String::from_utf8_unchecked(ser.buf) })
+}
+
+/// Serializes the given data structure as a pretty printed JSON byte vector, indented by
+/// `indent` per level of nesting
+///
+/// This is [`to_vec_with_formatter`] with [`PrettyFormatter::with_indent`], so `indent` should
+/// only contain whitespace for the output to be valid JSON.
+pub fn to_vec_with_indent<T>(value: &T, indent: &[u8]) -> Result<Vec<u8>>
+where
+    T: ser::Serialize + ?Sized,
+{
+    to_vec_with_formatter(value, PrettyFormatter::with_indent(indent))
+}
+
+/// Serializes the given data structure as a JSON byte vector laid out by `formatter`
+///
+/// ```
+/// use serde_json_wasm::ser::{to_vec_with_formatter, Formatter};
+///
+/// /// Escapes `<` and `>`, so the output can be embedded in HTML
+/// struct HtmlFormatter;
+///
+/// impl Formatter for HtmlFormatter {
+///     fn write_string_fragment(&mut self, out: &mut Vec<u8>, fragment: &str) {
+///         for c in fragment.chars() {
+///             match c {
+///                 '<' => out.extend_from_slice(b"\\u003c"),
+///                 '>' => out.extend_from_slice(b"\\u003e"),
+///                 c => out.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes()),
+///             }
+///         }
+///     }
+/// }
+///
+/// assert_eq!(
+///     to_vec_with_formatter(&["<script>"], HtmlFormatter).unwrap(),
+///     br#"["\u003cscript\u003e"]"#
+/// );
+/// ```
+pub fn to_vec_with_formatter<T, F>(value: &T, formatter: F) -> Result<Vec<u8>>
+where
+    T: ser::Serialize + ?Sized,
+    F: Formatter,
+{
+    to_vec_with_formatter_and_options(value, formatter, &Options::default())
+}
+
+/// Serializes the given data structure as a JSON byte vector laid out by `formatter`, using the
+/// given [`Options`]
+///
+/// ```
+/// use serde_json_wasm::ser::{to_vec_with_formatter_and_options, Options, PrettyFormatter};
+/// use std::collections::HashMap;
+///
+/// let mut coin = HashMap::new();
+/// coin.insert("denom", "uatom");
+/// coin.insert("amount", "1");
+/// let options = Options::default().sort_keys(true);
+/// assert_eq!(
+///     to_vec_with_formatter_and_options(&coin, PrettyFormatter::new(), &options).unwrap(),
+///     b"{\n  \"amount\": \"1\",\n  \"denom\": \"uatom\"\n}"
+/// );
+/// ```
+pub fn to_vec_with_formatter_and_options<T, F>(
+    value: &T,
+    formatter: F,
+    options: &Options,
+) -> Result<Vec<u8>>
+where
+    T: ser::Serialize + ?Sized,
+    F: Formatter,
+{
+    let mut ser = Serializer::with_formatter_and_options(formatter, options);
+    value.serialize(&mut ser)?;
+    Ok(ser.buf)
+}
+
+/// Serializes the given values as a JSON text sequence (RFC 7464, `application/json-seq`)
+///
+/// Every value is written as an ASCII record separator (0x1E), its JSON text and a line feed.
+pub fn to_json_seq<I>(values: I) -> Result<Vec<u8>>
+where
+    I: IntoIterator,
+    I::Item: ser::Serialize,
+{
+    let mut ser = Serializer::new();
+    for value in values {
+        ser.buf.push(0x1E);
+        ser::Serialize::serialize(&value, &mut ser)?;
+        ser.buf.push(b'\n');
+    }
+    Ok(ser.buf)
+}
+
 impl ser::Error for Error {
     fn custom<T>(msg: T) -> Self
     where
@@ -471,36 +811,13 @@ impl ser::SerializeTupleVariant for Unreachable {
     }
 }
 
-impl ser::SerializeMap for Unreachable {
-    type Ok = ();
-    type Error = Error;
-
-    fn serialize_key<T: ?Sized>(&mut self, _key: &T) -> Result<()>
-    where
-        T: ser::Serialize,
-    {
-        unreachable!()
-    }
-
-    fn serialize_value<T: ?Sized>(&mut self, _value: &T) -> Result<()>
-    where
-        T: ser::Serialize,
-    {
-        unreachable!()
-    }
-
-    fn end(self) -> Result<Self::Ok> {
-        unreachable!()
-    }
-}
-
 impl ser::SerializeStructVariant for Unreachable {
     type Ok = ();
     type Error = Error;
 
-    fn serialize_field<T: ?Sized>(&mut self, _key: &'static str, _value: &T) -> Result<()>
+    fn serialize_field<T>(&mut self, _key: &'static str, _value: &T) -> Result<()>
     where
-        T: ser::Serialize,
+        T: ser::Serialize + ?Sized,
     {
         unreachable!()
     }
@@ -513,6 +830,7 @@ impl ser::SerializeStructVariant for Unreachable {
 #[cfg(test)]
 mod tests {
     use super::to_string;
+    use serde_bytes::ByteBuf;
     use serde_derive::Serialize;
 
     #[test]
@@ -597,6 +915,93 @@ mod tests {
             to_string::<i64>(&std::i64::MIN).unwrap(),
             "-9223372036854775808"
         );
+
+        assert_eq!(to_string::<u128>(&0).unwrap(), "0");
+        assert_eq!(
+            to_string::<u128>(&18446744073709551616).unwrap(),
+            "18446744073709551616"
+        );
+        assert_eq!(
+            to_string::<u128>(&std::u128::MAX).unwrap(),
+            "340282366920938463463374607431768211455"
+        );
+
+        assert_eq!(to_string::<i128>(&0).unwrap(), "0");
+        assert_eq!(to_string::<i128>(&-1).unwrap(), "-1");
+        assert_eq!(
+            to_string::<i128>(&std::i128::MAX).unwrap(),
+            "170141183460469231731687303715884105727"
+        );
+        assert_eq!(
+            to_string::<i128>(&std::i128::MIN).unwrap(),
+            "-170141183460469231731687303715884105728"
+        );
+        assert_eq!(
+            crate::from_str::<i128>(&to_string(&std::i128::MIN).unwrap()),
+            Ok(std::i128::MIN)
+        );
+    }
+
+    #[test]
+    fn quote_large_integers() {
+        use super::{to_string_with_options, Options};
+        use crate::de::{from_str_with_options, Options as DeOptions};
+        use serde_derive::Deserialize;
+        use std::collections::BTreeMap;
+
+        #[derive(Debug, Serialize, Deserialize, PartialEq)]
+        struct Coin {
+            denom: String,
+            amount: u128,
+            height: u64,
+        }
+
+        let options = Options::default().quote_large_integers(true);
+        let coin = Coin {
+            denom: "uatom".to_string(),
+            amount: 340282366920938463463374607431768211455,
+            height: 42,
+        };
+        let json = to_string_with_options(&coin, &options).unwrap();
+        // only 128-bit integers are quoted, whatever their value
+        assert_eq!(
+            json,
+            r#"{"denom":"uatom","amount":"340282366920938463463374607431768211455","height":42}"#
+        );
+        assert_eq!(to_string_with_options(&0u128, &options).unwrap(), r#""0""#);
+        assert_eq!(
+            to_string_with_options(&std::i128::MIN, &options).unwrap(),
+            r#""-170141183460469231731687303715884105728""#
+        );
+        assert_eq!(to_string(&0u128).unwrap(), "0");
+
+        // read back with quoted integers allowed
+        let de_options = DeOptions::default().allow_quoted_integers(true);
+        assert_eq!(from_str_with_options(&json, &de_options), Ok(coin));
+
+        // map keys are quoted once
+        let mut balances = BTreeMap::new();
+        balances.insert(-1i128, 1u128);
+        assert_eq!(
+            to_string_with_options(&balances, &options).unwrap(),
+            r#"{"-1":"1"}"#
+        );
+    }
+
+    #[test]
+    fn char() {
+        assert_eq!(to_string(&'a').unwrap(), r#""a""#);
+        assert_eq!(to_string(&'é').unwrap(), r#""é""#);
+        assert_eq!(to_string(&'👏').unwrap(), r#""👏""#);
+        assert_eq!(to_string(&'"').unwrap(), r#""\"""#);
+        assert_eq!(to_string(&'\\').unwrap(), r#""\\""#);
+        assert_eq!(to_string(&'\n').unwrap(), r#""\n""#);
+        assert_eq!(to_string(&'\u{1}').unwrap(), r#""\u0001""#);
+        assert_eq!(to_string(&['x', 'y']).unwrap(), r#"["x","y"]"#);
+        assert_eq!(
+            crate::from_str::<char>(&to_string(&'\u{7f}').unwrap()),
+            Ok('\u{7f}')
+        );
     }
 
     #[test]
@@ -952,4 +1357,544 @@ mod tests {
         let loaded = crate::from_str(&json).expect("re-load ok enum");
         assert_eq!(full_list, loaded);
     }
+
+    #[test]
+    fn error_category() {
+        use crate::Category;
+
+        struct Unserializable;
+
+        impl serde::Serialize for Unserializable {
+            fn serialize<S: serde::Serializer>(&self, _serializer: S) -> Result<S::Ok, S::Error> {
+                Err(serde::ser::Error::custom("cannot be serialized"))
+            }
+        }
+
+        let err = to_string(&Unserializable).unwrap_err();
+        assert_eq!(err.classify(), Category::Data);
+    }
+
+    #[test]
+    fn map() {
+        use super::{to_string_pretty, Error};
+        use std::collections::BTreeMap;
+
+        let mut balances = BTreeMap::new();
+        assert_eq!(to_string(&balances).unwrap(), "{}");
+        balances.insert("uatom", 1u64);
+        balances.insert("e\"scaped", 2);
+        assert_eq!(
+            to_string(&balances).unwrap(),
+            r#"{"e\"scaped":2,"uatom":1}"#
+        );
+        assert_eq!(
+            to_string_pretty(&balances).unwrap(),
+            "{\n  \"e\\\"scaped\": 2,\n  \"uatom\": 1\n}"
+        );
+
+        let mut nested = BTreeMap::new();
+        nested.insert("a".to_string(), vec![balances.clone(), BTreeMap::new()]);
+        assert_eq!(
+            to_string(&nested).unwrap(),
+            r#"{"a":[{"e\"scaped":2,"uatom":1},{}]}"#
+        );
+
+        /// Serializes the even numbers of `0..n` as keys, so the length isn't known up front
+        struct Evens(u8);
+
+        impl serde::Serialize for Evens {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                use serde::ser::SerializeMap;
+
+                let mut map = serializer.serialize_map(None)?;
+                for n in (0..self.0).filter(|n| n % 2 == 0) {
+                    map.serialize_key(&format_args!("{}", n))?;
+                    map.serialize_value(&n)?;
+                }
+                map.end()
+            }
+        }
+
+        assert_eq!(to_string(&Evens(5)).unwrap(), r#"{"0":0,"2":2,"4":4}"#);
+        assert_eq!(to_string(&Evens(0)).unwrap(), "{}");
+
+        let mut by_id = BTreeMap::new();
+        by_id.insert(42u64, "a");
+        by_id.insert(7, "b");
+        assert_eq!(to_string(&by_id).unwrap(), r#"{"7":"b","42":"a"}"#);
+        let mut by_offset = BTreeMap::new();
+        by_offset.insert(-1i8, ());
+        by_offset.insert(std::i8::MIN, ());
+        assert_eq!(to_string(&by_offset).unwrap(), r#"{"-128":null,"-1":null}"#);
+        let mut by_balance = BTreeMap::new();
+        by_balance.insert(std::u128::MAX, ());
+        assert_eq!(
+            to_string(&by_balance).unwrap(),
+            r#"{"340282366920938463463374607431768211455":null}"#
+        );
+        let mut by_flag = BTreeMap::new();
+        by_flag.insert(false, 0u8);
+        by_flag.insert(true, 1);
+        assert_eq!(to_string(&by_flag).unwrap(), r#"{"false":0,"true":1}"#);
+        let mut by_char = BTreeMap::new();
+        by_char.insert('"', 0u8);
+        by_char.insert('é', 1);
+        assert_eq!(to_string(&by_char).unwrap(), r#"{"\"":0,"é":1}"#);
+        assert_eq!(
+            to_string_pretty(&by_id).unwrap(),
+            serde_json::to_string_pretty(&by_id).unwrap()
+        );
+        // and back
+        assert_eq!(
+            crate::from_str::<BTreeMap<u64, String>>(&to_string(&by_id).unwrap()).unwrap()[&42],
+            "a"
+        );
+
+        #[derive(Serialize, PartialEq, Eq, PartialOrd, Ord)]
+        struct Addr(&'static str);
+        #[derive(Serialize, PartialEq, Eq, PartialOrd, Ord)]
+        struct TokenId(u64);
+        #[derive(Serialize, PartialEq, Eq, PartialOrd, Ord)]
+        #[serde(rename_all = "snake_case")]
+        enum Side {
+            Buy,
+            Sell,
+        }
+        #[derive(Serialize, PartialEq, Eq, PartialOrd, Ord)]
+        enum Ref {
+            Token(TokenId),
+        }
+
+        let mut by_addr = BTreeMap::new();
+        by_addr.insert(Addr("alice"), 1u8);
+        assert_eq!(to_string(&by_addr).unwrap(), r#"{"alice":1}"#);
+        let mut by_token = BTreeMap::new();
+        by_token.insert(TokenId(42), 1u8);
+        assert_eq!(to_string(&by_token).unwrap(), r#"{"42":1}"#);
+        let mut by_side = BTreeMap::new();
+        by_side.insert(Side::Buy, 1u8);
+        by_side.insert(Side::Sell, 2);
+        assert_eq!(to_string(&by_side).unwrap(), r#"{"buy":1,"sell":2}"#);
+        assert_eq!(
+            to_string(&by_side).unwrap(),
+            serde_json::to_string(&by_side).unwrap()
+        );
+        let mut by_ref = BTreeMap::new();
+        by_ref.insert(Ref::Token(TokenId(1)), 1u8);
+        assert!(to_string(&by_ref).is_err());
+
+        let mut by_list = BTreeMap::new();
+        by_list.insert(vec![1u8], 1u8);
+        let err = to_string(&by_list).unwrap_err();
+        assert!(matches_key_error(&err));
+        assert_eq!(err.to_string(), "Map key must be a string");
+
+        fn matches_key_error(err: &Error) -> bool {
+            match err {
+                Error::KeyMustBeAString => true,
+                _ => false,
+            }
+        }
+    }
+
+    #[test]
+    fn flatten() {
+        use super::to_string_pretty;
+        use std::collections::BTreeMap;
+
+        #[derive(Serialize)]
+        struct Coin {
+            denom: &'static str,
+            amount: u64,
+        }
+
+        #[derive(Serialize)]
+        struct Pair(u8, u8);
+
+        #[derive(Serialize)]
+        #[serde(rename_all = "snake_case")]
+        enum Expiration {
+            AtHeight(u64),
+            AtTime { seconds: u64 },
+            Range(Pair),
+            Never,
+        }
+
+        #[derive(Serialize)]
+        struct AllowanceInfo {
+            spender: &'static str,
+            #[serde(flatten)]
+            allowance: Coin,
+            #[serde(flatten)]
+            expires: Option<Expiration>,
+            #[serde(flatten)]
+            extra: BTreeMap<&'static str, u8>,
+        }
+
+        let mut extra = BTreeMap::new();
+        extra.insert("version", 2);
+        let info = |expires| AllowanceInfo {
+            spender: "bob",
+            allowance: Coin {
+                denom: "uatom",
+                amount: 10,
+            },
+            expires,
+            extra: extra.clone(),
+        };
+
+        assert_eq!(
+            to_string(&info(None)).unwrap(),
+            r#"{"spender":"bob","denom":"uatom","amount":10,"version":2}"#
+        );
+        assert_eq!(
+            to_string(&info(Some(Expiration::AtHeight(5)))).unwrap(),
+            r#"{"spender":"bob","denom":"uatom","amount":10,"at_height":5,"version":2}"#
+        );
+        assert_eq!(
+            to_string(&info(Some(Expiration::AtTime { seconds: 7 }))).unwrap(),
+            r#"{"spender":"bob","denom":"uatom","amount":10,"at_time":{"seconds":7},"version":2}"#
+        );
+        for expires in vec![
+            None,
+            Some(Expiration::AtHeight(5)),
+            Some(Expiration::AtTime { seconds: 7 }),
+            Some(Expiration::Range(Pair(1, 2))),
+        ] {
+            let info = info(expires);
+            assert_eq!(
+                to_string(&info).unwrap(),
+                serde_json::to_string(&info).unwrap()
+            );
+            assert_eq!(
+                to_string_pretty(&info).unwrap(),
+                serde_json::to_string_pretty(&info).unwrap()
+            );
+        }
+        // whether a unit variant can be flattened depends on the serde version, but the result
+        // is the same as with serde_json
+        let never = info(Some(Expiration::Never));
+        assert_eq!(to_string(&never).ok(), serde_json::to_string(&never).ok());
+    }
+
+    #[test]
+    fn json_seq() {
+        use super::to_json_seq;
+
+        assert_eq!(
+            to_json_seq(&[vec![1u8], vec![2, 3]]).unwrap(),
+            b"\x1e[1]\n\x1e[2,3]\n"
+        );
+        assert_eq!(to_json_seq(Vec::<u8>::new()).unwrap(), b"");
+
+        let values: Result<Vec<u8>, _> =
+            crate::de::json_seq(&to_json_seq(&[1u8, 2, 3]).unwrap()).collect();
+        assert_eq!(values, Ok(vec![1, 2, 3]));
+    }
+
+    #[test]
+    fn pretty() {
+        use super::{to_string_pretty, to_string_with_indent, to_vec_pretty, to_vec_with_indent};
+
+        #[derive(Serialize)]
+        struct Empty {}
+
+        #[derive(Serialize)]
+        enum Msg {
+            Send { to: &'static str, amounts: Vec<u8> },
+            Burn(u8),
+            Swap(u8, u8),
+            Stop,
+        }
+
+        assert_eq!(
+            to_string_pretty(&Msg::Send {
+                to: "alice",
+                amounts: vec![1, 2],
+            })
+            .unwrap(),
+            r#"{
+  "Send": {
+    "to": "alice",
+    "amounts": [
+      1,
+      2
+    ]
+  }
+}"#
+        );
+        assert_eq!(
+            to_string_pretty(&[Msg::Burn(1), Msg::Swap(2, 3), Msg::Stop]).unwrap(),
+            r#"[
+  {
+    "Burn": 1
+  },
+  {
+    "Swap": [
+      2,
+      3
+    ]
+  },
+  "Stop"
+]"#
+        );
+        assert_eq!(
+            to_string_pretty(&(Vec::<u8>::new(), Empty {})).unwrap(),
+            "[\n  [],\n  {}\n]"
+        );
+        assert_eq!(to_vec_pretty(&1u8).unwrap(), b"1");
+
+        let value = ([1u8], Empty {});
+        assert_eq!(
+            to_string_with_indent(&value, "\t").unwrap(),
+            "[\n\t[\n\t\t1\n\t],\n\t{}\n]"
+        );
+        assert_eq!(
+            to_vec_with_indent(&value, b"    ").unwrap(),
+            b"[\n    [\n        1\n    ],\n    {}\n]"
+        );
+        assert_eq!(
+            to_string_with_indent(&value, "").unwrap(),
+            "[\n[\n1\n],\n{}\n]"
+        );
+
+        // the same as serde_json
+        let value = (
+            Msg::Send {
+                to: "bob",
+                amounts: vec![],
+            },
+            [Some(true), None],
+        );
+        assert_eq!(
+            to_string_pretty(&value).unwrap(),
+            serde_json::to_string_pretty(&value).unwrap()
+        );
+    }
+
+    #[test]
+    fn formatter() {
+        use super::{to_vec_with_formatter, CharEscape, Formatter};
+
+        /// One line with spaces after commas and colons, `\u` escapes only and hex integers
+        struct Spaced;
+
+        impl Formatter for Spaced {
+            fn write_number_str(&mut self, out: &mut Vec<u8>, value: &str) {
+                let value: i64 = value.parse().unwrap();
+                out.extend_from_slice(format!("{:#x}", value).as_bytes());
+            }
+
+            fn write_char_escape(&mut self, out: &mut Vec<u8>, escape: CharEscape) {
+                let c = match escape {
+                    CharEscape::Quote => b'"',
+                    CharEscape::ReverseSolidus => b'\\',
+                    CharEscape::Backspace => 0x08,
+                    CharEscape::Tab => b'\t',
+                    CharEscape::LineFeed => b'\n',
+                    CharEscape::FormFeed => 0x0C,
+                    CharEscape::CarriageReturn => b'\r',
+                    CharEscape::AsciiControl(c) => c,
+                };
+                out.extend_from_slice(format!("\\u{:04x}", c).as_bytes());
+            }
+
+            fn begin_array_value(&mut self, out: &mut Vec<u8>, first: bool) {
+                if !first {
+                    out.extend_from_slice(b", ");
+                }
+            }
+
+            fn begin_object_key(&mut self, out: &mut Vec<u8>, first: bool) {
+                if !first {
+                    out.extend_from_slice(b", ");
+                }
+            }
+
+            fn begin_object_value(&mut self, out: &mut Vec<u8>) {
+                out.extend_from_slice(b": ");
+            }
+        }
+
+        #[derive(Serialize)]
+        enum Msg {
+            Send { to: &'static str, amounts: [u8; 2] },
+        }
+
+        let msg = Msg::Send {
+            to: "a\"b\n",
+            amounts: [10, 255],
+        };
+        assert_eq!(
+            String::from_utf8(to_vec_with_formatter(&(msg, None::<u8>, true), Spaced).unwrap())
+                .unwrap(),
+            r#"[{"Send": {"to": "a\u0022b\u000a", "amounts": [0xa, 0xff]}}, null, true]"#
+        );
+    }
+
+    #[test]
+    fn bytes() {
+        let bytes = ByteBuf::from(vec![0, 1, 255]);
+        #[cfg(not(feature = "base64"))]
+        assert_eq!(to_string(&bytes).unwrap(), "[0,1,255]");
+        #[cfg(feature = "base64")]
+        assert_eq!(to_string(&bytes).unwrap(), r#""AAH/""#);
+        assert_eq!(crate::from_str(&to_string(&bytes).unwrap()), Ok(bytes));
+        assert_eq!(to_string(&ByteBuf::from(vec![])).unwrap().len(), 2);
+
+        // arrays of numbers written by serde_json are accepted in any case
+        let json = serde_json::to_string(&serde_json::json!([0, 1, 255])).unwrap();
+        assert_eq!(crate::from_str(&json), Ok(ByteBuf::from(vec![0, 1, 255])));
+    }
+
+    #[test]
+    fn bytes_hex() {
+        use super::{to_string_with_options, Options};
+
+        let options = Options::default().hex_bytes(true);
+        let bytes = ByteBuf::from(vec![0x00, 0x0f, 0xa0, 0xff]);
+        assert_eq!(
+            to_string_with_options(&bytes, &options).unwrap(),
+            r#""000fa0ff""#
+        );
+        assert_eq!(
+            to_string_with_options(&ByteBuf::from(vec![]), &options).unwrap(),
+            r#""""#
+        );
+        assert_eq!(
+            to_string_with_options(&[bytes], &options).unwrap(),
+            r#"["000fa0ff"]"#
+        );
+
+        let json = to_string_with_options(&ByteBuf::from(vec![1, 2, 255]), &options).unwrap();
+        let de_options = crate::de::Options::default().hex_bytes(true);
+        assert_eq!(
+            crate::de::from_str_with_options(&json, &de_options),
+            Ok(ByteBuf::from(vec![1, 2, 255]))
+        );
+    }
+
+    #[test]
+    fn bytes_array() {
+        use super::{to_string_pretty, to_string_with_options, Options};
+
+        let options = Options::default().byte_arrays(true);
+        let bytes = ByteBuf::from(vec![1, 2, 255]);
+        assert_eq!(
+            to_string_with_options(&bytes, &options).unwrap(),
+            "[1,2,255]"
+        );
+        assert_eq!(
+            to_string_with_options(&ByteBuf::from(vec![]), &options).unwrap(),
+            "[]"
+        );
+        assert_eq!(
+            to_string_with_options(&bytes, &options.clone().hex_bytes(true)).unwrap(),
+            r#""0102ff""#
+        );
+
+        // the same as serde_json, which writes bytes as arrays
+        assert_eq!(
+            to_string_with_options(&bytes, &options).unwrap(),
+            serde_json::to_string(&bytes).unwrap()
+        );
+        #[cfg(not(feature = "base64"))]
+        assert_eq!(
+            to_string_pretty(&bytes).unwrap(),
+            serde_json::to_string_pretty(&bytes).unwrap()
+        );
+        #[cfg(feature = "base64")]
+        assert_eq!(to_string_pretty(&bytes).unwrap(), r#""AQL/""#);
+    }
+
+    #[test]
+    fn sort_keys() {
+        use super::{
+            to_string_with_options, to_vec_with_formatter_and_options, Options, PrettyFormatter,
+        };
+        use std::collections::{BTreeMap, HashMap};
+
+        #[derive(Serialize)]
+        struct Inner {
+            z: u8,
+            a: u8,
+        }
+
+        #[derive(Serialize)]
+        struct Outer {
+            name: &'static str,
+            inner: Inner,
+            #[serde(flatten)]
+            extra: BTreeMap<&'static str, u8>,
+            list: [Inner; 1],
+            empty: BTreeMap<u8, u8>,
+        }
+
+        let options = Options::default().sort_keys(true);
+        let mut extra = BTreeMap::new();
+        extra.insert("b", 1);
+        extra.insert("y", 2);
+        let value = Outer {
+            name: "outer",
+            inner: Inner { z: 1, a: 2 },
+            extra,
+            list: [Inner { z: 3, a: 4 }],
+            empty: BTreeMap::new(),
+        };
+        let expected = r#"{"b":1,"empty":{},"inner":{"a":2,"z":1},"list":[{"a":4,"z":3}],"name":"outer","y":2}"#;
+        assert_eq!(to_string_with_options(&value, &options).unwrap(), expected);
+        // unsorted by default
+        assert_eq!(
+            to_string(&Inner { z: 1, a: 2 }).unwrap(),
+            r#"{"z":1,"a":2}"#
+        );
+
+        // the same order as the keys of a `serde_json::Value`
+        let json = serde_json::to_value(&value).unwrap();
+        assert_eq!(to_string_with_options(&json, &options).unwrap(), expected);
+        assert_eq!(serde_json::to_string(&json).unwrap(), expected);
+
+        // independent of the iteration order of maps, comparing the unescaped keys
+        let mut map = HashMap::new();
+        for key in &["b", "a\"", "a#", "\u{e9}", "a", "B", "10", "9"] {
+            map.insert(*key, key.len());
+        }
+        assert_eq!(
+            to_string_with_options(&map, &options).unwrap(),
+            r#"{"10":2,"9":1,"B":1,"a":1,"a\"":2,"a#":2,"b":1,"é":2}"#
+        );
+
+        // keeps the layout of the formatter
+        let pretty =
+            to_vec_with_formatter_and_options(&value, PrettyFormatter::new(), &options).unwrap();
+        assert_eq!(
+            std::str::from_utf8(&pretty).unwrap(),
+            serde_json::to_string_pretty(&json).unwrap()
+        );
+    }
+
+    #[cfg(feature = "base64")]
+    #[test]
+    fn bytes_base64() {
+        use serde_bytes::Bytes;
+
+        /// Like CosmWasm `Binary`
+        #[derive(Serialize)]
+        struct Msg {
+            data: ByteBuf,
+        }
+
+        assert_eq!(to_string(&Bytes::new(b"")).unwrap(), r#""""#);
+        assert_eq!(to_string(&Bytes::new(b"f")).unwrap(), r#""Zg==""#);
+        assert_eq!(to_string(&Bytes::new(b"fo")).unwrap(), r#""Zm8=""#);
+        assert_eq!(to_string(&Bytes::new(b"foo")).unwrap(), r#""Zm9v""#);
+        assert_eq!(to_string(&Bytes::new(&[0xfb, 0xff])).unwrap(), r#""+/8=""#);
+        assert_eq!(
+            to_string(&Msg {
+                data: ByteBuf::from(br#"{"send":{}}"#.to_vec())
+            })
+            .unwrap(),
+            r#"{"data":"eyJzZW5kIjp7fX0="}"#
+        );
+    }
 }
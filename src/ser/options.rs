@@ -0,0 +1,60 @@
+/// Settings that control how JSON is written
+///
+/// Use [`Options::default`] and the builder methods to create one, then pass it to
+/// [`to_vec_with_options`](crate::ser::to_vec_with_options), or to
+/// [`to_vec_with_formatter_and_options`](crate::ser::to_vec_with_formatter_and_options) together
+/// with a [`Formatter`](crate::ser::Formatter) like the
+/// [`PrettyFormatter`](crate::ser::PrettyFormatter).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Options {
+    /// Encode bytes as strings of lowercase hexadecimal digits, like `"deadbeef"`, as used for
+    /// hashes and addresses. This replaces base64 strings with the `base64` feature. Off by
+    /// default.
+    pub hex_bytes: bool,
+
+    /// Encode bytes as arrays of numbers, like `[1,2,255]`, even with the `base64` feature, so
+    /// the output is the same as serde_json's. [`Options::hex_bytes`] takes precedence. Off by
+    /// default.
+    pub byte_arrays: bool,
+
+    /// Write the members of structs and maps sorted by key, for output that doesn't depend on
+    /// the order of fields or the iteration order of maps, as needed for signed payloads or
+    /// state hashes. Keys are compared by their UTF-8 bytes, like the keys of a
+    /// `serde_json::Value`. Off by default.
+    pub sort_keys: bool,
+
+    /// Write `i128` and `u128` values as strings, like `"340282366920938463463374607431768211455"`,
+    /// as CosmWasm's `Uint128` does. JavaScript numbers lose precision above 2^53, so this keeps
+    /// 128-bit amounts exact for JavaScript clients. All 128-bit values are quoted, whatever their
+    /// size, so the JSON type of a field doesn't depend on its value. They can be read back with
+    /// [`de::Options::allow_quoted_integers`](crate::de::Options::allow_quoted_integers). Off by
+    /// default.
+    pub quote_large_integers: bool,
+}
+
+impl Options {
+    /// Sets whether bytes are encoded as hex strings
+    pub fn hex_bytes(mut self, hex: bool) -> Self {
+        self.hex_bytes = hex;
+        self
+    }
+
+    /// Sets whether bytes are encoded as arrays of numbers
+    pub fn byte_arrays(mut self, arrays: bool) -> Self {
+        self.byte_arrays = arrays;
+        self
+    }
+
+    /// Sets whether the members of objects are sorted by key
+    pub fn sort_keys(mut self, sort: bool) -> Self {
+        self.sort_keys = sort;
+        self
+    }
+
+    /// Sets whether `i128` and `u128` values are written as strings
+    pub fn quote_large_integers(mut self, quote: bool) -> Self {
+        self.quote_large_integers = quote;
+        self
+    }
+}
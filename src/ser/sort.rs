@@ -0,0 +1,84 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+
+/// Where a member of an object lies in the output
+#[derive(Clone, Copy)]
+struct Member {
+    /// Start of the separator in front of the member
+    start: usize,
+    key_start: usize,
+    key_end: usize,
+    end: usize,
+}
+
+/// The members of an object as they are written, recorded to sort them by key with
+/// [`Options::sort_keys`](crate::ser::Options::sort_keys)
+#[derive(Default)]
+pub(crate) struct Members(Vec<Member>);
+
+impl Members {
+    /// Records that a member starts at `start` and its key at `key_start`
+    pub fn begin_key(&mut self, start: usize, key_start: usize) {
+        self.0.push(Member {
+            start,
+            key_start,
+            key_end: key_start,
+            end: key_start,
+        });
+    }
+
+    /// Records that the key of the current member ends at `key_end`
+    pub fn end_key(&mut self, key_end: usize) {
+        if let Some(member) = self.0.last_mut() {
+            member.key_end = key_end;
+        }
+    }
+
+    /// Records that the current member ends at `end`
+    pub fn end_value(&mut self, end: usize) {
+        if let Some(member) = self.0.last_mut() {
+            member.end = end;
+        }
+    }
+
+    /// Reorders the members at the end of `buf` by key
+    ///
+    /// The members keep their text, only the separators in front of the first and the other
+    /// members stay where they are. Members with equal keys keep their order.
+    pub fn sort(&self, buf: &mut Vec<u8>) {
+        if self.0.len() < 2 {
+            return;
+        }
+        let first_separator = self.0[0].start..self.0[0].key_start;
+        let separator = self.0[1].start..self.0[1].key_start;
+
+        let mut members: Vec<(Vec<u8>, Member)> = self
+            .0
+            .iter()
+            .map(|member| (key(&buf[member.key_start..member.key_end]), *member))
+            .collect();
+        members.sort_by(|a, b| a.0.cmp(&b.0));
+
+        let start = first_separator.start;
+        let mut sorted = Vec::with_capacity(buf.len() - start);
+        for (i, (_, member)) in members.iter().enumerate() {
+            let separator = if i == 0 {
+                first_separator.clone()
+            } else {
+                separator.clone()
+            };
+            sorted.extend_from_slice(&buf[separator]);
+            sorted.extend_from_slice(&buf[member.key_start..member.end]);
+        }
+        buf.truncate(start);
+        buf.extend_from_slice(&sorted);
+    }
+}
+
+/// Returns the UTF-8 bytes of the key written as `text`, which is compared to order the members
+fn key(text: &[u8]) -> Vec<u8> {
+    // a custom formatter may not write the key as a JSON string, so fall back to its text
+    crate::de::from_slice::<String>(text)
+        .map(String::into_bytes)
+        .unwrap_or_else(|_| text.to_vec())
+}
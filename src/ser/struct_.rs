@@ -1,75 +1,71 @@
 use serde::ser;
 
-use crate::ser::{Error, Result, Serializer};
+use crate::ser::{Error, Formatter, Members, Result, Serializer};
 
-pub struct SerializeStruct<'a> {
-    de: &'a mut Serializer,
+pub struct SerializeStruct<'a, F> {
+    de: &'a mut Serializer<F>,
     first: bool,
+    members: Option<Members>,
 }
 
-impl<'a> SerializeStruct<'a> {
-    pub(crate) fn new(de: &'a mut Serializer) -> Self {
-        SerializeStruct { de, first: true }
+impl<'a, F: Formatter> SerializeStruct<'a, F> {
+    pub(crate) fn new(de: &'a mut Serializer<F>) -> Self {
+        let members = de.members();
+        SerializeStruct {
+            de,
+            first: true,
+            members,
+        }
     }
 }
 
-impl<'a> ser::SerializeStruct for SerializeStruct<'a> {
+impl<'a, F: Formatter> ser::SerializeStruct for SerializeStruct<'a, F> {
     type Ok = ();
     type Error = Error;
 
-    fn serialize_field<T: ?Sized>(&mut self, key: &'static str, value: &T) -> Result<()>
+    fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> Result<()>
     where
-        T: ser::Serialize,
+        T: ser::Serialize + ?Sized,
     {
         // XXX if `value` is `None` we not produce any output for this field
-        if !self.first {
-            self.de.buf.push(b',');
-        }
+        self.de.write_field_key(&mut self.members, self.first, key);
         self.first = false;
 
-        self.de.buf.push(b'"');
-        self.de.buf.extend_from_slice(key.as_bytes());
-        self.de.buf.extend_from_slice(b"\":");
-
         value.serialize(&mut *self.de)?;
+        self.de.end_value(&mut self.members);
 
         Ok(())
     }
 
     fn end(self) -> Result<Self::Ok> {
-        self.de.buf.push(b'}');
+        self.de.end_object(self.members);
         Ok(())
     }
 }
 
-impl<'a> ser::SerializeStructVariant for SerializeStruct<'a> {
+impl<'a, F: Formatter> ser::SerializeStructVariant for SerializeStruct<'a, F> {
     type Ok = ();
     type Error = Error;
 
-    fn serialize_field<T: ?Sized>(&mut self, key: &'static str, value: &T) -> Result<()>
+    fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> Result<()>
     where
-        T: ser::Serialize,
+        T: ser::Serialize + ?Sized,
     {
         // XXX if `value` is `None` we not produce any output for this field
-        if !self.first {
-            self.de.buf.push(b',');
-        }
+        self.de.write_field_key(&mut self.members, self.first, key);
         self.first = false;
 
-        self.de.buf.push(b'"');
-        self.de.buf.extend_from_slice(key.as_bytes());
-        self.de.buf.extend_from_slice(b"\":");
-
         value.serialize(&mut *self.de)?;
+        self.de.end_value(&mut self.members);
 
         Ok(())
     }
 
     fn end(self) -> Result<Self::Ok> {
         // close struct
-        self.de.buf.push(b'}');
+        self.de.end_object(self.members);
         // close surrounding enum
-        self.de.buf.push(b'}');
+        self.de.end_variant();
         Ok(())
     }
 }